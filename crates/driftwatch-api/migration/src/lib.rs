@@ -1,6 +1,12 @@
 pub use sea_orm_migration::prelude::*;
 
 mod m20241221_000001_create_driftwatch_tables;
+mod m20261018_000000_create_metric_rollups;
+mod m20261018_000001_add_report_ci_metadata;
+mod m20261018_000002_add_report_idempotency_key;
+mod m20261018_000003_add_report_finalized;
+mod m20261018_000004_add_testbed_fingerprint;
+mod m20261019_000005_add_measure_higher_is_better;
 
 pub struct Migrator;
 
@@ -11,6 +17,18 @@ impl MigratorTrait for Migrator {
         migrations.push(Box::new(
             m20241221_000001_create_driftwatch_tables::Migration,
         ));
+        migrations.push(Box::new(m20261018_000000_create_metric_rollups::Migration));
+        migrations.push(Box::new(m20261018_000001_add_report_ci_metadata::Migration));
+        migrations.push(Box::new(
+            m20261018_000002_add_report_idempotency_key::Migration,
+        ));
+        migrations.push(Box::new(m20261018_000003_add_report_finalized::Migration));
+        migrations.push(Box::new(
+            m20261018_000004_add_testbed_fingerprint::Migration,
+        ));
+        migrations.push(Box::new(
+            m20261019_000005_add_measure_higher_is_better::Migration,
+        ));
         migrations
     }
 }
@@ -0,0 +1,107 @@
+use sea_orm_migration::{prelude::*, schema::*};
+
+use super::m20241221_000001_create_driftwatch_tables::{
+    Benchmarks, Branches, Measures, Projects, Testbeds,
+};
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_table(
+                Table::create()
+                    .table(MetricRollups::Table)
+                    .if_not_exists()
+                    .col(uuid(MetricRollups::Id).primary_key())
+                    .col(uuid(MetricRollups::ProjectId).not_null())
+                    .col(uuid(MetricRollups::BranchId).not_null())
+                    .col(uuid(MetricRollups::TestbedId).not_null())
+                    .col(uuid(MetricRollups::BenchmarkId).not_null())
+                    .col(uuid(MetricRollups::MeasureId).not_null())
+                    .col(string_len(MetricRollups::Granularity, 16).not_null())
+                    .col(timestamp_with_time_zone(MetricRollups::BucketStart).not_null())
+                    .col(double(MetricRollups::MinValue).not_null())
+                    .col(double(MetricRollups::MaxValue).not_null())
+                    .col(double(MetricRollups::MeanValue).not_null())
+                    .col(double(MetricRollups::MedianValue).not_null())
+                    .col(big_integer(MetricRollups::SampleCount).not_null())
+                    .col(timestamp_with_time_zone(MetricRollups::UpdatedAt).not_null())
+                    .foreign_key(
+                        ForeignKey::create()
+                            .from(MetricRollups::Table, MetricRollups::ProjectId)
+                            .to(Projects::Table, Projects::Id)
+                            .on_delete(ForeignKeyAction::Cascade),
+                    )
+                    .foreign_key(
+                        ForeignKey::create()
+                            .from(MetricRollups::Table, MetricRollups::BranchId)
+                            .to(Branches::Table, Branches::Id)
+                            .on_delete(ForeignKeyAction::Cascade),
+                    )
+                    .foreign_key(
+                        ForeignKey::create()
+                            .from(MetricRollups::Table, MetricRollups::TestbedId)
+                            .to(Testbeds::Table, Testbeds::Id)
+                            .on_delete(ForeignKeyAction::Cascade),
+                    )
+                    .foreign_key(
+                        ForeignKey::create()
+                            .from(MetricRollups::Table, MetricRollups::BenchmarkId)
+                            .to(Benchmarks::Table, Benchmarks::Id)
+                            .on_delete(ForeignKeyAction::Cascade),
+                    )
+                    .foreign_key(
+                        ForeignKey::create()
+                            .from(MetricRollups::Table, MetricRollups::MeasureId)
+                            .to(Measures::Table, Measures::Id)
+                            .on_delete(ForeignKeyAction::Cascade),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx_metric_rollups_series_bucket")
+                    .table(MetricRollups::Table)
+                    .col(MetricRollups::BranchId)
+                    .col(MetricRollups::TestbedId)
+                    .col(MetricRollups::BenchmarkId)
+                    .col(MetricRollups::MeasureId)
+                    .col(MetricRollups::Granularity)
+                    .col(MetricRollups::BucketStart)
+                    .unique()
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(Table::drop().table(MetricRollups::Table).to_owned())
+            .await
+    }
+}
+
+#[derive(DeriveIden)]
+pub enum MetricRollups {
+    Table,
+    Id,
+    ProjectId,
+    BranchId,
+    TestbedId,
+    BenchmarkId,
+    MeasureId,
+    Granularity,
+    BucketStart,
+    MinValue,
+    MaxValue,
+    MeanValue,
+    MedianValue,
+    SampleCount,
+    UpdatedAt,
+}
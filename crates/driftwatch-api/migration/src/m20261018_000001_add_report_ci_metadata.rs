@@ -0,0 +1,42 @@
+use sea_orm_migration::{prelude::*, schema::*};
+
+use super::m20241221_000001_create_driftwatch_tables::Reports;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Reports::Table)
+                    .add_column_if_not_exists(string_null(ReportCi::BaseBranch))
+                    .add_column_if_not_exists(string_null(ReportCi::CiProvider))
+                    .add_column_if_not_exists(string_null(ReportCi::CiRunUrl))
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Reports::Table)
+                    .drop_column(ReportCi::BaseBranch)
+                    .drop_column(ReportCi::CiProvider)
+                    .drop_column(ReportCi::CiRunUrl)
+                    .to_owned(),
+            )
+            .await
+    }
+}
+
+#[derive(DeriveIden)]
+enum ReportCi {
+    BaseBranch,
+    CiProvider,
+    CiRunUrl,
+}
@@ -0,0 +1,58 @@
+use sea_orm_migration::{prelude::*, schema::*};
+
+use super::m20241221_000001_create_driftwatch_tables::Reports;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+const INDEX: &str = "idx_reports_project_idempotency_key";
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Reports::Table)
+                    .add_column_if_not_exists(string_len_null(
+                        ReportIdempotency::IdempotencyKey,
+                        255,
+                    ))
+                    .to_owned(),
+            )
+            .await?;
+
+        // NULL keys never conflict, so reports without one are unaffected
+        manager
+            .create_index(
+                Index::create()
+                    .if_not_exists()
+                    .name(INDEX)
+                    .table(Reports::Table)
+                    .col(Reports::ProjectId)
+                    .col(ReportIdempotency::IdempotencyKey)
+                    .unique()
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_index(Index::drop().name(INDEX).table(Reports::Table).to_owned())
+            .await?;
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Reports::Table)
+                    .drop_column(ReportIdempotency::IdempotencyKey)
+                    .to_owned(),
+            )
+            .await
+    }
+}
+
+#[derive(DeriveIden)]
+enum ReportIdempotency {
+    IdempotencyKey,
+}
@@ -0,0 +1,39 @@
+use sea_orm_migration::{prelude::*, schema::*};
+
+use super::m20241221_000001_create_driftwatch_tables::Measures;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        // Existing measures read as lower is better, like latency
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Measures::Table)
+                    .add_column_if_not_exists(
+                        boolean(MeasureDirection::HigherIsBetter).default(false),
+                    )
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Measures::Table)
+                    .drop_column(MeasureDirection::HigherIsBetter)
+                    .to_owned(),
+            )
+            .await
+    }
+}
+
+#[derive(DeriveIden)]
+enum MeasureDirection {
+    HigherIsBetter,
+}
@@ -0,0 +1,282 @@
+use chrono::Utc;
+use sea_orm::prelude::DateTimeWithTimeZone;
+use sea_orm::{
+    ActiveModelTrait, ColumnTrait, ConnectionTrait, DbBackend, DbErr, EntityTrait, QueryFilter,
+    Set, Statement, Value,
+};
+use std::collections::HashMap;
+use uuid::Uuid;
+
+use crate::entities::{self, alert, metric, report, threshold};
+
+/// Number of most recent values of a series averaged into its baseline.
+pub const BASELINE_WINDOW: u64 = 30;
+
+// Six bind parameters per key keep a query well below Postgres' 65535 limit.
+const BASELINE_CHUNK_SIZE: usize = 1000;
+
+/// A series as of one report: the values of a benchmark and measure on a
+/// branch and testbed, stored in reports created before `before`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct BaselineKey {
+    pub branch_id: Uuid,
+    pub testbed_id: Uuid,
+    pub benchmark_id: Uuid,
+    pub measure_id: Uuid,
+    pub before: DateTimeWithTimeZone,
+}
+
+impl BaselineKey {
+    pub fn new(report: &report::Model, benchmark_id: Uuid, measure_id: Uuid) -> Self {
+        Self {
+            branch_id: report.branch_id,
+            testbed_id: report.testbed_id,
+            benchmark_id,
+            measure_id,
+            before: report.created_at,
+        }
+    }
+}
+
+/// Mean of the latest values of each series, stored in finalized reports
+/// before the key's, and how many values went into it; series without any
+/// earlier value are left out. Each chunk of keys takes one query.
+pub async fn baselines<C: ConnectionTrait>(
+    db: &C,
+    keys: &[BaselineKey],
+) -> Result<HashMap<BaselineKey, (f64, usize)>, DbErr> {
+    let mut baselines = HashMap::with_capacity(keys.len());
+    for chunk in keys.chunks(BASELINE_CHUNK_SIZE) {
+        baselines.extend(baselines_chunk(db, chunk).await?);
+    }
+    Ok(baselines)
+}
+
+async fn baselines_chunk<C: ConnectionTrait>(
+    db: &C,
+    keys: &[BaselineKey],
+) -> Result<HashMap<BaselineKey, (f64, usize)>, DbErr> {
+    let mut rows = Vec::with_capacity(keys.len());
+    let mut values = Vec::with_capacity(keys.len() * 6);
+    for (idx, key) in keys.iter().enumerate() {
+        let n = idx * 6;
+        rows.push(format!(
+            "(${}::int, ${}::uuid, ${}::uuid, ${}::uuid, ${}::uuid, ${}::timestamptz)",
+            n + 1,
+            n + 2,
+            n + 3,
+            n + 4,
+            n + 5,
+            n + 6
+        ));
+        values.extend([
+            Value::from(idx as i32),
+            key.branch_id.into(),
+            key.testbed_id.into(),
+            key.benchmark_id.into(),
+            key.measure_id.into(),
+            key.before.into(),
+        ]);
+    }
+    let sql = format!(
+        r#"
+        SELECT k.idx, AVG(w.value) AS mean, COUNT(w.value) AS samples
+        FROM (VALUES {}) AS k(idx, branch_id, testbed_id, benchmark_id, measure_id, before)
+        CROSS JOIN LATERAL (
+            SELECT m.value
+            FROM metrics m
+            JOIN reports r ON r.id = m.report_id
+            WHERE r.branch_id = k.branch_id
+              AND r.testbed_id = k.testbed_id
+              AND r.finalized
+              AND r.created_at < k.before
+              AND m.benchmark_id = k.benchmark_id
+              AND m.measure_id = k.measure_id
+            ORDER BY r.created_at DESC
+            LIMIT {}
+        ) w
+        GROUP BY k.idx
+        "#,
+        rows.join(", "),
+        BASELINE_WINDOW
+    );
+
+    let rows = db
+        .query_all(Statement::from_sql_and_values(
+            DbBackend::Postgres,
+            sql,
+            values,
+        ))
+        .await?;
+    let mut baselines = HashMap::with_capacity(rows.len());
+    for row in rows {
+        let idx: i32 = row.try_get("", "idx")?;
+        let mean: f64 = row.try_get("", "mean")?;
+        let samples: i64 = row.try_get("", "samples")?;
+        baselines.insert(keys[idx as usize], (mean, samples as usize));
+    }
+    Ok(baselines)
+}
+
+/// Relative change from `baseline` to `current` in percent, `None` for a
+/// zero baseline.
+pub fn percent_change(baseline: f64, current: f64) -> Option<f64> {
+    (baseline != 0.0).then(|| (current - baseline) / baseline.abs() * 100.0)
+}
+
+/// Whether `percent_change` is outside the threshold's boundaries, which are
+/// the allowed increase and decrease in percent.
+pub fn breaches(threshold: &threshold::Model, percent_change: f64) -> bool {
+    threshold
+        .upper_boundary
+        .is_some_and(|upper| percent_change > upper)
+        || threshold
+            .lower_boundary
+            .is_some_and(|lower| percent_change < -lower.abs())
+}
+
+fn applies_to(threshold: &threshold::Model, report: &report::Model, measure_id: Uuid) -> bool {
+    threshold.measure_id == measure_id
+        && threshold.branch_id.is_none_or(|id| id == report.branch_id)
+        && threshold
+            .testbed_id
+            .is_none_or(|id| id == report.testbed_id)
+}
+
+/// Compares every metric of a report being finalized against its baseline and
+/// raises an alert for each threshold it breaches.
+pub async fn evaluate_report<C: ConnectionTrait>(
+    db: &C,
+    report: &report::Model,
+    metrics: &[metric::Model],
+) -> Result<Vec<alert::Model>, DbErr> {
+    let thresholds = entities::Threshold::find()
+        .filter(threshold::Column::ProjectId.eq(report.project_id))
+        .all(db)
+        .await?;
+
+    let mut alerts = Vec::new();
+    if thresholds.is_empty() {
+        return Ok(alerts);
+    }
+
+    let checked: Vec<_> = metrics
+        .iter()
+        .filter(|m| {
+            thresholds
+                .iter()
+                .any(|t| applies_to(t, report, m.measure_id))
+        })
+        .collect();
+    let keys: Vec<_> = checked
+        .iter()
+        .map(|m| BaselineKey::new(report, m.benchmark_id, m.measure_id))
+        .collect();
+    let baselines = baselines(db, &keys).await?;
+
+    let now = Utc::now().fixed_offset();
+    for (metric, key) in checked.into_iter().zip(&keys) {
+        let Some(&(baseline, samples)) = baselines.get(key) else {
+            continue;
+        };
+        let applicable = thresholds
+            .iter()
+            .filter(|t| applies_to(t, report, metric.measure_id));
+        let Some(change) = percent_change(baseline, metric.value) else {
+            continue;
+        };
+
+        for threshold in applicable {
+            if samples < threshold.min_sample_size.max(0) as usize || !breaches(threshold, change) {
+                continue;
+            }
+            let alert = alert::ActiveModel {
+                id: Set(Uuid::new_v4()),
+                threshold_id: Set(threshold.id),
+                metric_id: Set(metric.id),
+                status: Set(alert::AlertStatus::Active),
+                percent_change: Set(change),
+                baseline_value: Set(baseline),
+                current_value: Set(metric.value),
+                created_at: Set(now),
+                updated_at: Set(now),
+            }
+            .insert(db)
+            .await?;
+            alerts.push(alert);
+        }
+    }
+
+    Ok(alerts)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn threshold(upper: Option<f64>, lower: Option<f64>) -> threshold::Model {
+        let now = Utc::now().fixed_offset();
+        threshold::Model {
+            id: Uuid::new_v4(),
+            project_id: Uuid::new_v4(),
+            measure_id: Uuid::new_v4(),
+            branch_id: None,
+            testbed_id: None,
+            upper_boundary: upper,
+            lower_boundary: lower,
+            min_sample_size: 2,
+            created_at: now,
+            updated_at: now,
+        }
+    }
+
+    #[test]
+    fn test_percent_change() {
+        assert_eq!(percent_change(100.0, 110.0), Some(10.0));
+        assert_eq!(percent_change(100.0, 75.0), Some(-25.0));
+        assert_eq!(percent_change(-10.0, -5.0), Some(50.0));
+        assert_eq!(percent_change(0.0, 5.0), None);
+    }
+
+    #[test]
+    fn test_breaches() {
+        let upper = threshold(Some(5.0), None);
+        assert!(breaches(&upper, 5.1));
+        assert!(!breaches(&upper, 5.0));
+        assert!(!breaches(&upper, -50.0));
+
+        // The lower boundary is a magnitude whichever sign it is stored with
+        for lower in [threshold(None, Some(10.0)), threshold(None, Some(-10.0))] {
+            assert!(breaches(&lower, -10.5));
+            assert!(!breaches(&lower, -9.0));
+            assert!(!breaches(&lower, 50.0));
+        }
+    }
+
+    #[test]
+    fn test_applies_to() {
+        let now = Utc::now().fixed_offset();
+        let report = report::Model {
+            id: Uuid::new_v4(),
+            project_id: Uuid::new_v4(),
+            branch_id: Uuid::new_v4(),
+            testbed_id: Uuid::new_v4(),
+            git_hash: None,
+            pr_number: None,
+            base_branch: None,
+            ci_provider: None,
+            ci_run_url: None,
+            idempotency_key: None,
+            finalized: true,
+            created_at: now,
+        };
+        let mut t = threshold(Some(5.0), None);
+        assert!(applies_to(&t, &report, t.measure_id));
+        assert!(!applies_to(&t, &report, Uuid::new_v4()));
+
+        t.branch_id = Some(report.branch_id);
+        assert!(applies_to(&t, &report, t.measure_id));
+        t.testbed_id = Some(Uuid::new_v4());
+        assert!(!applies_to(&t, &report, t.measure_id));
+    }
+}
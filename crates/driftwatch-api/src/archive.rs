@@ -0,0 +1,320 @@
+use std::collections::HashMap;
+
+use chrono::Utc;
+use sea_orm::{
+    ActiveModelTrait, ColumnTrait, ConnectionTrait, DbErr, EntityTrait, IntoActiveModel,
+    QueryFilter, Set,
+};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use crate::entities::{
+    self, alert, benchmark, branch, flamegraph, measure, metric, metric_rollup, project, report,
+    testbed, threshold,
+};
+
+/// Bumped whenever the archive layout changes. Version 2 added the CI
+/// details, idempotency key and `finalized` flag of reports and testbed
+/// fingerprints and the direction of measures; version 1 archives are read
+/// with those unset, every report finalized and every measure lower is
+/// better.
+pub const ARCHIVE_VERSION: u32 = 2;
+
+// Keeps each multi-row INSERT well below Postgres' 65535 bind parameter limit.
+const INSERT_CHUNK_SIZE: usize = 1000;
+
+/// Everything needed to recreate a project on another server.
+///
+/// Rows keep their original UUIDs; `import` assigns fresh ones and remaps
+/// every reference. Flamegraph entries carry metadata only, the SVGs stay in
+/// storage under their original paths.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ProjectArchive {
+    pub version: u32,
+    pub exported_at: chrono::DateTime<Utc>,
+    pub project: ProjectRecord,
+    pub branches: Vec<branch::Model>,
+    pub testbeds: Vec<testbed::Model>,
+    pub measures: Vec<measure::Model>,
+    pub benchmarks: Vec<benchmark::Model>,
+    pub reports: Vec<report::Model>,
+    pub metrics: Vec<metric::Model>,
+    pub thresholds: Vec<threshold::Model>,
+    pub alerts: Vec<alert::Model>,
+    pub flamegraphs: Vec<flamegraph::Model>,
+    #[serde(default)]
+    pub rollups: Vec<metric_rollup::Model>,
+}
+
+/// Project settings carried by an archive. The GitHub token is deliberately
+/// left out so archives can be stored as offline backups.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ProjectRecord {
+    pub name: String,
+    pub description: Option<String>,
+    pub public: bool,
+    pub github_repo: Option<String>,
+    pub github_pr_comments: bool,
+    pub github_status_checks: bool,
+}
+
+pub async fn export<C: ConnectionTrait>(
+    db: &C,
+    project: &project::Model,
+) -> Result<ProjectArchive, DbErr> {
+    let project_id = project.id;
+
+    Ok(ProjectArchive {
+        version: ARCHIVE_VERSION,
+        exported_at: Utc::now(),
+        project: ProjectRecord {
+            name: project.name.clone(),
+            description: project.description.clone(),
+            public: project.public,
+            github_repo: project.github_repo.clone(),
+            github_pr_comments: project.github_pr_comments,
+            github_status_checks: project.github_status_checks,
+        },
+        branches: entities::Branch::find()
+            .filter(branch::Column::ProjectId.eq(project_id))
+            .all(db)
+            .await?,
+        testbeds: entities::Testbed::find()
+            .filter(testbed::Column::ProjectId.eq(project_id))
+            .all(db)
+            .await?,
+        measures: entities::Measure::find()
+            .filter(measure::Column::ProjectId.eq(project_id))
+            .all(db)
+            .await?,
+        benchmarks: entities::Benchmark::find()
+            .filter(benchmark::Column::ProjectId.eq(project_id))
+            .all(db)
+            .await?,
+        reports: entities::Report::find()
+            .filter(report::Column::ProjectId.eq(project_id))
+            .all(db)
+            .await?,
+        metrics: entities::Metric::find()
+            .inner_join(entities::Report)
+            .filter(report::Column::ProjectId.eq(project_id))
+            .all(db)
+            .await?,
+        thresholds: entities::Threshold::find()
+            .filter(threshold::Column::ProjectId.eq(project_id))
+            .all(db)
+            .await?,
+        alerts: entities::Alert::find()
+            .inner_join(entities::Threshold)
+            .filter(threshold::Column::ProjectId.eq(project_id))
+            .all(db)
+            .await?,
+        flamegraphs: entities::Flamegraph::find()
+            .inner_join(entities::Report)
+            .filter(report::Column::ProjectId.eq(project_id))
+            .all(db)
+            .await?,
+        rollups: entities::MetricRollup::find()
+            .filter(metric_rollup::Column::ProjectId.eq(project_id))
+            .all(db)
+            .await?,
+    })
+}
+
+/// Old-to-new UUID mapping for one kind of row.
+#[derive(Default)]
+struct IdMap(HashMap<Uuid, Uuid>);
+
+impl IdMap {
+    fn assign(&mut self, old: Uuid) -> Uuid {
+        *self.0.entry(old).or_insert_with(Uuid::new_v4)
+    }
+
+    fn get(&self, kind: &str, old: Uuid) -> Result<Uuid, DbErr> {
+        self.0
+            .get(&old)
+            .copied()
+            .ok_or_else(|| DbErr::Custom(format!("Archive references unknown {} {}", kind, old)))
+    }
+
+    fn get_opt(&self, kind: &str, old: Option<Uuid>) -> Result<Option<Uuid>, DbErr> {
+        old.map(|id| self.get(kind, id)).transpose()
+    }
+}
+
+async fn insert_chunked<C, A>(db: &C, models: Vec<A>) -> Result<(), DbErr>
+where
+    C: ConnectionTrait,
+    A: ActiveModelTrait + Send,
+{
+    let mut models = models.into_iter().peekable();
+    while models.peek().is_some() {
+        let chunk: Vec<A> = models.by_ref().take(INSERT_CHUNK_SIZE).collect();
+        A::Entity::insert_many(chunk).exec(db).await?;
+    }
+    Ok(())
+}
+
+/// Recreates an archived project under `slug` for `user_id` with fresh UUIDs.
+///
+/// Run this inside a transaction: a dangling reference in the archive aborts
+/// the import part-way through.
+pub async fn import<C: ConnectionTrait>(
+    db: &C,
+    user_id: Uuid,
+    slug: &str,
+    name: Option<String>,
+    archive: ProjectArchive,
+) -> Result<project::Model, DbErr> {
+    if archive.version > ARCHIVE_VERSION {
+        return Err(DbErr::Custom(format!(
+            "Unsupported archive version {} (this server reads up to {})",
+            archive.version, ARCHIVE_VERSION
+        )));
+    }
+
+    let now = Utc::now().fixed_offset();
+    let project = project::ActiveModel {
+        id: Set(Uuid::new_v4()),
+        user_id: Set(user_id),
+        slug: Set(slug.to_string()),
+        name: Set(name.unwrap_or(archive.project.name)),
+        description: Set(archive.project.description),
+        public: Set(archive.project.public),
+        github_repo: Set(archive.project.github_repo),
+        github_token: Set(None),
+        github_pr_comments: Set(archive.project.github_pr_comments),
+        github_status_checks: Set(archive.project.github_status_checks),
+        created_at: Set(now),
+        updated_at: Set(now),
+    }
+    .insert(db)
+    .await?;
+
+    let mut branches = IdMap::default();
+    let mut testbeds = IdMap::default();
+    let mut measures = IdMap::default();
+    let mut benchmarks = IdMap::default();
+    let mut reports = IdMap::default();
+    let mut metrics = IdMap::default();
+    let mut thresholds = IdMap::default();
+
+    let mut rows = Vec::with_capacity(archive.branches.len());
+    for mut model in archive.branches {
+        model.id = branches.assign(model.id);
+        model.project_id = project.id;
+        rows.push(model.into_active_model());
+    }
+    insert_chunked(db, rows).await?;
+
+    let mut rows = Vec::with_capacity(archive.testbeds.len());
+    for mut model in archive.testbeds {
+        model.id = testbeds.assign(model.id);
+        model.project_id = project.id;
+        rows.push(model.into_active_model());
+    }
+    insert_chunked(db, rows).await?;
+
+    let mut rows = Vec::with_capacity(archive.measures.len());
+    for mut model in archive.measures {
+        model.id = measures.assign(model.id);
+        model.project_id = project.id;
+        rows.push(model.into_active_model());
+    }
+    insert_chunked(db, rows).await?;
+
+    let mut rows = Vec::with_capacity(archive.benchmarks.len());
+    for mut model in archive.benchmarks {
+        model.id = benchmarks.assign(model.id);
+        model.project_id = project.id;
+        rows.push(model.into_active_model());
+    }
+    insert_chunked(db, rows).await?;
+
+    let mut rows = Vec::with_capacity(archive.reports.len());
+    for mut model in archive.reports {
+        model.id = reports.assign(model.id);
+        model.project_id = project.id;
+        model.branch_id = branches.get("branch", model.branch_id)?;
+        model.testbed_id = testbeds.get("testbed", model.testbed_id)?;
+        rows.push(model.into_active_model());
+    }
+    insert_chunked(db, rows).await?;
+
+    let mut rows = Vec::with_capacity(archive.metrics.len());
+    for mut model in archive.metrics {
+        model.id = metrics.assign(model.id);
+        model.report_id = reports.get("report", model.report_id)?;
+        model.benchmark_id = benchmarks.get("benchmark", model.benchmark_id)?;
+        model.measure_id = measures.get("measure", model.measure_id)?;
+        rows.push(model.into_active_model());
+    }
+    insert_chunked(db, rows).await?;
+
+    let mut rows = Vec::with_capacity(archive.thresholds.len());
+    for mut model in archive.thresholds {
+        model.id = thresholds.assign(model.id);
+        model.project_id = project.id;
+        model.measure_id = measures.get("measure", model.measure_id)?;
+        model.branch_id = branches.get_opt("branch", model.branch_id)?;
+        model.testbed_id = testbeds.get_opt("testbed", model.testbed_id)?;
+        rows.push(model.into_active_model());
+    }
+    insert_chunked(db, rows).await?;
+
+    let mut rows = Vec::with_capacity(archive.alerts.len());
+    for mut model in archive.alerts {
+        model.id = Uuid::new_v4();
+        model.threshold_id = thresholds.get("threshold", model.threshold_id)?;
+        model.metric_id = metrics.get("metric", model.metric_id)?;
+        rows.push(model.into_active_model());
+    }
+    insert_chunked(db, rows).await?;
+
+    let mut rows = Vec::with_capacity(archive.flamegraphs.len());
+    for mut model in archive.flamegraphs {
+        model.id = Uuid::new_v4();
+        model.report_id = reports.get("report", model.report_id)?;
+        model.benchmark_id = benchmarks.get_opt("benchmark", model.benchmark_id)?;
+        rows.push(model.into_active_model());
+    }
+    insert_chunked(db, rows).await?;
+
+    let mut rows = Vec::with_capacity(archive.rollups.len());
+    for mut model in archive.rollups {
+        model.id = Uuid::new_v4();
+        model.project_id = project.id;
+        model.branch_id = branches.get("branch", model.branch_id)?;
+        model.testbed_id = testbeds.get("testbed", model.testbed_id)?;
+        model.benchmark_id = benchmarks.get("benchmark", model.benchmark_id)?;
+        model.measure_id = measures.get("measure", model.measure_id)?;
+        rows.push(model.into_active_model());
+    }
+    insert_chunked(db, rows).await?;
+
+    Ok(project)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_read_version_1_archive() {
+        let archive: ProjectArchive =
+            serde_json::from_str(include_str!("../tests/fixtures/archive_v1.json")).unwrap();
+
+        assert_eq!(archive.version, 1);
+        assert!(archive.version <= ARCHIVE_VERSION);
+        let report = &archive.reports[0];
+        assert!(report.finalized);
+        assert_eq!(report.idempotency_key, None);
+        assert_eq!(report.ci_provider, None);
+        assert_eq!(archive.testbeds[0].fingerprint, None);
+        assert_eq!(archive.alerts.len(), 1);
+        assert_eq!(
+            archive.rollups[0].granularity,
+            metric_rollup::Granularity::Daily
+        );
+    }
+}
@@ -6,6 +6,7 @@ pub struct Config {
     pub port: u16,
     pub grpc_port: u16,
     pub rust_log: String,
+    pub raw_metric_retention_days: Option<i64>,
 }
 
 impl Config {
@@ -21,6 +22,13 @@ impl Config {
                 .parse()
                 .expect("GRPC_PORT must be a valid number"),
             rust_log: env::var("RUST_LOG").unwrap_or_else(|_| "info".to_string()),
+            // Zero days would prune everything rolled up so far
+            raw_metric_retention_days: env::var("RAW_METRIC_RETENTION_DAYS").ok().map(|days| {
+                days.parse()
+                    .ok()
+                    .filter(|days: &i64| *days >= 1)
+                    .expect("RAW_METRIC_RETENTION_DAYS must be a number of days of at least 1")
+            }),
         }
     }
 }
@@ -10,6 +10,8 @@ pub struct Model {
     pub project_id: Uuid,
     pub name: String,
     pub units: Option<String>,
+    #[serde(default)]
+    pub higher_is_better: bool,
     #[sea_orm(column_name = "created_at")]
     pub created_at: DateTimeWithTimeZone,
     #[sea_orm(column_name = "updated_at")]
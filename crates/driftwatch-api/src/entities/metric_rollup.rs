@@ -0,0 +1,109 @@
+use sea_orm::entity::prelude::*;
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, EnumIter, DeriveActiveEnum, Serialize, Deserialize)]
+#[sea_orm(rs_type = "String", db_type = "String(StringLen::N(16))")]
+pub enum Granularity {
+    #[sea_orm(string_value = "daily")]
+    Daily,
+    #[sea_orm(string_value = "weekly")]
+    Weekly,
+}
+
+#[derive(Clone, Debug, PartialEq, DeriveEntityModel, Serialize, Deserialize)]
+#[sea_orm(table_name = "metric_rollups")]
+pub struct Model {
+    #[sea_orm(primary_key, auto_increment = false)]
+    pub id: Uuid,
+    #[sea_orm(column_name = "project_id")]
+    pub project_id: Uuid,
+    #[sea_orm(column_name = "branch_id")]
+    pub branch_id: Uuid,
+    #[sea_orm(column_name = "testbed_id")]
+    pub testbed_id: Uuid,
+    #[sea_orm(column_name = "benchmark_id")]
+    pub benchmark_id: Uuid,
+    #[sea_orm(column_name = "measure_id")]
+    pub measure_id: Uuid,
+    pub granularity: Granularity,
+    #[sea_orm(column_name = "bucket_start")]
+    pub bucket_start: DateTimeWithTimeZone,
+    #[sea_orm(column_name = "min_value")]
+    pub min_value: f64,
+    #[sea_orm(column_name = "max_value")]
+    pub max_value: f64,
+    #[sea_orm(column_name = "mean_value")]
+    pub mean_value: f64,
+    #[sea_orm(column_name = "median_value")]
+    pub median_value: f64,
+    #[sea_orm(column_name = "sample_count")]
+    pub sample_count: i64,
+    #[sea_orm(column_name = "updated_at")]
+    pub updated_at: DateTimeWithTimeZone,
+}
+
+#[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
+pub enum Relation {
+    #[sea_orm(
+        belongs_to = "super::project::Entity",
+        from = "Column::ProjectId",
+        to = "super::project::Column::Id"
+    )]
+    Project,
+    #[sea_orm(
+        belongs_to = "super::branch::Entity",
+        from = "Column::BranchId",
+        to = "super::branch::Column::Id"
+    )]
+    Branch,
+    #[sea_orm(
+        belongs_to = "super::testbed::Entity",
+        from = "Column::TestbedId",
+        to = "super::testbed::Column::Id"
+    )]
+    Testbed,
+    #[sea_orm(
+        belongs_to = "super::benchmark::Entity",
+        from = "Column::BenchmarkId",
+        to = "super::benchmark::Column::Id"
+    )]
+    Benchmark,
+    #[sea_orm(
+        belongs_to = "super::measure::Entity",
+        from = "Column::MeasureId",
+        to = "super::measure::Column::Id"
+    )]
+    Measure,
+}
+
+impl Related<super::project::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::Project.def()
+    }
+}
+
+impl Related<super::branch::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::Branch.def()
+    }
+}
+
+impl Related<super::testbed::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::Testbed.def()
+    }
+}
+
+impl Related<super::benchmark::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::Benchmark.def()
+    }
+}
+
+impl Related<super::measure::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::Measure.def()
+    }
+}
+
+impl ActiveModelBehavior for ActiveModel {}
@@ -4,6 +4,7 @@ pub mod branch;
 pub mod flamegraph;
 pub mod measure;
 pub mod metric;
+pub mod metric_rollup;
 pub mod project;
 pub mod report;
 pub mod testbed;
@@ -16,6 +17,7 @@ pub use branch::Entity as Branch;
 pub use flamegraph::Entity as Flamegraph;
 pub use measure::Entity as Measure;
 pub use metric::Entity as Metric;
+pub use metric_rollup::Entity as MetricRollup;
 pub use project::Entity as Project;
 pub use report::Entity as Report;
 pub use testbed::Entity as Testbed;
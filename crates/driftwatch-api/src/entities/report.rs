@@ -16,10 +16,28 @@ pub struct Model {
     pub git_hash: Option<String>,
     #[sea_orm(column_name = "pr_number", nullable)]
     pub pr_number: Option<i32>,
+    #[sea_orm(column_name = "base_branch", nullable)]
+    pub base_branch: Option<String>,
+    #[sea_orm(column_name = "ci_provider", nullable)]
+    pub ci_provider: Option<String>,
+    #[sea_orm(column_name = "ci_run_url", nullable)]
+    pub ci_run_url: Option<String>,
+    /// Client-chosen key that makes resubmitting the same report a no-op
+    #[sea_orm(column_name = "idempotency_key", nullable)]
+    pub idempotency_key: Option<String>,
+    /// False while an open report is still receiving metrics
+    #[sea_orm(column_name = "finalized")]
+    #[serde(default = "finalized_default")]
+    pub finalized: bool,
     #[sea_orm(column_name = "created_at")]
     pub created_at: DateTimeWithTimeZone,
 }
 
+// Archives exported before reports could be open only hold finalized ones
+fn finalized_default() -> bool {
+    true
+}
+
 #[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
 pub enum Relation {
     #[sea_orm(
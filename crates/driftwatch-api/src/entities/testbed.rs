@@ -9,6 +9,10 @@ pub struct Model {
     #[sea_orm(column_name = "project_id")]
     pub project_id: Uuid,
     pub name: String,
+    /// Hardware of the machine that submitted the latest report, see
+    /// [`crate::fingerprint::Fingerprint`]
+    #[sea_orm(column_type = "JsonBinary", nullable)]
+    pub fingerprint: Option<Json>,
     #[sea_orm(column_name = "created_at")]
     pub created_at: DateTimeWithTimeZone,
     #[sea_orm(column_name = "updated_at")]
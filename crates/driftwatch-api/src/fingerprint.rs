@@ -0,0 +1,193 @@
+use async_graphql::{InputObject, SimpleObject};
+use chrono::Utc;
+use sea_orm::{ActiveModelTrait, ConnectionTrait, DbErr, Set};
+use serde::{Deserialize, Serialize};
+
+use crate::entities::testbed;
+
+/// Relative difference in total memory still considered the same machine;
+/// the kernel reserves slightly different amounts across boots and versions.
+const MEMORY_TOLERANCE: f64 = 0.05;
+
+/// Hardware and toolchain of the machine a report was measured on, as far as
+/// the submitting client could tell. Every field is optional.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize, SimpleObject, InputObject)]
+#[graphql(name = "TestbedFingerprint", input_name = "TestbedFingerprintInput")]
+pub struct Fingerprint {
+    pub cpu_model: Option<String>,
+    /// Logical CPUs
+    pub cpu_cores: Option<i32>,
+    pub memory_mb: Option<i32>,
+    pub kernel: Option<String>,
+    /// `rustc --version`
+    pub rustc: Option<String>,
+    /// Frequency scaling governor, e.g. `performance`
+    pub cpu_governor: Option<String>,
+    /// `bare-metal`, `container`, `vm` or `container-in-vm`
+    pub virtualization: Option<String>,
+}
+
+impl Fingerprint {
+    /// What differs from `previous`, e.g. `cpu_cores 8 -> 16`. Fields either
+    /// side did not report are not compared.
+    pub fn changes_since(&self, previous: &Fingerprint) -> Vec<String> {
+        let mut changes = Vec::new();
+        let mut compare = |field: &str, old: Option<String>, new: Option<String>| {
+            if let (Some(old), Some(new)) = (old, new) {
+                if old != new {
+                    changes.push(format!("{} {} -> {}", field, old, new));
+                }
+            }
+        };
+        compare(
+            "cpu_model",
+            previous.cpu_model.clone(),
+            self.cpu_model.clone(),
+        );
+        compare(
+            "cpu_cores",
+            previous.cpu_cores.map(|c| c.to_string()),
+            self.cpu_cores.map(|c| c.to_string()),
+        );
+        compare("kernel", previous.kernel.clone(), self.kernel.clone());
+        compare("rustc", previous.rustc.clone(), self.rustc.clone());
+        compare(
+            "cpu_governor",
+            previous.cpu_governor.clone(),
+            self.cpu_governor.clone(),
+        );
+        compare(
+            "virtualization",
+            previous.virtualization.clone(),
+            self.virtualization.clone(),
+        );
+
+        if let (Some(old), Some(new)) = (previous.memory_mb, self.memory_mb) {
+            let larger = old.max(new).max(1) as f64;
+            if (old - new).abs() as f64 / larger > MEMORY_TOLERANCE {
+                changes.push(format!("memory_mb {} -> {}", old, new));
+            }
+        }
+        changes
+    }
+
+    /// This fingerprint with the fields it lacks taken from `previous`, so a
+    /// client that reports less does not erase what an earlier one knew.
+    fn merged(self, previous: Fingerprint) -> Fingerprint {
+        Fingerprint {
+            cpu_model: self.cpu_model.or(previous.cpu_model),
+            cpu_cores: self.cpu_cores.or(previous.cpu_cores),
+            memory_mb: self.memory_mb.or(previous.memory_mb),
+            kernel: self.kernel.or(previous.kernel),
+            rustc: self.rustc.or(previous.rustc),
+            cpu_governor: self.cpu_governor.or(previous.cpu_governor),
+            virtualization: self.virtualization.or(previous.virtualization),
+        }
+    }
+}
+
+/// The fingerprint stored on `testbed`, if any. One that no longer parses is
+/// treated as absent.
+pub fn stored(testbed: &testbed::Model) -> Option<Fingerprint> {
+    testbed
+        .fingerprint
+        .clone()
+        .and_then(|json| serde_json::from_value(json).ok())
+}
+
+/// Stores `fingerprint` on `testbed`. When the testbed's previous report came
+/// from different hardware, returns a warning saying what changed, since its
+/// results are then not comparable with the earlier ones.
+pub async fn record<C: ConnectionTrait>(
+    db: &C,
+    testbed: testbed::Model,
+    fingerprint: Fingerprint,
+) -> Result<Option<String>, DbErr> {
+    let previous = stored(&testbed);
+    let changes = previous
+        .as_ref()
+        .map(|p| fingerprint.changes_since(p))
+        .unwrap_or_default();
+    let latest = match previous.clone() {
+        Some(p) => fingerprint.merged(p),
+        None => fingerprint,
+    };
+    if previous.as_ref() == Some(&latest) {
+        return Ok(None);
+    }
+
+    let testbed_id = testbed.id;
+    let name = testbed.name.clone();
+    let mut model: testbed::ActiveModel = testbed.into();
+    model.fingerprint = Set(Some(
+        serde_json::to_value(&latest).map_err(|e| DbErr::Json(e.to_string()))?,
+    ));
+    model.updated_at = Set(Utc::now().fixed_offset());
+    model.update(db).await?;
+
+    if changes.is_empty() {
+        return Ok(None);
+    }
+    let warning = format!(
+        "Testbed '{}' ran on different hardware than its previous report ({}); \
+         results may not be comparable with earlier ones",
+        name,
+        changes.join(", ")
+    );
+    tracing::warn!(%testbed_id, "{}", warning);
+    Ok(Some(warning))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn fingerprint() -> Fingerprint {
+        Fingerprint {
+            cpu_model: Some("AMD EPYC 7763 64-Core Processor".to_string()),
+            cpu_cores: Some(4),
+            memory_mb: Some(16_000),
+            kernel: Some("6.5.0-1025-azure".to_string()),
+            rustc: Some("rustc 1.82.0 (f6e511eec 2024-10-15)".to_string()),
+            cpu_governor: None,
+            virtualization: Some("vm".to_string()),
+        }
+    }
+
+    #[test]
+    fn test_changes_since() {
+        let previous = fingerprint();
+        assert!(fingerprint().changes_since(&previous).is_empty());
+
+        let mut current = fingerprint();
+        current.cpu_cores = Some(8);
+        current.kernel = None;
+        current.cpu_governor = Some("performance".to_string());
+        assert_eq!(current.changes_since(&previous), ["cpu_cores 4 -> 8"]);
+    }
+
+    #[test]
+    fn test_memory_within_tolerance_is_unchanged() {
+        let previous = fingerprint();
+        let mut current = fingerprint();
+        current.memory_mb = Some(15_700);
+        assert!(current.changes_since(&previous).is_empty());
+
+        current.memory_mb = Some(32_000);
+        assert_eq!(
+            current.changes_since(&previous),
+            ["memory_mb 16000 -> 32000"]
+        );
+    }
+
+    #[test]
+    fn test_merged_keeps_previous_fields() {
+        let current = Fingerprint {
+            cpu_cores: Some(8),
+            ..Default::default()
+        };
+        let merged = current.merged(fingerprint());
+        assert_eq!(merged.cpu_cores, Some(8));
+        assert_eq!(merged.kernel, fingerprint().kernel);
+    }
+}
@@ -1,18 +1,25 @@
+use std::collections::HashMap;
 use std::sync::Arc;
 
-use async_graphql::{Context, Object, Result, ID};
+use async_graphql::{Context, Json, Object, Result, ID};
 use chrono::Utc;
-use sea_orm::{ActiveModelTrait, ColumnTrait, DatabaseConnection, EntityTrait, QueryFilter, Set};
+use sea_orm::{
+    ActiveModelTrait, ColumnTrait, Condition, DatabaseConnection, DatabaseTransaction, EntityTrait,
+    QueryFilter, QuerySelect, Set, TransactionTrait,
+};
 use uuid::Uuid;
 
 use super::types::{
-    AuthPayload, CreateApiKeyInput, CreateApiKeyPayload, CreateProjectInput, CreateThresholdInput,
-    GitHubSettingsInput, Project, SigninInput, SignupInput, Threshold, UpdateProjectInput,
+    AuthPayload, CreateApiKeyInput, CreateApiKeyPayload, CreateProjectInput, CreateReportInput,
+    CreateThresholdInput, GitHubSettingsInput, MetricInput, OpenReportInput, Project, Report,
+    SigninInput, SignupInput, Threshold, UpdateProjectInput, UpdateThresholdInput,
 };
+use crate::archive::{self, ProjectArchive};
 use crate::auth::AuthUser;
 use crate::cache::AppCache;
-use crate::entities::{self, measure, project, threshold};
+use crate::entities::{self, branch, measure, metric, project, report, testbed, threshold};
 use crate::grpc::AuthServiceImpl;
+use crate::{alerts, fingerprint, reports, rollups};
 
 pub struct MutationRoot;
 
@@ -61,6 +68,7 @@ impl MutationRoot {
             project_id: Set(project.id),
             name: Set("latency".to_string()),
             units: Set(Some("ns".to_string())),
+            higher_is_better: Set(false),
             created_at: Set(now),
             updated_at: Set(now),
         };
@@ -71,6 +79,38 @@ impl MutationRoot {
         Ok(project.into())
     }
 
+    /// Recreates an exported project under a new slug with fresh IDs.
+    async fn import_project(
+        &self,
+        ctx: &Context<'_>,
+        slug: String,
+        name: Option<String>,
+        archive: Json<ProjectArchive>,
+    ) -> Result<Project> {
+        let db = ctx.data::<DatabaseConnection>()?;
+        let user = ctx.data::<AuthUser>()?;
+        let cache = ctx.data::<AppCache>()?;
+        let user_id = user.user_id();
+
+        let existing = entities::Project::find()
+            .filter(project::Column::UserId.eq(user_id))
+            .filter(project::Column::Slug.eq(&slug))
+            .one(db)
+            .await?;
+
+        if existing.is_some() {
+            return Err("A workspace with this slug already exists".into());
+        }
+
+        let txn = db.begin().await?;
+        let project = archive::import(&txn, user_id, &slug, name, archive.0).await?;
+        txn.commit().await?;
+
+        cache.invalidate_user_projects(user_id).await;
+
+        Ok(project.into())
+    }
+
     async fn update_project(
         &self,
         ctx: &Context<'_>,
@@ -189,16 +229,75 @@ impl MutationRoot {
             .await?
             .ok_or("Workspace not found")?;
 
+        let min_sample_size = input.min_sample_size.unwrap_or(2);
+        validate_threshold(input.upper_boundary, input.lower_boundary, min_sample_size)?;
+
         let project_slug = project.slug.clone();
-        let measure_id = Uuid::parse_str(&input.measure_id.0)?;
-        let branch_id = input
-            .branch_id
-            .map(|id| Uuid::parse_str(&id.0))
-            .transpose()?;
-        let testbed_id = input
-            .testbed_id
-            .map(|id| Uuid::parse_str(&id.0))
-            .transpose()?;
+        let measure_id = match (input.measure_id, input.measure) {
+            (Some(id), None) => {
+                let id = Uuid::parse_str(&id.0)?;
+                entities::Measure::find_by_id(id)
+                    .filter(measure::Column::ProjectId.eq(project.id))
+                    .one(db)
+                    .await?
+                    .ok_or("Measure not found")?
+                    .id
+            }
+            (None, Some(name)) => {
+                entities::Measure::find()
+                    .filter(measure::Column::ProjectId.eq(project.id))
+                    .filter(measure::Column::Name.eq(&name))
+                    .one(db)
+                    .await?
+                    .ok_or_else(|| format!("Measure '{}' not found", name))?
+                    .id
+            }
+            _ => return Err("Give either measureId or measure".into()),
+        };
+        let branch_id = match (input.branch_id, input.branch) {
+            (Some(id), None) => {
+                let id = Uuid::parse_str(&id.0)?;
+                let branch = entities::Branch::find_by_id(id)
+                    .filter(branch::Column::ProjectId.eq(project.id))
+                    .one(db)
+                    .await?
+                    .ok_or("Branch not found")?;
+                Some(branch.id)
+            }
+            (None, Some(name)) => {
+                let branch = entities::Branch::find()
+                    .filter(branch::Column::ProjectId.eq(project.id))
+                    .filter(branch::Column::Name.eq(&name))
+                    .one(db)
+                    .await?
+                    .ok_or_else(|| format!("Branch '{}' not found", name))?;
+                Some(branch.id)
+            }
+            (None, None) => None,
+            (Some(_), Some(_)) => return Err("Give either branchId or branch, not both".into()),
+        };
+        let testbed_id = match (input.testbed_id, input.testbed) {
+            (Some(id), None) => {
+                let id = Uuid::parse_str(&id.0)?;
+                let testbed = entities::Testbed::find_by_id(id)
+                    .filter(testbed::Column::ProjectId.eq(project.id))
+                    .one(db)
+                    .await?
+                    .ok_or("Testbed not found")?;
+                Some(testbed.id)
+            }
+            (None, Some(name)) => {
+                let testbed = entities::Testbed::find()
+                    .filter(testbed::Column::ProjectId.eq(project.id))
+                    .filter(testbed::Column::Name.eq(&name))
+                    .one(db)
+                    .await?
+                    .ok_or_else(|| format!("Testbed '{}' not found", name))?;
+                Some(testbed.id)
+            }
+            (None, None) => None,
+            (Some(_), Some(_)) => return Err("Give either testbedId or testbed, not both".into()),
+        };
 
         let now = Utc::now().fixed_offset();
         let threshold = threshold::ActiveModel {
@@ -209,7 +308,7 @@ impl MutationRoot {
             testbed_id: Set(testbed_id),
             upper_boundary: Set(input.upper_boundary),
             lower_boundary: Set(input.lower_boundary),
-            min_sample_size: Set(input.min_sample_size.unwrap_or(2)),
+            min_sample_size: Set(min_sample_size),
             created_at: Set(now),
             updated_at: Set(now),
         };
@@ -221,6 +320,53 @@ impl MutationRoot {
         Ok(threshold.into())
     }
 
+    async fn update_threshold(
+        &self,
+        ctx: &Context<'_>,
+        id: ID,
+        input: UpdateThresholdInput,
+    ) -> Result<Threshold> {
+        let db = ctx.data::<DatabaseConnection>()?;
+        let user = ctx.data::<AuthUser>()?;
+        let cache = ctx.data::<AppCache>()?;
+        let user_id = user.user_id();
+
+        let threshold_id = Uuid::parse_str(&id.0)?;
+
+        let threshold = entities::Threshold::find_by_id(threshold_id)
+            .one(db)
+            .await?
+            .ok_or("Threshold not found")?;
+
+        let project = entities::Project::find_by_id(threshold.project_id)
+            .one(db)
+            .await?
+            .ok_or("Project not found")?;
+
+        if project.user_id != user_id {
+            return Err("Unauthorized".into());
+        }
+
+        let mut upper_boundary = threshold.upper_boundary;
+        let mut lower_boundary = threshold.lower_boundary;
+        input.upper_boundary.update_to(&mut upper_boundary);
+        input.lower_boundary.update_to(&mut lower_boundary);
+        let min_sample_size = input.min_sample_size.unwrap_or(threshold.min_sample_size);
+        validate_threshold(upper_boundary, lower_boundary, min_sample_size)?;
+
+        let mut active: threshold::ActiveModel = threshold.into();
+        active.upper_boundary = Set(upper_boundary);
+        active.lower_boundary = Set(lower_boundary);
+        active.min_sample_size = Set(min_sample_size);
+        active.updated_at = Set(Utc::now().fixed_offset());
+
+        let updated = active.update(db).await?;
+
+        cache.invalidate_project(user_id, &project.slug).await;
+
+        Ok(updated.into())
+    }
+
     async fn delete_threshold(&self, ctx: &Context<'_>, id: ID) -> Result<bool> {
         let db = ctx.data::<DatabaseConnection>()?;
         let user = ctx.data::<AuthUser>()?;
@@ -253,6 +399,143 @@ impl MutationRoot {
         Ok(true)
     }
 
+    async fn create_report(&self, ctx: &Context<'_>, input: CreateReportInput) -> Result<Report> {
+        let db = ctx.data::<DatabaseConnection>()?;
+        let user = ctx.data::<AuthUser>()?;
+        let cache = ctx.data::<AppCache>()?;
+        let user_id = user.user_id();
+
+        let project = find_project(db, user_id, &input.project_slug).await?;
+
+        if input.metrics.is_empty() {
+            return Err("A report must contain at least one metric".into());
+        }
+
+        let (open, metrics) = input.into_parts();
+        let txn = db.begin().await?;
+        let (report, warnings) = match insert_report(db, &txn, project.id, open, true).await? {
+            Inserted::New(report, warnings) => (report, warnings),
+            // Either a retry, or another submission reusing the key; the
+            // caller can't tell which, so it is told the metrics were dropped
+            Inserted::Existing(report) => {
+                txn.rollback().await?;
+                let warning = format!(
+                    "Report {} was stored earlier under idempotency key '{}' and is \
+                     returned as it was; the metrics of this request were not added to it",
+                    report.id,
+                    report.idempotency_key.as_deref().unwrap_or_default()
+                );
+                return Ok(Report {
+                    warnings: vec![warning],
+                    ..report.into()
+                });
+            }
+        };
+        let metrics = insert_metrics(db, &txn, project.id, report.id, metrics, false).await?;
+        alerts::evaluate_report(&txn, &report, &metrics).await?;
+        txn.commit().await?;
+
+        refresh_rollups(db, &report).await;
+        cache.invalidate_project(user_id, &project.slug).await;
+
+        Ok(Report {
+            warnings,
+            ..report.into()
+        })
+    }
+
+    /// Opens a report that `appendMetrics` adds to until `finalizeReport`.
+    async fn open_report(&self, ctx: &Context<'_>, input: OpenReportInput) -> Result<Report> {
+        let db = ctx.data::<DatabaseConnection>()?;
+        let user = ctx.data::<AuthUser>()?;
+        let user_id = user.user_id();
+
+        let project = find_project(db, user_id, &input.project_slug).await?;
+
+        let txn = db.begin().await?;
+        let report = match insert_report(db, &txn, project.id, input, false).await? {
+            Inserted::New(report, warnings) => {
+                txn.commit().await?;
+                Report {
+                    warnings,
+                    ..report.into()
+                }
+            }
+            Inserted::Existing(report) => {
+                txn.rollback().await?;
+                report.into()
+            }
+        };
+
+        Ok(report)
+    }
+
+    /// Adds metrics to an open report. Metrics for a benchmark and measure the
+    /// report already has replace them, so a retried call is harmless.
+    async fn append_metrics(
+        &self,
+        ctx: &Context<'_>,
+        report_id: ID,
+        metrics: Vec<MetricInput>,
+    ) -> Result<Report> {
+        let db = ctx.data::<DatabaseConnection>()?;
+        let user = ctx.data::<AuthUser>()?;
+        let cache = ctx.data::<AppCache>()?;
+        let user_id = user.user_id();
+
+        if metrics.is_empty() {
+            return Err("At least one metric is required".into());
+        }
+
+        let txn = db.begin().await?;
+        let (report, project) = lock_report(&txn, user_id, &report_id).await?;
+        if report.finalized {
+            return Err("Report is already finalized".into());
+        }
+
+        insert_metrics(db, &txn, project.id, report.id, metrics, true).await?;
+        txn.commit().await?;
+
+        cache.invalidate_project(user_id, &project.slug).await;
+
+        Ok(report.into())
+    }
+
+    /// Completes an open report and evaluates its thresholds, once: finalizing
+    /// a finalized report returns it unchanged.
+    async fn finalize_report(&self, ctx: &Context<'_>, report_id: ID) -> Result<Report> {
+        let db = ctx.data::<DatabaseConnection>()?;
+        let user = ctx.data::<AuthUser>()?;
+        let cache = ctx.data::<AppCache>()?;
+        let user_id = user.user_id();
+
+        let txn = db.begin().await?;
+        let (report, project) = lock_report(&txn, user_id, &report_id).await?;
+        if report.finalized {
+            txn.rollback().await?;
+            return Ok(report.into());
+        }
+
+        let metrics = entities::Metric::find()
+            .filter(metric::Column::ReportId.eq(report.id))
+            .all(&txn)
+            .await?;
+        if metrics.is_empty() {
+            return Err("A report must contain at least one metric".into());
+        }
+
+        let mut active: report::ActiveModel = report.into();
+        active.finalized = Set(true);
+        let report = active.update(&txn).await?;
+        alerts::evaluate_report(&txn, &report, &metrics).await?;
+        txn.commit().await?;
+
+        refresh_rollups(db, &report).await;
+        cache.invalidate_project(user_id, &project.slug).await;
+
+        Ok(report.into())
+    }
+
     async fn signup(&self, ctx: &Context<'_>, input: SignupInput) -> Result<AuthPayload> {
         let auth_service = ctx.data::<Arc<AuthServiceImpl>>()?;
 
@@ -332,3 +615,220 @@ impl MutationRoot {
             .map_err(async_graphql::Error::new)
     }
 }
+
+async fn find_project(
+    db: &DatabaseConnection,
+    user_id: Uuid,
+    slug: &str,
+) -> Result<project::Model> {
+    Ok(entities::Project::find()
+        .filter(project::Column::UserId.eq(user_id))
+        .filter(project::Column::Slug.eq(slug))
+        .one(db)
+        .await?
+        .ok_or("Workspace not found")?)
+}
+
+/// A threshold that can never fire is most likely a mistake.
+fn validate_threshold(
+    upper_boundary: Option<f64>,
+    lower_boundary: Option<f64>,
+    min_sample_size: i32,
+) -> Result<()> {
+    if upper_boundary.is_none() && lower_boundary.is_none() {
+        return Err("A threshold needs an upper or a lower boundary".into());
+    }
+    if upper_boundary.is_some_and(|upper| upper < 0.0) {
+        return Err("The upper boundary is an allowed increase and cannot be negative".into());
+    }
+    if lower_boundary.is_some_and(|lower| lower < 0.0) {
+        return Err("The lower boundary is an allowed decrease and cannot be negative".into());
+    }
+    if min_sample_size < 1 {
+        return Err("The minimum sample size must be at least 1".into());
+    }
+    Ok(())
+}
+
+/// Loads a report of the user's and its project, locking the report row for
+/// the rest of `txn` so appends and finalizing do not interleave.
+async fn lock_report(
+    txn: &DatabaseTransaction,
+    user_id: Uuid,
+    report_id: &ID,
+) -> Result<(report::Model, project::Model)> {
+    let report_id = Uuid::parse_str(report_id)?;
+    let report = entities::Report::find_by_id(report_id)
+        .lock_exclusive()
+        .one(txn)
+        .await?
+        .ok_or("Report not found")?;
+    let project = entities::Project::find_by_id(report.project_id)
+        .filter(project::Column::UserId.eq(user_id))
+        .one(txn)
+        .await?
+        .ok_or("Report not found")?;
+    Ok((report, project))
+}
+
+enum Inserted {
+    /// With any warnings to return alongside it
+    New(report::Model, Vec<String>),
+    /// Stored before under the same idempotency key
+    Existing(report::Model),
+}
+
+/// Inserts a report row in `txn`, resolving its branch and testbed through
+/// `db`. With an idempotency key already in use, returns that report instead;
+/// `txn` must then be rolled back.
+async fn insert_report(
+    db: &DatabaseConnection,
+    txn: &DatabaseTransaction,
+    project_id: Uuid,
+    input: OpenReportInput,
+    finalized: bool,
+) -> Result<Inserted> {
+    if let Some(ref key) = input.idempotency_key {
+        if key.is_empty() || key.len() > reports::MAX_IDEMPOTENCY_KEY_LEN {
+            return Err(format!(
+                "An idempotency key must be 1 to {} bytes long",
+                reports::MAX_IDEMPOTENCY_KEY_LEN
+            )
+            .into());
+        }
+        if let Some(existing) = reports::find_by_idempotency_key(db, project_id, key).await? {
+            return Ok(Inserted::Existing(existing));
+        }
+    }
+
+    let branch = reports::find_or_create_branch(db, project_id, &input.branch).await?;
+    let testbed = reports::find_or_create_testbed(db, project_id, &input.testbed).await?;
+    let testbed_id = testbed.id;
+    let mut warnings = Vec::new();
+    if let Some(fingerprint) = input.testbed_fingerprint {
+        warnings.extend(fingerprint::record(txn, testbed, fingerprint).await?);
+    }
+
+    let inserted = report::ActiveModel {
+        id: Set(Uuid::new_v4()),
+        project_id: Set(project_id),
+        branch_id: Set(branch.id),
+        testbed_id: Set(testbed_id),
+        git_hash: Set(input.git_hash),
+        pr_number: Set(input.pr_number),
+        base_branch: Set(input.base_branch),
+        ci_provider: Set(input.ci_provider),
+        ci_run_url: Set(input.ci_run_url),
+        idempotency_key: Set(input.idempotency_key.clone()),
+        finalized: Set(finalized),
+        created_at: Set(Utc::now().fixed_offset()),
+    }
+    .insert(txn)
+    .await;
+
+    match (inserted, input.idempotency_key) {
+        (Ok(report), _) => Ok(Inserted::New(report, warnings)),
+        // A concurrent submission with the same key won the unique index
+        (Err(err), Some(key)) => {
+            match reports::find_by_idempotency_key(db, project_id, &key).await? {
+                Some(existing) => Ok(Inserted::Existing(existing)),
+                None => Err(err.into()),
+            }
+        }
+        (Err(err), None) => Err(err.into()),
+    }
+}
+
+// Two bind parameters per replaced metric keep a DELETE well below Postgres'
+// 65535 limit.
+const REPLACE_CHUNK_SIZE: usize = 1000;
+
+/// Stores `metrics` on a report in `txn`, creating their benchmarks and
+/// measures through `db` on first use. With `replace`, for a report that may
+/// already have metrics, those for the same benchmark and measure are
+/// replaced. Within the batch the last such metric wins.
+async fn insert_metrics(
+    db: &DatabaseConnection,
+    txn: &DatabaseTransaction,
+    project_id: Uuid,
+    report_id: Uuid,
+    metrics: Vec<MetricInput>,
+    replace: bool,
+) -> Result<Vec<metric::Model>> {
+    let mut positions = HashMap::new();
+    let mut unique: Vec<MetricInput> = Vec::with_capacity(metrics.len());
+    for metric in metrics {
+        let key = (metric.benchmark.clone(), metric.measure.clone());
+        match positions.get(&key) {
+            Some(&i) => unique[i] = metric,
+            None => {
+                positions.insert(key, unique.len());
+                unique.push(metric);
+            }
+        }
+    }
+    let metrics = unique;
+
+    let mut benchmark_ids = HashMap::new();
+    let mut measure_ids = HashMap::new();
+    for metric in &metrics {
+        if !benchmark_ids.contains_key(&metric.benchmark) {
+            let benchmark =
+                reports::find_or_create_benchmark(db, project_id, &metric.benchmark).await?;
+            benchmark_ids.insert(metric.benchmark.clone(), benchmark.id);
+        }
+        if !measure_ids.contains_key(&metric.measure) {
+            let measure = reports::find_or_create_measure(
+                db,
+                project_id,
+                &metric.measure,
+                metric.units.as_deref(),
+                metric.higher_is_better.unwrap_or_default(),
+            )
+            .await?;
+            measure_ids.insert(metric.measure.clone(), measure.id);
+        }
+    }
+
+    if replace {
+        for chunk in metrics.chunks(REPLACE_CHUNK_SIZE) {
+            let replaced = chunk.iter().fold(Condition::any(), |cond, metric| {
+                cond.add(
+                    Condition::all()
+                        .add(metric::Column::BenchmarkId.eq(benchmark_ids[&metric.benchmark]))
+                        .add(metric::Column::MeasureId.eq(measure_ids[&metric.measure])),
+                )
+            });
+            entities::Metric::delete_many()
+                .filter(metric::Column::ReportId.eq(report_id))
+                .filter(replaced)
+                .exec(txn)
+                .await?;
+        }
+    }
+
+    let now = Utc::now().fixed_offset();
+    let mut inserted = Vec::with_capacity(metrics.len());
+    for metric in metrics {
+        let metric = metric::ActiveModel {
+            id: Set(Uuid::new_v4()),
+            report_id: Set(report_id),
+            benchmark_id: Set(benchmark_ids[&metric.benchmark]),
+            measure_id: Set(measure_ids[&metric.measure]),
+            value: Set(metric.value),
+            lower: Set(metric.lower_value),
+            upper: Set(metric.upper_value),
+            created_at: Set(now),
+        }
+        .insert(txn)
+        .await?;
+        inserted.push(metric);
+    }
+    Ok(inserted)
+}
+
+async fn refresh_rollups(db: &DatabaseConnection, report: &report::Model) {
+    if let Err(e) = rollups::refresh_for_report(db, report).await {
+        tracing::warn!(report_id = %report.id, "Failed to refresh metric rollups: {}", e);
+    }
+}
@@ -1,10 +1,11 @@
 use std::sync::Arc;
 
-use async_graphql::{Context, Object, Result};
+use async_graphql::{Context, Json, Object, Result};
 use sea_orm::{ColumnTrait, DatabaseConnection, EntityTrait, QueryFilter, QueryOrder};
 use tracing::{info_span, instrument, Instrument};
 
 use super::types::{ApiKey, Project, User};
+use crate::archive::{self, ProjectArchive};
 use crate::auth::AuthUser;
 use crate::cache::AppCache;
 use crate::entities::{self, project};
@@ -127,6 +128,26 @@ impl QueryRoot {
         Ok(result)
     }
 
+    /// Versioned archive of a project and all of its history, for
+    /// `importProject` on this or another server.
+    async fn export_project(
+        &self,
+        ctx: &Context<'_>,
+        slug: String,
+    ) -> Result<Json<ProjectArchive>> {
+        let db = ctx.data::<DatabaseConnection>()?;
+        let user = ctx.data::<AuthUser>()?;
+
+        let project = entities::Project::find()
+            .filter(project::Column::UserId.eq(user.user_id()))
+            .filter(project::Column::Slug.eq(&slug))
+            .one(db)
+            .await?
+            .ok_or("Workspace not found")?;
+
+        Ok(Json(archive::export(db, &project).await?))
+    }
+
     async fn me(&self, ctx: &Context<'_>) -> Result<User> {
         let user = ctx.data::<AuthUser>()?;
         Ok(user.user.clone().into())
@@ -0,0 +1,81 @@
+use async_graphql::{Enum, SimpleObject};
+
+use crate::entities::{metric, metric_rollup};
+
+#[derive(Enum, Copy, Clone, Eq, PartialEq)]
+pub enum HistoryResolution {
+    Raw,
+    Daily,
+    Weekly,
+}
+
+impl HistoryResolution {
+    pub fn to_granularity(self) -> Option<metric_rollup::Granularity> {
+        match self {
+            HistoryResolution::Raw => None,
+            HistoryResolution::Daily => Some(metric_rollup::Granularity::Daily),
+            HistoryResolution::Weekly => Some(metric_rollup::Granularity::Weekly),
+        }
+    }
+}
+
+impl From<Option<metric_rollup::Granularity>> for HistoryResolution {
+    fn from(granularity: Option<metric_rollup::Granularity>) -> Self {
+        match granularity {
+            None => HistoryResolution::Raw,
+            Some(metric_rollup::Granularity::Daily) => HistoryResolution::Daily,
+            Some(metric_rollup::Granularity::Weekly) => HistoryResolution::Weekly,
+        }
+    }
+}
+
+/// One point of a benchmark's history. Raw points carry the metric's own
+/// bounds; rolled-up points carry the bucket's statistics and use the mean
+/// as `value`.
+#[derive(SimpleObject)]
+pub struct HistoryPoint {
+    pub timestamp: chrono::DateTime<chrono::Utc>,
+    pub value: f64,
+    pub lower: Option<f64>,
+    pub upper: Option<f64>,
+    pub min: Option<f64>,
+    pub max: Option<f64>,
+    pub median: Option<f64>,
+    pub count: i64,
+}
+
+impl From<metric::Model> for HistoryPoint {
+    fn from(model: metric::Model) -> Self {
+        Self {
+            timestamp: model.created_at.into(),
+            value: model.value,
+            lower: model.lower,
+            upper: model.upper,
+            min: None,
+            max: None,
+            median: None,
+            count: 1,
+        }
+    }
+}
+
+impl From<metric_rollup::Model> for HistoryPoint {
+    fn from(model: metric_rollup::Model) -> Self {
+        Self {
+            timestamp: model.bucket_start.into(),
+            value: model.mean_value,
+            lower: None,
+            upper: None,
+            min: Some(model.min_value),
+            max: Some(model.max_value),
+            median: Some(model.median_value),
+            count: model.sample_count,
+        }
+    }
+}
+
+#[derive(SimpleObject)]
+pub struct MetricHistory {
+    pub resolution: HistoryResolution,
+    pub points: Vec<HistoryPoint>,
+}
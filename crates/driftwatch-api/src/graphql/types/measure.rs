@@ -8,6 +8,9 @@ pub struct Measure {
     pub id: ID,
     pub name: String,
     pub units: Option<String>,
+    /// Whether an increase is an improvement, as for throughput, rather
+    /// than a regression, as for latency
+    pub higher_is_better: bool,
     pub created_at: chrono::DateTime<chrono::Utc>,
 }
 
@@ -17,6 +20,7 @@ impl From<measure::Model> for Measure {
             id: ID(model.id.to_string()),
             name: model.name,
             units: model.units,
+            higher_is_better: model.higher_is_better,
             created_at: model.created_at.into(),
         }
     }
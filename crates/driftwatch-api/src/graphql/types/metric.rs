@@ -2,7 +2,8 @@ use async_graphql::dataloader::DataLoader;
 use async_graphql::{ComplexObject, Context, Result, SimpleObject, ID};
 use uuid::Uuid;
 
-use crate::loaders::{BenchmarkLoader, MeasureLoader};
+use crate::alerts::BaselineKey;
+use crate::loaders::{BaselineLoader, BenchmarkLoader, MeasureLoader, ReportLoader};
 
 #[derive(SimpleObject, Clone)]
 #[graphql(complex, cache_control(max_age = 3600))]
@@ -13,6 +14,8 @@ pub struct Metric {
     pub upper: Option<f64>,
     pub created_at: chrono::DateTime<chrono::Utc>,
     #[graphql(skip)]
+    pub report_id: Uuid,
+    #[graphql(skip)]
     pub benchmark_id: Uuid,
     #[graphql(skip)]
     pub measure_id: Uuid,
@@ -26,6 +29,7 @@ impl From<crate::entities::metric::Model> for Metric {
             lower: model.lower,
             upper: model.upper,
             created_at: model.created_at.into(),
+            report_id: model.report_id,
             benchmark_id: model.benchmark_id,
             measure_id: model.measure_id,
         }
@@ -49,4 +53,21 @@ impl Metric {
             .await?
             .ok_or_else(|| "Measure not found".into())
     }
+
+    /// Mean of the same series on the report's branch and testbed before
+    /// this metric was stored.
+    async fn baseline_value(&self, ctx: &Context<'_>) -> Result<Option<f64>> {
+        let report = ctx
+            .data::<DataLoader<ReportLoader>>()?
+            .load_one(self.report_id)
+            .await?
+            .ok_or("Report not found")?;
+
+        let key = BaselineKey::new(&report, self.benchmark_id, self.measure_id);
+        let baseline = ctx
+            .data::<DataLoader<BaselineLoader>>()?
+            .load_one(key)
+            .await?;
+        Ok(baseline.map(|(mean, _)| mean))
+    }
 }
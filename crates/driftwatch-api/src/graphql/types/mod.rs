@@ -2,6 +2,7 @@ mod alert;
 mod auth;
 mod benchmark;
 mod branch;
+mod history;
 mod measure;
 mod metric;
 mod project;
@@ -13,6 +14,7 @@ pub use alert::*;
 pub use auth::*;
 pub use benchmark::*;
 pub use branch::*;
+pub use history::*;
 pub use measure::*;
 pub use metric::*;
 pub use project::*;
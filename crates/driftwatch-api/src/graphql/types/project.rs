@@ -4,8 +4,10 @@ use serde::{Deserialize, Serialize};
 use uuid::Uuid;
 
 use crate::entities::{
-    self, alert, benchmark, branch, measure, project, report, testbed, threshold,
+    self, alert, benchmark, branch, measure, metric, metric_rollup, project, report, testbed,
+    threshold,
 };
+use crate::rollups::{self, RawRetention};
 
 #[derive(SimpleObject, Serialize, Deserialize)]
 #[graphql(complex, cache_control(max_age = 300))]
@@ -153,6 +155,103 @@ impl Project {
             .await?;
         Ok(alerts.into_iter().map(Into::into).collect())
     }
+
+    /// History of one benchmark/measure on a branch and testbed, from
+    /// finalized reports only. Short ranges are served from raw metrics and
+    /// longer ones from daily or weekly rollups, unless `resolution` forces a
+    /// specific source.
+    #[allow(clippy::too_many_arguments)]
+    async fn metric_history(
+        &self,
+        ctx: &Context<'_>,
+        benchmark: String,
+        measure: String,
+        branch: String,
+        testbed: String,
+        from: chrono::DateTime<chrono::Utc>,
+        to: Option<chrono::DateTime<chrono::Utc>>,
+        resolution: Option<super::HistoryResolution>,
+    ) -> Result<super::MetricHistory> {
+        let db = ctx.data::<DatabaseConnection>()?;
+        let project_id = Uuid::parse_str(&self.id.0)?;
+        let now = chrono::Utc::now();
+        let to = to.unwrap_or(now);
+
+        let granularity = match resolution {
+            Some(resolution) => resolution.to_granularity(),
+            None => {
+                let retention = ctx.data_opt::<RawRetention>().copied().unwrap_or_default();
+                rollups::choose_granularity(from, to, retention.horizon(now))
+            }
+        };
+
+        let benchmark = entities::Benchmark::find()
+            .filter(benchmark::Column::ProjectId.eq(project_id))
+            .filter(benchmark::Column::Name.eq(&benchmark))
+            .one(db)
+            .await?;
+        let measure = entities::Measure::find()
+            .filter(measure::Column::ProjectId.eq(project_id))
+            .filter(measure::Column::Name.eq(&measure))
+            .one(db)
+            .await?;
+        let branch = entities::Branch::find()
+            .filter(branch::Column::ProjectId.eq(project_id))
+            .filter(branch::Column::Name.eq(&branch))
+            .one(db)
+            .await?;
+        let testbed = entities::Testbed::find()
+            .filter(testbed::Column::ProjectId.eq(project_id))
+            .filter(testbed::Column::Name.eq(&testbed))
+            .one(db)
+            .await?;
+
+        let (Some(benchmark), Some(measure), Some(branch), Some(testbed)) =
+            (benchmark, measure, branch, testbed)
+        else {
+            return Ok(super::MetricHistory {
+                resolution: granularity.into(),
+                points: Vec::new(),
+            });
+        };
+
+        let points = match granularity {
+            None => entities::Metric::find()
+                .inner_join(entities::Report)
+                .filter(metric::Column::BenchmarkId.eq(benchmark.id))
+                .filter(metric::Column::MeasureId.eq(measure.id))
+                .filter(report::Column::BranchId.eq(branch.id))
+                .filter(report::Column::TestbedId.eq(testbed.id))
+                .filter(report::Column::Finalized.eq(true))
+                .filter(report::Column::CreatedAt.gte(from))
+                .filter(report::Column::CreatedAt.lt(to))
+                .order_by_asc(report::Column::CreatedAt)
+                .all(db)
+                .await?
+                .into_iter()
+                .map(Into::into)
+                .collect(),
+            Some(granularity) => entities::MetricRollup::find()
+                .filter(metric_rollup::Column::BenchmarkId.eq(benchmark.id))
+                .filter(metric_rollup::Column::MeasureId.eq(measure.id))
+                .filter(metric_rollup::Column::BranchId.eq(branch.id))
+                .filter(metric_rollup::Column::TestbedId.eq(testbed.id))
+                .filter(metric_rollup::Column::Granularity.eq(granularity))
+                .filter(metric_rollup::Column::BucketStart.gte(granularity.bucket_start(from)))
+                .filter(metric_rollup::Column::BucketStart.lt(to))
+                .order_by_asc(metric_rollup::Column::BucketStart)
+                .all(db)
+                .await?
+                .into_iter()
+                .map(Into::into)
+                .collect(),
+        };
+
+        Ok(super::MetricHistory {
+            resolution: granularity.into(),
+            points,
+        })
+    }
 }
 
 #[derive(InputObject)]
@@ -1,7 +1,10 @@
 use async_graphql::dataloader::DataLoader;
-use async_graphql::{ComplexObject, Context, Result, SimpleObject, ID};
+use async_graphql::{ComplexObject, Context, InputObject, Result, SimpleObject, ID};
+use sea_orm::{ColumnTrait, DatabaseConnection, EntityTrait, QueryFilter, QueryOrder};
 use uuid::Uuid;
 
+use crate::entities::{self, alert, metric};
+use crate::fingerprint::Fingerprint;
 use crate::loaders::{BranchLoader, TestbedLoader};
 
 #[derive(SimpleObject)]
@@ -10,7 +13,19 @@ pub struct Report {
     pub id: ID,
     pub git_hash: Option<String>,
     pub pr_number: Option<i32>,
+    pub base_branch: Option<String>,
+    /// CI system the report was submitted from, e.g. `github-actions`
+    pub ci_provider: Option<String>,
+    pub ci_run_url: Option<String>,
+    pub idempotency_key: Option<String>,
+    /// Whether the report is complete; an open report is still receiving
+    /// metrics from `appendMetrics`
+    pub finalized: bool,
     pub created_at: chrono::DateTime<chrono::Utc>,
+    /// Problems noticed while storing the report, e.g. a testbed whose
+    /// hardware changed, or `createReport` returning a report stored earlier
+    /// under the same idempotency key; only set in the mutation's response
+    pub warnings: Vec<String>,
     #[graphql(skip)]
     pub branch_id: Uuid,
     #[graphql(skip)]
@@ -23,7 +38,13 @@ impl From<crate::entities::report::Model> for Report {
             id: ID(model.id.to_string()),
             git_hash: model.git_hash,
             pr_number: model.pr_number,
+            base_branch: model.base_branch,
+            ci_provider: model.ci_provider,
+            ci_run_url: model.ci_run_url,
+            idempotency_key: model.idempotency_key,
+            finalized: model.finalized,
             created_at: model.created_at.into(),
+            warnings: Vec::new(),
             branch_id: model.branch_id,
             testbed_id: model.testbed_id,
         }
@@ -47,4 +68,100 @@ impl Report {
             .await?
             .ok_or_else(|| "Testbed not found".into())
     }
+
+    async fn metrics(&self, ctx: &Context<'_>) -> Result<Vec<super::Metric>> {
+        let db = ctx.data::<DatabaseConnection>()?;
+        let report_id = Uuid::parse_str(&self.id.0)?;
+
+        let metrics = entities::Metric::find()
+            .filter(metric::Column::ReportId.eq(report_id))
+            .all(db)
+            .await?;
+
+        Ok(metrics.into_iter().map(Into::into).collect())
+    }
+
+    async fn alerts(&self, ctx: &Context<'_>) -> Result<Vec<super::Alert>> {
+        let db = ctx.data::<DatabaseConnection>()?;
+        let report_id = Uuid::parse_str(&self.id.0)?;
+
+        let alerts = entities::Alert::find()
+            .inner_join(entities::Metric)
+            .filter(metric::Column::ReportId.eq(report_id))
+            .order_by_desc(alert::Column::PercentChange)
+            .all(db)
+            .await?;
+
+        Ok(alerts.into_iter().map(Into::into).collect())
+    }
+}
+
+#[derive(InputObject)]
+pub struct MetricInput {
+    pub benchmark: String,
+    pub measure: String,
+    /// Units used when the measure is created by this report, e.g. `ns`.
+    pub units: Option<String>,
+    /// Whether higher values are better, used when the measure is created
+    /// by this report [default: false]
+    pub higher_is_better: Option<bool>,
+    pub value: f64,
+    pub lower_value: Option<f64>,
+    pub upper_value: Option<f64>,
+}
+
+#[derive(InputObject)]
+pub struct CreateReportInput {
+    pub project_slug: String,
+    pub branch: String,
+    pub testbed: String,
+    pub git_hash: Option<String>,
+    pub pr_number: Option<i32>,
+    /// Branch the pull request targets
+    pub base_branch: Option<String>,
+    pub ci_provider: Option<String>,
+    pub ci_run_url: Option<String>,
+    /// Submitting a report again with the same key, e.g. a CI run id and
+    /// attempt or a client-generated UUID, returns the original report
+    pub idempotency_key: Option<String>,
+    /// Hardware the metrics were measured on, stored on the testbed
+    pub testbed_fingerprint: Option<Fingerprint>,
+    pub metrics: Vec<MetricInput>,
+}
+
+/// A report that receives its metrics from several `appendMetrics` calls,
+/// e.g. one per CI shard, and is evaluated once by `finalizeReport`.
+#[derive(InputObject)]
+pub struct OpenReportInput {
+    pub project_slug: String,
+    pub branch: String,
+    pub testbed: String,
+    pub git_hash: Option<String>,
+    pub pr_number: Option<i32>,
+    pub base_branch: Option<String>,
+    pub ci_provider: Option<String>,
+    pub ci_run_url: Option<String>,
+    /// Opening a report again with the same key returns the open report, so
+    /// every shard can open it with e.g. the CI run id
+    pub idempotency_key: Option<String>,
+    pub testbed_fingerprint: Option<Fingerprint>,
+}
+
+impl CreateReportInput {
+    /// The report to open and the metrics to append to it.
+    pub fn into_parts(self) -> (OpenReportInput, Vec<MetricInput>) {
+        let open = OpenReportInput {
+            project_slug: self.project_slug,
+            branch: self.branch,
+            testbed: self.testbed,
+            git_hash: self.git_hash,
+            pr_number: self.pr_number,
+            base_branch: self.base_branch,
+            ci_provider: self.ci_provider,
+            ci_run_url: self.ci_run_url,
+            idempotency_key: self.idempotency_key,
+            testbed_fingerprint: self.testbed_fingerprint,
+        };
+        (open, self.metrics)
+    }
 }
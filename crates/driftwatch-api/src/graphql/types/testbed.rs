@@ -1,12 +1,15 @@
 use async_graphql::{SimpleObject, ID};
 
 use crate::entities::testbed;
+use crate::fingerprint::{self, Fingerprint};
 
 #[derive(SimpleObject, Clone)]
 #[graphql(cache_control(max_age = 300))]
 pub struct Testbed {
     pub id: ID,
     pub name: String,
+    /// Hardware of the machine that submitted the latest report
+    pub fingerprint: Option<Fingerprint>,
     pub created_at: chrono::DateTime<chrono::Utc>,
 }
 
@@ -14,6 +17,7 @@ impl From<testbed::Model> for Testbed {
     fn from(model: testbed::Model) -> Self {
         Self {
             id: ID(model.id.to_string()),
+            fingerprint: fingerprint::stored(&model),
             name: model.name,
             created_at: model.created_at.into(),
         }
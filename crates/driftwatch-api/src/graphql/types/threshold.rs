@@ -1,9 +1,14 @@
-use async_graphql::{InputObject, SimpleObject, ID};
+use async_graphql::dataloader::DataLoader;
+use async_graphql::{
+    ComplexObject, Context, InputObject, MaybeUndefined, Result, SimpleObject, ID,
+};
+use uuid::Uuid;
 
 use crate::entities::threshold;
+use crate::loaders::{BranchLoader, MeasureLoader, TestbedLoader};
 
 #[derive(SimpleObject, Clone)]
-#[graphql(cache_control(max_age = 300))]
+#[graphql(complex, cache_control(max_age = 300))]
 pub struct Threshold {
     pub id: ID,
     pub measure_id: ID,
@@ -30,13 +35,58 @@ impl From<threshold::Model> for Threshold {
     }
 }
 
+#[ComplexObject]
+impl Threshold {
+    async fn measure(&self, ctx: &Context<'_>) -> Result<super::Measure> {
+        let loader = ctx.data::<DataLoader<MeasureLoader>>()?;
+        loader
+            .load_one(Uuid::parse_str(&self.measure_id)?)
+            .await?
+            .ok_or_else(|| "Measure not found".into())
+    }
+
+    /// The branch the threshold is limited to; `null` for every branch
+    async fn branch(&self, ctx: &Context<'_>) -> Result<Option<super::Branch>> {
+        let Some(ref id) = self.branch_id else {
+            return Ok(None);
+        };
+        let loader = ctx.data::<DataLoader<BranchLoader>>()?;
+        loader.load_one(Uuid::parse_str(id)?).await
+    }
+
+    /// The testbed the threshold is limited to; `null` for every testbed
+    async fn testbed(&self, ctx: &Context<'_>) -> Result<Option<super::Testbed>> {
+        let Some(ref id) = self.testbed_id else {
+            return Ok(None);
+        };
+        let loader = ctx.data::<DataLoader<TestbedLoader>>()?;
+        loader.load_one(Uuid::parse_str(id)?).await
+    }
+}
+
+/// The measure, branch and testbed are each given either by ID or by name,
+/// and must already exist in the project.
 #[derive(InputObject)]
 pub struct CreateThresholdInput {
     pub project_slug: String,
-    pub measure_id: ID,
+    pub measure_id: Option<ID>,
+    pub measure: Option<String>,
     pub branch_id: Option<ID>,
+    pub branch: Option<String>,
     pub testbed_id: Option<ID>,
+    pub testbed: Option<String>,
+    /// Allowed increase over the baseline, in percent
     pub upper_boundary: Option<f64>,
+    /// Allowed decrease below the baseline, in percent
     pub lower_boundary: Option<f64>,
+    /// Baseline values needed before the threshold is checked [default: 2]
+    pub min_sample_size: Option<i32>,
+}
+
+/// Fields left out are unchanged; a boundary set to `null` is removed.
+#[derive(InputObject)]
+pub struct UpdateThresholdInput {
+    pub upper_boundary: MaybeUndefined<f64>,
+    pub lower_boundary: MaybeUndefined<f64>,
     pub min_sample_size: Option<i32>,
 }
@@ -1,13 +1,19 @@
+pub mod alerts;
+pub mod archive;
 pub mod auth;
 pub mod cache;
 pub mod config;
 pub mod entities;
+pub mod fingerprint;
 pub mod graphql;
 pub mod grpc;
 pub mod loaders;
 pub mod migrations;
+pub mod reports;
+pub mod rollups;
 
 use std::sync::Arc;
+use std::time::Duration;
 
 use async_graphql::dataloader::DataLoader;
 use async_graphql::http::GraphiQLSource;
@@ -29,8 +35,10 @@ use cache::AppCache;
 use grpc::auth::auth_service_server::AuthServiceServer;
 use grpc::AuthServiceImpl;
 use loaders::{
-    BenchmarkLoader, BranchLoader, MeasureLoader, MetricLoader, TestbedLoader, ThresholdLoader,
+    BaselineLoader, BenchmarkLoader, BranchLoader, MeasureLoader, MetricLoader, ReportLoader,
+    TestbedLoader, ThresholdLoader,
 };
+use rollups::RawRetention;
 use tower_http::cors::{Any, CorsLayer};
 
 use config::Config;
@@ -43,6 +51,7 @@ struct AppState {
     auth: Arc<TsaAuth>,
     auth_service: Arc<AuthServiceImpl>,
     cache: AppCache,
+    retention: RawRetention,
 }
 
 async fn health() -> &'static str {
@@ -79,6 +88,7 @@ async fn graphql_handler(
     request = request.data(state.cache.clone());
     request = request.data(state.auth.clone());
     request = request.data(state.auth_service.clone());
+    request = request.data(state.retention);
 
     request = request.data(DataLoader::new(
         BranchLoader {
@@ -116,6 +126,18 @@ async fn graphql_handler(
         },
         tokio::spawn,
     ));
+    request = request.data(DataLoader::new(
+        ReportLoader {
+            db: state.db.clone(),
+        },
+        tokio::spawn,
+    ));
+    request = request.data(DataLoader::new(
+        BaselineLoader {
+            db: state.db.clone(),
+        },
+        tokio::spawn,
+    ));
 
     if let Some(user) = user {
         request = request.data(user);
@@ -124,6 +146,17 @@ async fn graphql_handler(
     Ok(state.schema.execute(request).await.into())
 }
 
+async fn prune_raw_metrics_daily(db: DatabaseConnection, retention: chrono::Duration) {
+    let mut interval = tokio::time::interval(Duration::from_secs(24 * 60 * 60));
+    loop {
+        interval.tick().await;
+        match rollups::prune_raw_metrics(&db, chrono::Utc::now() - retention).await {
+            Ok(pruned) => tracing::info!(pruned, "Pruned raw metrics past retention"),
+            Err(e) => tracing::error!("Failed to prune raw metrics: {}", e),
+        }
+    }
+}
+
 pub async fn serve(port: Option<u16>, grpc_port: Option<u16>) -> anyhow::Result<()> {
     dotenvy::dotenv().ok();
 
@@ -147,6 +180,11 @@ pub async fn serve(port: Option<u16>, grpc_port: Option<u16>) -> anyhow::Result<
 
     let schema = build_schema();
 
+    let retention = RawRetention(config.raw_metric_retention_days.map(chrono::Duration::days));
+    if let Some(retention) = retention.0 {
+        tokio::spawn(prune_raw_metrics_daily(db.clone(), retention));
+    }
+
     let cache = AppCache::new();
     let state = AppState {
         schema,
@@ -154,6 +192,7 @@ pub async fn serve(port: Option<u16>, grpc_port: Option<u16>) -> anyhow::Result<
         auth: auth.clone(),
         auth_service: auth_service.clone(),
         cache,
+        retention,
     };
 
     let cors = CorsLayer::new()
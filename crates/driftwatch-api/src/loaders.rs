@@ -3,7 +3,8 @@ use sea_orm::{ColumnTrait, DatabaseConnection, EntityTrait, QueryFilter};
 use std::collections::HashMap;
 use uuid::Uuid;
 
-use crate::entities::{self, benchmark, branch, measure, metric, testbed, threshold};
+use crate::alerts::{self, BaselineKey};
+use crate::entities::{self, benchmark, branch, measure, metric, report, testbed, threshold};
 use crate::graphql::types::{Benchmark, Branch, Measure, Metric, Testbed, Threshold};
 
 macro_rules! define_loader {
@@ -57,3 +58,26 @@ define_loader!(
     threshold::Column::Id,
     Threshold
 );
+define_loader!(
+    ReportLoader,
+    entities::Report,
+    report::Column::Id,
+    report::Model
+);
+
+/// Baselines of the series metrics belong to, as `(mean, samples)`.
+pub struct BaselineLoader {
+    pub db: DatabaseConnection,
+}
+
+impl Loader<BaselineKey> for BaselineLoader {
+    type Value = (f64, usize);
+    type Error = async_graphql::Error;
+
+    async fn load(
+        &self,
+        keys: &[BaselineKey],
+    ) -> Result<HashMap<BaselineKey, Self::Value>, Self::Error> {
+        Ok(alerts::baselines(&self.db, keys).await?)
+    }
+}
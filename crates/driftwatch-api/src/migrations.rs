@@ -26,6 +26,33 @@ pub async fn run_migrations(db: &DatabaseConnection) -> Result<(), DbErr> {
                 ALTER TABLE alerts ALTER COLUMN status SET DEFAULT 'active'::alert_status;
             END IF;
         END $$"#,
+        r#"CREATE TABLE IF NOT EXISTS metric_rollups (
+            id UUID PRIMARY KEY,
+            project_id UUID NOT NULL REFERENCES projects(id) ON DELETE CASCADE,
+            branch_id UUID NOT NULL REFERENCES branches(id) ON DELETE CASCADE,
+            testbed_id UUID NOT NULL REFERENCES testbeds(id) ON DELETE CASCADE,
+            benchmark_id UUID NOT NULL REFERENCES benchmarks(id) ON DELETE CASCADE,
+            measure_id UUID NOT NULL REFERENCES measures(id) ON DELETE CASCADE,
+            granularity VARCHAR(16) NOT NULL,
+            bucket_start TIMESTAMPTZ NOT NULL,
+            min_value DOUBLE PRECISION NOT NULL,
+            max_value DOUBLE PRECISION NOT NULL,
+            mean_value DOUBLE PRECISION NOT NULL,
+            median_value DOUBLE PRECISION NOT NULL,
+            sample_count BIGINT NOT NULL,
+            updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+        )"#,
+        r#"CREATE UNIQUE INDEX IF NOT EXISTS idx_metric_rollups_series_bucket
+            ON metric_rollups (branch_id, testbed_id, benchmark_id, measure_id, granularity, bucket_start)"#,
+        "ALTER TABLE reports ADD COLUMN IF NOT EXISTS base_branch VARCHAR",
+        "ALTER TABLE reports ADD COLUMN IF NOT EXISTS ci_provider VARCHAR",
+        "ALTER TABLE reports ADD COLUMN IF NOT EXISTS ci_run_url VARCHAR",
+        "ALTER TABLE reports ADD COLUMN IF NOT EXISTS idempotency_key VARCHAR(255)",
+        r#"CREATE UNIQUE INDEX IF NOT EXISTS idx_reports_project_idempotency_key
+            ON reports (project_id, idempotency_key)"#,
+        "ALTER TABLE reports ADD COLUMN IF NOT EXISTS finalized BOOLEAN NOT NULL DEFAULT TRUE",
+        "ALTER TABLE testbeds ADD COLUMN IF NOT EXISTS fingerprint JSONB",
+        "ALTER TABLE measures ADD COLUMN IF NOT EXISTS higher_is_better BOOLEAN NOT NULL DEFAULT FALSE",
     ];
 
     for sql in migrations {
@@ -0,0 +1,131 @@
+use chrono::Utc;
+use sea_orm::{
+    ActiveModelTrait, ColumnTrait, ConnectionTrait, DbErr, EntityTrait, QueryFilter, Set,
+};
+use uuid::Uuid;
+
+use crate::entities::{self, benchmark, branch, measure, report, testbed};
+
+/// Longest accepted report idempotency key, the column's size.
+pub const MAX_IDEMPOTENCY_KEY_LEN: usize = 255;
+
+// Branches, testbeds and benchmarks are created on first use when a report
+// names them. The unique (project_id, name) index makes a concurrent insert
+// fail, in which case the row the other writer created is returned instead.
+macro_rules! define_find_or_create {
+    ($fn_name:ident, $module:ident, $entity:ty) => {
+        pub async fn $fn_name<C: ConnectionTrait>(
+            db: &C,
+            project_id: Uuid,
+            name: &str,
+        ) -> Result<$module::Model, DbErr> {
+            let find = || {
+                <$entity>::find()
+                    .filter($module::Column::ProjectId.eq(project_id))
+                    .filter($module::Column::Name.eq(name))
+                    .one(db)
+            };
+
+            if let Some(existing) = find().await? {
+                return Ok(existing);
+            }
+
+            let now = Utc::now().fixed_offset();
+            let model = $module::ActiveModel {
+                id: Set(Uuid::new_v4()),
+                project_id: Set(project_id),
+                name: Set(name.to_string()),
+                created_at: Set(now),
+                updated_at: Set(now),
+            };
+
+            match model.insert(db).await {
+                Ok(model) => Ok(model),
+                Err(err) => find().await?.ok_or(err),
+            }
+        }
+    };
+}
+
+define_find_or_create!(find_or_create_branch, branch, entities::Branch);
+define_find_or_create!(find_or_create_benchmark, benchmark, entities::Benchmark);
+
+pub async fn find_or_create_testbed<C: ConnectionTrait>(
+    db: &C,
+    project_id: Uuid,
+    name: &str,
+) -> Result<testbed::Model, DbErr> {
+    let find = || {
+        entities::Testbed::find()
+            .filter(testbed::Column::ProjectId.eq(project_id))
+            .filter(testbed::Column::Name.eq(name))
+            .one(db)
+    };
+
+    if let Some(existing) = find().await? {
+        return Ok(existing);
+    }
+
+    let now = Utc::now().fixed_offset();
+    let model = testbed::ActiveModel {
+        id: Set(Uuid::new_v4()),
+        project_id: Set(project_id),
+        name: Set(name.to_string()),
+        fingerprint: Set(None),
+        created_at: Set(now),
+        updated_at: Set(now),
+    };
+
+    match model.insert(db).await {
+        Ok(model) => Ok(model),
+        Err(err) => find().await?.ok_or(err),
+    }
+}
+
+pub async fn find_or_create_measure<C: ConnectionTrait>(
+    db: &C,
+    project_id: Uuid,
+    name: &str,
+    units: Option<&str>,
+    higher_is_better: bool,
+) -> Result<measure::Model, DbErr> {
+    let find = || {
+        entities::Measure::find()
+            .filter(measure::Column::ProjectId.eq(project_id))
+            .filter(measure::Column::Name.eq(name))
+            .one(db)
+    };
+
+    if let Some(existing) = find().await? {
+        return Ok(existing);
+    }
+
+    let now = Utc::now().fixed_offset();
+    let model = measure::ActiveModel {
+        id: Set(Uuid::new_v4()),
+        project_id: Set(project_id),
+        name: Set(name.to_string()),
+        units: Set(units.map(str::to_string)),
+        higher_is_better: Set(higher_is_better),
+        created_at: Set(now),
+        updated_at: Set(now),
+    };
+
+    match model.insert(db).await {
+        Ok(model) => Ok(model),
+        Err(err) => find().await?.ok_or(err),
+    }
+}
+
+/// The report of a project submitted with `idempotency_key`, if any.
+pub async fn find_by_idempotency_key<C: ConnectionTrait>(
+    db: &C,
+    project_id: Uuid,
+    idempotency_key: &str,
+) -> Result<Option<report::Model>, DbErr> {
+    entities::Report::find()
+        .filter(report::Column::ProjectId.eq(project_id))
+        .filter(report::Column::IdempotencyKey.eq(idempotency_key))
+        .one(db)
+        .await
+}
@@ -0,0 +1,340 @@
+use chrono::{DateTime, Datelike, Duration, NaiveTime, Utc};
+use sea_orm::prelude::DateTimeWithTimeZone;
+use sea_orm::{ConnectionTrait, DbBackend, DbErr, Statement, Value};
+use uuid::Uuid;
+
+use crate::entities::{metric_rollup::Granularity, report};
+
+/// Ranges up to this many days are served from raw metrics.
+const RAW_MAX_SPAN_DAYS: i64 = 31;
+/// Ranges up to this many days are served from daily rollups, longer ones from weekly.
+const DAILY_MAX_SPAN_DAYS: i64 = 366;
+
+// Recomputes every bucket of one granularity that has raw metrics in
+// finalized reports in [$3, $4), optionally restricted to one branch/testbed
+// pair; an open report is rolled up when it is finalized. Buckets are
+// aligned in UTC and weeks start on Monday, matching `Granularity::bucket_start`.
+// The conflict clause decides whether existing buckets are overwritten.
+macro_rules! refresh_sql {
+    ($on_conflict:literal) => {
+        concat!(
+            r#"
+INSERT INTO metric_rollups (
+    id, project_id, branch_id, testbed_id, benchmark_id, measure_id, granularity,
+    bucket_start, min_value, max_value, mean_value, median_value, sample_count, updated_at
+)
+SELECT
+    gen_random_uuid(),
+    r.project_id,
+    r.branch_id,
+    r.testbed_id,
+    m.benchmark_id,
+    m.measure_id,
+    $1,
+    date_trunc($2, r.created_at AT TIME ZONE 'UTC') AT TIME ZONE 'UTC',
+    MIN(m.value),
+    MAX(m.value),
+    AVG(m.value),
+    percentile_cont(0.5) WITHIN GROUP (ORDER BY m.value),
+    COUNT(*),
+    NOW()
+FROM metrics m
+JOIN reports r ON r.id = m.report_id
+WHERE r.finalized
+  AND r.created_at >= $3
+  AND r.created_at < $4
+  AND ($5::uuid IS NULL OR r.branch_id = $5)
+  AND ($6::uuid IS NULL OR r.testbed_id = $6)
+GROUP BY 2, 3, 4, 5, 6, 8
+ON CONFLICT (branch_id, testbed_id, benchmark_id, measure_id, granularity, bucket_start)
+"#,
+            $on_conflict
+        )
+    };
+}
+
+const REFRESH_SQL: &str = refresh_sql!(
+    r#"
+DO UPDATE SET
+    min_value = EXCLUDED.min_value,
+    max_value = EXCLUDED.max_value,
+    mean_value = EXCLUDED.mean_value,
+    median_value = EXCLUDED.median_value,
+    sample_count = EXCLUDED.sample_count,
+    updated_at = EXCLUDED.updated_at
+"#
+);
+
+// Only adds missing buckets: one that exists may already have lost part of
+// its raw metrics to an earlier prune, so recomputing it would shrink it.
+const BACKFILL_SQL: &str = refresh_sql!("DO NOTHING");
+
+// Start of the oldest finalized report before $1 with raw metrics that
+// pruning would delete.
+const PRUNABLE_FROM_SQL: &str = r#"
+SELECT MIN(r.created_at) AS oldest
+FROM reports r
+JOIN metrics m ON m.report_id = r.id
+WHERE r.finalized
+  AND r.created_at < $1
+  AND NOT EXISTS (SELECT 1 FROM alerts a WHERE a.metric_id = m.id)
+"#;
+
+// Deletes the raw metrics of finalized reports in [$1, $2) that are covered
+// by both a daily and a weekly rollup. Metrics an alert points at are kept, so
+// deleting them does not take the alert with it; reports are never deleted.
+const PRUNE_SQL: &str = r#"
+DELETE FROM metrics m
+USING reports r
+WHERE r.id = m.report_id
+  AND r.finalized
+  AND r.created_at >= $1
+  AND r.created_at < $2
+  AND NOT EXISTS (SELECT 1 FROM alerts a WHERE a.metric_id = m.id)
+  AND EXISTS (
+      SELECT 1 FROM metric_rollups mr
+      WHERE mr.branch_id = r.branch_id
+        AND mr.testbed_id = r.testbed_id
+        AND mr.benchmark_id = m.benchmark_id
+        AND mr.measure_id = m.measure_id
+        AND mr.granularity = 'daily'
+        AND mr.bucket_start = date_trunc('day', r.created_at AT TIME ZONE 'UTC') AT TIME ZONE 'UTC'
+  )
+  AND EXISTS (
+      SELECT 1 FROM metric_rollups mr
+      WHERE mr.branch_id = r.branch_id
+        AND mr.testbed_id = r.testbed_id
+        AND mr.benchmark_id = m.benchmark_id
+        AND mr.measure_id = m.measure_id
+        AND mr.granularity = 'weekly'
+        AND mr.bucket_start = date_trunc('week', r.created_at AT TIME ZONE 'UTC') AT TIME ZONE 'UTC'
+  )
+"#;
+
+impl Granularity {
+    pub const ALL: [Granularity; 2] = [Granularity::Daily, Granularity::Weekly];
+
+    /// Start of the bucket containing `ts`.
+    pub fn bucket_start(self, ts: DateTime<Utc>) -> DateTime<Utc> {
+        let day = ts.date_naive();
+        let day = match self {
+            Granularity::Daily => day,
+            Granularity::Weekly => {
+                day - Duration::days(day.weekday().num_days_from_monday() as i64)
+            }
+        };
+        day.and_time(NaiveTime::MIN).and_utc()
+    }
+
+    pub fn bucket_len(self) -> Duration {
+        match self {
+            Granularity::Daily => Duration::days(1),
+            Granularity::Weekly => Duration::weeks(1),
+        }
+    }
+
+    fn date_trunc_field(self) -> &'static str {
+        match self {
+            Granularity::Daily => "day",
+            Granularity::Weekly => "week",
+        }
+    }
+
+    fn as_db_str(self) -> &'static str {
+        match self {
+            Granularity::Daily => "daily",
+            Granularity::Weekly => "weekly",
+        }
+    }
+}
+
+/// How long raw metrics are kept before only rollups remain.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct RawRetention(pub Option<Duration>);
+
+impl RawRetention {
+    /// Oldest instant for which raw metrics are still guaranteed to exist.
+    ///
+    /// Pruning only removes whole weeks, so this is the start of the week
+    /// containing the retention cutoff.
+    pub fn horizon(&self, now: DateTime<Utc>) -> Option<DateTime<Utc>> {
+        self.0
+            .map(|retention| Granularity::Weekly.bucket_start(now - retention))
+    }
+}
+
+/// Picks the data source for a history query: `None` means raw metrics.
+pub fn choose_granularity(
+    from: DateTime<Utc>,
+    to: DateTime<Utc>,
+    raw_horizon: Option<DateTime<Utc>>,
+) -> Option<Granularity> {
+    let span = to - from;
+    let raw_available = raw_horizon.is_none_or(|horizon| from >= horizon);
+
+    if span <= Duration::days(RAW_MAX_SPAN_DAYS) && raw_available {
+        None
+    } else if span <= Duration::days(DAILY_MAX_SPAN_DAYS) {
+        Some(Granularity::Daily)
+    } else {
+        Some(Granularity::Weekly)
+    }
+}
+
+#[allow(clippy::too_many_arguments)]
+async fn refresh_range<C: ConnectionTrait>(
+    db: &C,
+    sql: &str,
+    granularity: Granularity,
+    from: DateTime<Utc>,
+    to: DateTime<Utc>,
+    branch_id: Option<Uuid>,
+    testbed_id: Option<Uuid>,
+) -> Result<(), DbErr> {
+    db.execute(Statement::from_sql_and_values(
+        DbBackend::Postgres,
+        sql,
+        [
+            Value::from(granularity.as_db_str()),
+            Value::from(granularity.date_trunc_field()),
+            Value::from(from),
+            Value::from(to),
+            Value::from(branch_id),
+            Value::from(testbed_id),
+        ],
+    ))
+    .await?;
+    Ok(())
+}
+
+/// Recomputes the daily and weekly buckets a newly stored report falls into.
+pub async fn refresh_for_report<C: ConnectionTrait>(
+    db: &C,
+    report: &report::Model,
+) -> Result<(), DbErr> {
+    let created_at = report.created_at.with_timezone(&Utc);
+
+    for granularity in Granularity::ALL {
+        let start = granularity.bucket_start(created_at);
+        refresh_range(
+            db,
+            REFRESH_SQL,
+            granularity,
+            start,
+            start + granularity.bucket_len(),
+            Some(report.branch_id),
+            Some(report.testbed_id),
+        )
+        .await?;
+    }
+
+    Ok(())
+}
+
+/// Deletes raw metrics of reports older than `older_than` once rollups
+/// cover them; the reports, their alerts and flamegraphs stay.
+///
+/// The cutoff is rounded down to a week boundary so no rollup bucket is left
+/// partially backed by raw data. Buckets missing in the window being pruned,
+/// e.g. for reports stored before rollups existed, are computed first.
+/// Returns the number of metrics deleted.
+pub async fn prune_raw_metrics<C: ConnectionTrait>(
+    db: &C,
+    older_than: DateTime<Utc>,
+) -> Result<u64, DbErr> {
+    let cutoff = Granularity::Weekly.bucket_start(older_than);
+
+    let oldest: Option<DateTimeWithTimeZone> = db
+        .query_one(Statement::from_sql_and_values(
+            DbBackend::Postgres,
+            PRUNABLE_FROM_SQL,
+            [Value::from(cutoff)],
+        ))
+        .await?
+        .map(|row| row.try_get("", "oldest"))
+        .transpose()?
+        .flatten();
+    let Some(oldest) = oldest else {
+        return Ok(0);
+    };
+    let from = Granularity::Weekly.bucket_start(oldest.with_timezone(&Utc));
+
+    for granularity in Granularity::ALL {
+        refresh_range(db, BACKFILL_SQL, granularity, from, cutoff, None, None).await?;
+    }
+
+    let result = db
+        .execute(Statement::from_sql_and_values(
+            DbBackend::Postgres,
+            PRUNE_SQL,
+            [Value::from(from), Value::from(cutoff)],
+        ))
+        .await?;
+
+    Ok(result.rows_affected())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    fn at(y: i32, m: u32, d: u32, h: u32) -> DateTime<Utc> {
+        Utc.with_ymd_and_hms(y, m, d, h, 0, 0).unwrap()
+    }
+
+    #[test]
+    fn test_bucket_start() {
+        // 2024-12-26 is a Thursday
+        let ts = at(2024, 12, 26, 15);
+        assert_eq!(Granularity::Daily.bucket_start(ts), at(2024, 12, 26, 0));
+        assert_eq!(Granularity::Weekly.bucket_start(ts), at(2024, 12, 23, 0));
+
+        // Weeks start on Monday, so a Monday is its own bucket start
+        let monday = at(2024, 12, 23, 0);
+        assert_eq!(Granularity::Weekly.bucket_start(monday), monday);
+
+        // Weekly buckets may span a year boundary
+        assert_eq!(
+            Granularity::Weekly.bucket_start(at(2025, 1, 1, 9)),
+            at(2024, 12, 30, 0)
+        );
+    }
+
+    #[test]
+    fn test_choose_granularity_by_span() {
+        let to = at(2024, 12, 26, 0);
+
+        assert_eq!(choose_granularity(to - Duration::days(7), to, None), None);
+        assert_eq!(
+            choose_granularity(to - Duration::days(90), to, None),
+            Some(Granularity::Daily)
+        );
+        assert_eq!(
+            choose_granularity(to - Duration::days(730), to, None),
+            Some(Granularity::Weekly)
+        );
+    }
+
+    #[test]
+    fn test_choose_granularity_respects_raw_horizon() {
+        let to = at(2024, 12, 26, 0);
+        let from = to - Duration::days(7);
+
+        assert_eq!(choose_granularity(from, to, Some(from)), None);
+        assert_eq!(
+            choose_granularity(from, to, Some(to - Duration::days(3))),
+            Some(Granularity::Daily)
+        );
+    }
+
+    #[test]
+    fn test_raw_retention_horizon_is_week_aligned() {
+        let now = at(2024, 12, 26, 15);
+        assert_eq!(RawRetention(None).horizon(now), None);
+        assert_eq!(
+            RawRetention(Some(Duration::days(2))).horizon(now),
+            Some(at(2024, 12, 23, 0))
+        );
+    }
+}
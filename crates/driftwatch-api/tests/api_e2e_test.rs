@@ -102,6 +102,35 @@ struct CreateThresholdData {
     create_threshold: ThresholdData,
 }
 
+#[derive(Debug, Deserialize)]
+struct NamedThresholdData {
+    id: String,
+    #[serde(rename = "upperBoundary")]
+    upper_boundary: Option<f64>,
+    #[serde(rename = "lowerBoundary")]
+    lower_boundary: Option<f64>,
+    measure: NameData,
+    branch: Option<NameData>,
+    testbed: Option<NameData>,
+}
+
+#[derive(Debug, Deserialize)]
+struct NameData {
+    name: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct CreateNamedThresholdData {
+    #[serde(rename = "createThreshold")]
+    create_threshold: NamedThresholdData,
+}
+
+#[derive(Debug, Deserialize)]
+struct UpdateThresholdData {
+    #[serde(rename = "updateThreshold")]
+    update_threshold: NamedThresholdData,
+}
+
 #[derive(Debug, Deserialize)]
 struct DeleteThresholdData {
     #[serde(rename = "deleteThreshold")]
@@ -123,6 +152,97 @@ struct ProjectWithMeasures {
 struct MeasureData {
     id: String,
     name: String,
+    #[serde(rename = "higherIsBetter")]
+    higher_is_better: bool,
+}
+
+#[derive(Debug, Deserialize)]
+struct ReportData {
+    id: String,
+    #[serde(rename = "gitHash")]
+    git_hash: Option<String>,
+    metrics: Vec<ReportMetricData>,
+    alerts: Vec<ReportAlertData>,
+    warnings: Vec<String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct ReportMetricData {
+    value: f64,
+    #[serde(rename = "baselineValue")]
+    baseline_value: Option<f64>,
+}
+
+#[derive(Debug, Deserialize)]
+struct ReportAlertData {
+    #[serde(rename = "percentChange")]
+    percent_change: f64,
+    #[serde(rename = "baselineValue")]
+    baseline_value: f64,
+    #[serde(rename = "currentValue")]
+    current_value: f64,
+}
+
+#[derive(Debug, Deserialize)]
+struct CreateReportData {
+    #[serde(rename = "createReport")]
+    create_report: ReportData,
+}
+
+#[derive(Debug, Deserialize)]
+struct OpenReportData {
+    #[serde(rename = "openReport")]
+    open_report: ReportData,
+}
+
+#[derive(Debug, Deserialize)]
+struct AppendMetricsData {
+    #[serde(rename = "appendMetrics")]
+    append_metrics: ReportData,
+}
+
+#[derive(Debug, Deserialize)]
+struct FinalizeReportData {
+    #[serde(rename = "finalizeReport")]
+    finalize_report: ReportData,
+}
+
+#[derive(Debug, Deserialize)]
+struct ExportProjectData {
+    #[serde(rename = "exportProject")]
+    export_project: serde_json::Value,
+}
+
+#[derive(Debug, Deserialize)]
+struct ImportProjectData {
+    #[serde(rename = "importProject")]
+    import_project: ProjectData,
+}
+
+#[derive(Debug, Deserialize)]
+struct MetricHistoryData {
+    project: Option<ProjectWithHistory>,
+}
+
+#[derive(Debug, Deserialize)]
+struct ProjectWithHistory {
+    #[serde(rename = "metricHistory")]
+    metric_history: HistoryData,
+}
+
+#[derive(Debug, Deserialize)]
+struct HistoryData {
+    resolution: String,
+    points: Vec<HistoryPointData>,
+}
+
+#[derive(Debug, Deserialize)]
+struct HistoryPointData {
+    value: f64,
+    min: Option<f64>,
+    max: Option<f64>,
+    median: Option<f64>,
+    count: i64,
 }
 
 const CREATE_PROJECT: &str = r#"
@@ -180,6 +300,7 @@ query GetProjectWithMeasures($slug: String!) {
         measures {
             id
             name
+            higherIsBetter
         }
     }
 }
@@ -263,12 +384,140 @@ mutation CreateThreshold($input: CreateThresholdInput!) {
 }
 "#;
 
+const CREATE_NAMED_THRESHOLD: &str = r#"
+mutation CreateThreshold($input: CreateThresholdInput!) {
+    createThreshold(input: $input) {
+        id
+        upperBoundary
+        lowerBoundary
+        measure { name }
+        branch { name }
+        testbed { name }
+    }
+}
+"#;
+
+const UPDATE_THRESHOLD: &str = r#"
+mutation UpdateThreshold($id: ID!, $input: UpdateThresholdInput!) {
+    updateThreshold(id: $id, input: $input) {
+        id
+        upperBoundary
+        lowerBoundary
+        measure { name }
+        branch { name }
+        testbed { name }
+    }
+}
+"#;
+
 const DELETE_THRESHOLD: &str = r#"
 mutation DeleteThreshold($id: ID!) {
     deleteThreshold(id: $id)
 }
 "#;
 
+const CREATE_REPORT: &str = r#"
+mutation CreateReport($input: CreateReportInput!) {
+    createReport(input: $input) {
+        id
+        gitHash
+        metrics {
+            value
+            baselineValue
+        }
+        alerts {
+            percentChange
+            baselineValue
+            currentValue
+        }
+        warnings
+    }
+}
+"#;
+
+const REPORT_FIELDS: &str = r#"
+    id
+    gitHash
+    metrics {
+        value
+        baselineValue
+    }
+    alerts {
+        percentChange
+        baselineValue
+        currentValue
+    }
+    warnings
+"#;
+
+fn open_report_mutation() -> String {
+    format!(
+        "mutation OpenReport($input: OpenReportInput!) {{ openReport(input: $input) {{ {} }} }}",
+        REPORT_FIELDS
+    )
+}
+
+fn append_metrics_mutation() -> String {
+    format!(
+        "mutation AppendMetrics($reportId: ID!, $metrics: [MetricInput!]!) {{ \
+         appendMetrics(reportId: $reportId, metrics: $metrics) {{ {} }} }}",
+        REPORT_FIELDS
+    )
+}
+
+fn finalize_report_mutation() -> String {
+    format!(
+        "mutation FinalizeReport($reportId: ID!) {{ finalizeReport(reportId: $reportId) {{ {} }} }}",
+        REPORT_FIELDS
+    )
+}
+
+const EXPORT_PROJECT: &str = r#"
+query ExportProject($slug: String!) {
+    exportProject(slug: $slug)
+}
+"#;
+
+const IMPORT_PROJECT: &str = r#"
+mutation ImportProject($slug: String!, $name: String, $archive: JSON!) {
+    importProject(slug: $slug, name: $name, archive: $archive) {
+        id
+        slug
+        name
+        description
+        public
+        githubRepo
+        githubPrComments
+        githubStatusChecks
+        hasGithubToken
+    }
+}
+"#;
+
+const GET_METRIC_HISTORY: &str = r#"
+query GetMetricHistory($slug: String!, $from: DateTime!, $resolution: HistoryResolution) {
+    project(slug: $slug) {
+        metricHistory(
+            benchmark: "fibonacci/10",
+            measure: "latency",
+            branch: "main",
+            testbed: "ci-linux",
+            from: $from,
+            resolution: $resolution
+        ) {
+            resolution
+            points {
+                value
+                min
+                max
+                median
+                count
+            }
+        }
+    }
+}
+"#;
+
 #[tokio::test]
 async fn test_create_and_get_project() {
     let server = test_server!();
@@ -745,6 +994,136 @@ async fn test_threshold_deletion() {
     assert!(result.delete_threshold);
 }
 
+#[tokio::test]
+async fn test_threshold_by_name_and_update() {
+    let server = test_server!();
+    let token = server.create_test_token("user-1");
+
+    let _: CreateProjectData = server
+        .graphql(
+            CREATE_PROJECT,
+            Some(serde_json::json!({
+                "input": {
+                    "slug": "threshold-names",
+                    "name": "Threshold Names"
+                }
+            })),
+            Some(&token),
+        )
+        .await
+        .unwrap();
+
+    // A report creates the branch and testbed the threshold names
+    let _: CreateReportData = server
+        .graphql(
+            CREATE_REPORT,
+            Some(serde_json::json!({
+                "input": {
+                    "projectSlug": "threshold-names",
+                    "branch": "main",
+                    "testbed": "ci-linux",
+                    "metrics": [{
+                        "benchmark": "parse",
+                        "measure": "latency",
+                        "value": 100.0
+                    }]
+                }
+            })),
+            Some(&token),
+        )
+        .await
+        .unwrap();
+
+    let result: CreateNamedThresholdData = server
+        .graphql(
+            CREATE_NAMED_THRESHOLD,
+            Some(serde_json::json!({
+                "input": {
+                    "projectSlug": "threshold-names",
+                    "measure": "latency",
+                    "branch": "main",
+                    "testbed": "ci-linux",
+                    "upperBoundary": 10.0
+                }
+            })),
+            Some(&token),
+        )
+        .await
+        .unwrap();
+
+    let threshold = result.create_threshold;
+    assert_eq!(threshold.measure.name, "latency");
+    assert_eq!(threshold.branch.unwrap().name, "main");
+    assert_eq!(threshold.testbed.unwrap().name, "ci-linux");
+
+    let result: UpdateThresholdData = server
+        .graphql(
+            UPDATE_THRESHOLD,
+            Some(serde_json::json!({
+                "id": threshold.id,
+                "input": { "upperBoundary": null, "lowerBoundary": 5.0 }
+            })),
+            Some(&token),
+        )
+        .await
+        .unwrap();
+
+    assert_eq!(result.update_threshold.upper_boundary, None);
+    assert_eq!(result.update_threshold.lower_boundary, Some(5.0));
+
+    let result = server
+        .graphql::<UpdateThresholdData>(
+            UPDATE_THRESHOLD,
+            Some(serde_json::json!({
+                "id": threshold.id,
+                "input": { "lowerBoundary": -5.0 }
+            })),
+            Some(&token),
+        )
+        .await;
+    assert!(result.errors.is_some());
+
+    // Unknown measures, branches and testbeds are not created, thresholds
+    // need a boundary and boundaries cannot be negative
+    for input in [
+        serde_json::json!({
+            "projectSlug": "threshold-names",
+            "measure": "latancy",
+            "upperBoundary": 10.0
+        }),
+        serde_json::json!({
+            "projectSlug": "threshold-names",
+            "measure": "latency",
+            "branch": "develop",
+            "upperBoundary": 10.0
+        }),
+        serde_json::json!({
+            "projectSlug": "threshold-names",
+            "measure": "latency",
+            "testbed": "ci-macos",
+            "upperBoundary": 10.0
+        }),
+        serde_json::json!({
+            "projectSlug": "threshold-names",
+            "measure": "latency"
+        }),
+        serde_json::json!({
+            "projectSlug": "threshold-names",
+            "measure": "latency",
+            "lowerBoundary": -5.0
+        }),
+    ] {
+        let result = server
+            .graphql::<CreateNamedThresholdData>(
+                CREATE_NAMED_THRESHOLD,
+                Some(serde_json::json!({ "input": input })),
+                Some(&token),
+            )
+            .await;
+        assert!(result.errors.is_some());
+    }
+}
+
 #[tokio::test]
 async fn test_cross_user_threshold_rejection() {
     let server = test_server!();
@@ -1121,3 +1500,789 @@ async fn test_large_project_list() {
 
     assert_eq!(result.projects.len(), 20);
 }
+
+#[tokio::test]
+async fn test_create_report_and_metric_history() {
+    let server = test_server!();
+    let token = server.create_test_token("user-1");
+
+    let _: CreateProjectData = server
+        .graphql(
+            CREATE_PROJECT,
+            Some(serde_json::json!({
+                "input": {
+                    "slug": "history-test",
+                    "name": "History Test"
+                }
+            })),
+            Some(&token),
+        )
+        .await
+        .unwrap();
+
+    for (hash, value) in [("abc123", 100.0), ("def456", 200.0), ("0a1b2c", 600.0)] {
+        let result: CreateReportData = server
+            .graphql(
+                CREATE_REPORT,
+                Some(serde_json::json!({
+                    "input": {
+                        "projectSlug": "history-test",
+                        "branch": "main",
+                        "testbed": "ci-linux",
+                        "gitHash": hash,
+                        "metrics": [{
+                            "benchmark": "fibonacci/10",
+                            "measure": "latency",
+                            "value": value,
+                            "lowerValue": value - 1.0,
+                            "upperValue": value + 1.0
+                        }]
+                    }
+                })),
+                Some(&token),
+            )
+            .await
+            .unwrap();
+
+        assert_eq!(result.create_report.git_hash.as_deref(), Some(hash));
+        assert_eq!(result.create_report.metrics.len(), 1);
+        assert_eq!(result.create_report.metrics[0].value, value);
+        assert!(!result.create_report.id.is_empty());
+    }
+
+    let from = (chrono::Utc::now() - chrono::Duration::days(1)).to_rfc3339();
+
+    let raw: MetricHistoryData = server
+        .graphql(
+            GET_METRIC_HISTORY,
+            Some(serde_json::json!({ "slug": "history-test", "from": from })),
+            Some(&token),
+        )
+        .await
+        .unwrap();
+
+    let raw = raw.project.unwrap().metric_history;
+    assert_eq!(raw.resolution, "RAW");
+    assert_eq!(raw.points.len(), 3);
+    assert_eq!(raw.points[2].value, 600.0);
+
+    let daily: MetricHistoryData = server
+        .graphql(
+            GET_METRIC_HISTORY,
+            Some(serde_json::json!({
+                "slug": "history-test",
+                "from": from,
+                "resolution": "DAILY"
+            })),
+            Some(&token),
+        )
+        .await
+        .unwrap();
+
+    let daily = daily.project.unwrap().metric_history;
+    assert_eq!(daily.resolution, "DAILY");
+    let count: i64 = daily.points.iter().map(|p| p.count).sum();
+    assert_eq!(count, 3);
+    if let [bucket] = daily.points.as_slice() {
+        assert_eq!(bucket.value, 300.0);
+        assert_eq!(bucket.min, Some(100.0));
+        assert_eq!(bucket.max, Some(600.0));
+        assert_eq!(bucket.median, Some(200.0));
+    }
+}
+
+#[tokio::test]
+async fn test_measure_direction_is_set_when_created() {
+    let server = test_server!();
+    let token = server.create_test_token("user-1");
+
+    let _: CreateProjectData = server
+        .graphql(
+            CREATE_PROJECT,
+            Some(serde_json::json!({
+                "input": {
+                    "slug": "direction-test",
+                    "name": "Direction Test"
+                }
+            })),
+            Some(&token),
+        )
+        .await
+        .unwrap();
+
+    // Only the report creating the measure decides its direction
+    for higher_is_better in [true, false] {
+        let _: CreateReportData = server
+            .graphql(
+                CREATE_REPORT,
+                Some(serde_json::json!({
+                    "input": {
+                        "projectSlug": "direction-test",
+                        "branch": "main",
+                        "testbed": "ci-linux",
+                        "metrics": [{
+                            "benchmark": "parse",
+                            "measure": "throughput",
+                            "units": "B/s",
+                            "higherIsBetter": higher_is_better,
+                            "value": 1000.0
+                        }]
+                    }
+                })),
+                Some(&token),
+            )
+            .await
+            .unwrap();
+    }
+
+    let project: ProjectWithMeasuresData = server
+        .graphql(
+            GET_PROJECT_WITH_MEASURES,
+            Some(serde_json::json!({ "slug": "direction-test" })),
+            Some(&token),
+        )
+        .await
+        .unwrap();
+    let measures = project.project.unwrap().measures;
+    let direction = |name: &str| {
+        measures
+            .iter()
+            .find(|m| m.name == name)
+            .map(|m| m.higher_is_better)
+    };
+    assert_eq!(direction("latency"), Some(false));
+    assert_eq!(direction("throughput"), Some(true));
+}
+
+#[tokio::test]
+async fn test_create_report_with_idempotency_key_is_stored_once() {
+    let server = test_server!();
+    let token = server.create_test_token("user-1");
+
+    let _: CreateProjectData = server
+        .graphql(
+            CREATE_PROJECT,
+            Some(serde_json::json!({
+                "input": {
+                    "slug": "idempotency-test",
+                    "name": "Idempotency Test"
+                }
+            })),
+            Some(&token),
+        )
+        .await
+        .unwrap();
+
+    let submit = |key: &'static str, value: f64| {
+        server.graphql::<CreateReportData>(
+            CREATE_REPORT,
+            Some(serde_json::json!({
+                "input": {
+                    "projectSlug": "idempotency-test",
+                    "branch": "main",
+                    "testbed": "ci-linux",
+                    "idempotencyKey": key,
+                    "metrics": [{
+                        "benchmark": "fibonacci/10",
+                        "measure": "latency",
+                        "value": value
+                    }]
+                }
+            })),
+            Some(&token),
+        )
+    };
+
+    let first = submit("run-42-attempt-1", 100.0)
+        .await
+        .unwrap()
+        .create_report;
+    // A retried submission returns the original report and its metrics
+    let retried = submit("run-42-attempt-1", 250.0)
+        .await
+        .unwrap()
+        .create_report;
+    assert!(first.warnings.is_empty());
+    assert_eq!(retried.id, first.id);
+    assert_eq!(retried.metrics.len(), 1);
+    assert_eq!(retried.metrics[0].value, 100.0);
+    // The caller learns its metrics were not stored
+    assert_eq!(retried.warnings.len(), 1);
+    assert!(retried.warnings[0].contains("'run-42-attempt-1'"));
+
+    let other = submit("run-42-attempt-2", 100.0)
+        .await
+        .unwrap()
+        .create_report;
+    assert_ne!(other.id, first.id);
+    // The retry did not become part of the baseline
+    assert_eq!(other.metrics[0].baseline_value, Some(100.0));
+
+    let result = server
+        .graphql::<CreateReportData>(
+            CREATE_REPORT,
+            Some(serde_json::json!({
+                "input": {
+                    "projectSlug": "idempotency-test",
+                    "branch": "main",
+                    "testbed": "ci-linux",
+                    "idempotencyKey": "",
+                    "metrics": [{
+                        "benchmark": "fibonacci/10",
+                        "measure": "latency",
+                        "value": 1.0
+                    }]
+                }
+            })),
+            Some(&token),
+        )
+        .await;
+    assert!(result.errors.is_some());
+}
+
+#[tokio::test]
+async fn test_testbed_fingerprint_change_is_reported() {
+    let server = test_server!();
+    let token = server.create_test_token("user-1");
+
+    let _: CreateProjectData = server
+        .graphql(
+            CREATE_PROJECT,
+            Some(serde_json::json!({
+                "input": {
+                    "slug": "fingerprint-test",
+                    "name": "Fingerprint Test"
+                }
+            })),
+            Some(&token),
+        )
+        .await
+        .unwrap();
+
+    let submit = |fingerprint: serde_json::Value| {
+        server.graphql::<CreateReportData>(
+            CREATE_REPORT,
+            Some(serde_json::json!({
+                "input": {
+                    "projectSlug": "fingerprint-test",
+                    "branch": "main",
+                    "testbed": "ci-linux",
+                    "testbedFingerprint": fingerprint,
+                    "metrics": [{
+                        "benchmark": "fibonacci/10",
+                        "measure": "latency",
+                        "value": 100.0
+                    }]
+                }
+            })),
+            Some(&token),
+        )
+    };
+    let runner = serde_json::json!({
+        "cpuModel": "AMD EPYC 7763 64-Core Processor",
+        "cpuCores": 4,
+        "memoryMb": 16000,
+        "kernel": "6.5.0-1025-azure",
+        "virtualization": "vm"
+    });
+
+    let first = submit(runner.clone()).await.unwrap().create_report;
+    assert!(first.warnings.is_empty());
+    let same = submit(runner).await.unwrap().create_report;
+    assert!(same.warnings.is_empty());
+
+    // A client that knows less about the machine is not a change
+    let partial = submit(serde_json::json!({ "cpuCores": 4 }))
+        .await
+        .unwrap()
+        .create_report;
+    assert!(partial.warnings.is_empty());
+
+    let upgraded = submit(serde_json::json!({
+        "cpuModel": "AMD EPYC 7763 64-Core Processor",
+        "cpuCores": 8,
+        "memoryMb": 32000
+    }))
+    .await
+    .unwrap()
+    .create_report;
+    assert_eq!(upgraded.warnings.len(), 1);
+    assert!(upgraded.warnings[0].contains("'ci-linux'"));
+    assert!(upgraded.warnings[0].contains("cpu_cores 4 -> 8"));
+    assert!(upgraded.warnings[0].contains("memory_mb 16000 -> 32000"));
+}
+
+#[tokio::test]
+async fn test_sharded_report_is_evaluated_once_on_finalize() {
+    let server = test_server!();
+    let token = server.create_test_token("user-1");
+
+    let _: CreateProjectData = server
+        .graphql(
+            CREATE_PROJECT,
+            Some(serde_json::json!({
+                "input": {
+                    "slug": "shard-test",
+                    "name": "Shard Test"
+                }
+            })),
+            Some(&token),
+        )
+        .await
+        .unwrap();
+
+    let project: ProjectWithMeasuresData = server
+        .graphql(
+            GET_PROJECT_WITH_MEASURES,
+            Some(serde_json::json!({ "slug": "shard-test" })),
+            Some(&token),
+        )
+        .await
+        .unwrap();
+    let measure_id = &project.project.unwrap().measures[0].id;
+
+    let _: CreateThresholdData = server
+        .graphql(
+            CREATE_THRESHOLD,
+            Some(serde_json::json!({
+                "input": {
+                    "projectSlug": "shard-test",
+                    "measureId": measure_id,
+                    "upperBoundary": 10.0,
+                    "minSampleSize": 2
+                }
+            })),
+            Some(&token),
+        )
+        .await
+        .unwrap();
+
+    for value in [100.0, 100.0] {
+        let _: CreateReportData = server
+            .graphql(
+                CREATE_REPORT,
+                Some(serde_json::json!({
+                    "input": {
+                        "projectSlug": "shard-test",
+                        "branch": "main",
+                        "testbed": "ci-linux",
+                        "metrics": [{
+                            "benchmark": "fibonacci/10",
+                            "measure": "latency",
+                            "value": value
+                        }]
+                    }
+                })),
+                Some(&token),
+            )
+            .await
+            .unwrap();
+    }
+
+    // Every shard opens the report with the same key and gets the same one
+    let open = || {
+        server.graphql::<OpenReportData>(
+            &open_report_mutation(),
+            Some(serde_json::json!({
+                "input": {
+                    "projectSlug": "shard-test",
+                    "branch": "main",
+                    "testbed": "ci-linux",
+                    "gitHash": "abc123",
+                    "idempotencyKey": "run-7"
+                }
+            })),
+            Some(&token),
+        )
+    };
+    let report = open().await.unwrap().open_report;
+    // Every shard opens the same report, which is not worth a warning
+    let reopened = open().await.unwrap().open_report;
+    assert_eq!(reopened.id, report.id);
+    assert!(reopened.warnings.is_empty());
+    assert!(report.metrics.is_empty());
+
+    let append = |benchmark: &'static str, value: f64| {
+        server.graphql::<AppendMetricsData>(
+            &append_metrics_mutation(),
+            Some(serde_json::json!({
+                "reportId": report.id,
+                "metrics": [{
+                    "benchmark": benchmark,
+                    "measure": "latency",
+                    "value": value
+                }]
+            })),
+            Some(&token),
+        )
+    };
+    append("fibonacci/10", 150.0).await.unwrap();
+    let appended = append("fibonacci/20", 5.0).await.unwrap().append_metrics;
+    // A retried append does not add the metric twice
+    let retried = append("fibonacci/10", 150.0).await.unwrap().append_metrics;
+    assert_eq!(appended.metrics.len(), 2);
+    assert_eq!(retried.metrics.len(), 2);
+    // A metric appended again replaces the earlier one, the last in a batch
+    // winning
+    let replaced = server
+        .graphql::<AppendMetricsData>(
+            &append_metrics_mutation(),
+            Some(serde_json::json!({
+                "reportId": report.id,
+                "metrics": [
+                    { "benchmark": "fibonacci/20", "measure": "latency", "value": 6.0 },
+                    { "benchmark": "fibonacci/20", "measure": "latency", "value": 7.0 }
+                ]
+            })),
+            Some(&token),
+        )
+        .await
+        .unwrap()
+        .append_metrics;
+    let mut values: Vec<f64> = replaced.metrics.iter().map(|m| m.value).collect();
+    values.sort_by(f64::total_cmp);
+    assert_eq!(values, [7.0, 150.0]);
+    // Thresholds wait for the complete report
+    assert!(retried.alerts.is_empty());
+
+    // History and rollups leave the open report out until it is finalized
+    let from = &(chrono::Utc::now() - chrono::Duration::days(1)).to_rfc3339();
+    let (history_server, history_token) = (&server, &token);
+    let history_counts = || async move {
+        let mut counts = Vec::new();
+        for resolution in ["RAW", "DAILY"] {
+            let history: MetricHistoryData = history_server
+                .graphql(
+                    GET_METRIC_HISTORY,
+                    Some(serde_json::json!({
+                        "slug": "shard-test",
+                        "from": from,
+                        "resolution": resolution
+                    })),
+                    Some(history_token),
+                )
+                .await
+                .unwrap();
+            let points = history.project.unwrap().metric_history.points;
+            counts.push(points.iter().map(|p| p.count).sum::<i64>());
+        }
+        counts
+    };
+    assert_eq!(history_counts().await, [2, 2]);
+
+    let finalize = || {
+        server.graphql::<FinalizeReportData>(
+            &finalize_report_mutation(),
+            Some(serde_json::json!({ "reportId": report.id })),
+            Some(&token),
+        )
+    };
+    let finalized = finalize().await.unwrap().finalize_report;
+    assert_eq!(finalized.git_hash.as_deref(), Some("abc123"));
+    assert_eq!(finalized.alerts.len(), 1);
+    assert_eq!(finalized.alerts[0].current_value, 150.0);
+    assert_eq!(finalized.alerts[0].baseline_value, 100.0);
+    assert_eq!(history_counts().await, [3, 3]);
+
+    // Finalizing again does not evaluate again
+    assert_eq!(finalize().await.unwrap().finalize_report.alerts.len(), 1);
+    assert!(append("fibonacci/30", 1.0).await.errors.is_some());
+
+    // Another user cannot touch the report
+    let other = server.create_test_token("user-2");
+    let result = server
+        .graphql::<FinalizeReportData>(
+            &finalize_report_mutation(),
+            Some(serde_json::json!({ "reportId": report.id })),
+            Some(&other),
+        )
+        .await;
+    assert!(result.errors.is_some());
+}
+
+#[tokio::test]
+async fn test_report_breaching_threshold_raises_alert() {
+    let server = test_server!();
+    let token = server.create_test_token("user-1");
+
+    let _: CreateProjectData = server
+        .graphql(
+            CREATE_PROJECT,
+            Some(serde_json::json!({
+                "input": {
+                    "slug": "alert-test",
+                    "name": "Alert Test"
+                }
+            })),
+            Some(&token),
+        )
+        .await
+        .unwrap();
+
+    let project: ProjectWithMeasuresData = server
+        .graphql(
+            GET_PROJECT_WITH_MEASURES,
+            Some(serde_json::json!({ "slug": "alert-test" })),
+            Some(&token),
+        )
+        .await
+        .unwrap();
+    let measure_id = &project.project.unwrap().measures[0].id;
+
+    let _: CreateThresholdData = server
+        .graphql(
+            CREATE_THRESHOLD,
+            Some(serde_json::json!({
+                "input": {
+                    "projectSlug": "alert-test",
+                    "measureId": measure_id,
+                    "upperBoundary": 10.0,
+                    "minSampleSize": 2
+                }
+            })),
+            Some(&token),
+        )
+        .await
+        .unwrap();
+
+    let mut reports = Vec::new();
+    for value in [100.0, 100.0, 105.0, 150.0] {
+        let result: CreateReportData = server
+            .graphql(
+                CREATE_REPORT,
+                Some(serde_json::json!({
+                    "input": {
+                        "projectSlug": "alert-test",
+                        "branch": "main",
+                        "testbed": "ci-linux",
+                        "metrics": [{
+                            "benchmark": "fibonacci/10",
+                            "measure": "latency",
+                            "value": value
+                        }]
+                    }
+                })),
+                Some(&token),
+            )
+            .await
+            .unwrap();
+        reports.push(result.create_report);
+    }
+
+    // No baseline yet, then too few samples, then within the boundary
+    assert_eq!(reports[0].metrics[0].baseline_value, None);
+    assert!(reports[..3].iter().all(|r| r.alerts.is_empty()));
+
+    let breach = &reports[3];
+    assert_eq!(breach.metrics[0].baseline_value, Some(101.66666666666667));
+    assert_eq!(breach.alerts.len(), 1);
+    assert_eq!(breach.alerts[0].current_value, 150.0);
+    assert_eq!(breach.alerts[0].baseline_value, 101.66666666666667);
+    assert!((breach.alerts[0].percent_change - 47.54).abs() < 0.01);
+}
+
+#[tokio::test]
+async fn test_create_report_requires_metrics() {
+    let server = test_server!();
+    let token = server.create_test_token("user-1");
+
+    let _: CreateProjectData = server
+        .graphql(
+            CREATE_PROJECT,
+            Some(serde_json::json!({
+                "input": {
+                    "slug": "empty-report-test",
+                    "name": "Empty Report Test"
+                }
+            })),
+            Some(&token),
+        )
+        .await
+        .unwrap();
+
+    let result = server
+        .graphql::<CreateReportData>(
+            CREATE_REPORT,
+            Some(serde_json::json!({
+                "input": {
+                    "projectSlug": "empty-report-test",
+                    "branch": "main",
+                    "testbed": "ci-linux",
+                    "metrics": []
+                }
+            })),
+            Some(&token),
+        )
+        .await;
+
+    assert!(result.errors.is_some());
+}
+
+#[tokio::test]
+async fn test_export_and_import_project() {
+    let server = test_server!();
+    let token = server.create_test_token("user-1");
+
+    let _: CreateProjectData = server
+        .graphql(
+            CREATE_PROJECT,
+            Some(serde_json::json!({
+                "input": {
+                    "slug": "export-source",
+                    "name": "Export Source",
+                    "description": "Project to copy"
+                }
+            })),
+            Some(&token),
+        )
+        .await
+        .unwrap();
+
+    let _: CreateReportData = server
+        .graphql(
+            CREATE_REPORT,
+            Some(serde_json::json!({
+                "input": {
+                    "projectSlug": "export-source",
+                    "branch": "main",
+                    "testbed": "ci-linux",
+                    "metrics": [
+                        { "benchmark": "fibonacci/10", "measure": "latency", "value": 120.0 },
+                        { "benchmark": "fibonacci/20", "measure": "latency", "value": 240.0 }
+                    ]
+                }
+            })),
+            Some(&token),
+        )
+        .await
+        .unwrap();
+
+    let exported: ExportProjectData = server
+        .graphql(
+            EXPORT_PROJECT,
+            Some(serde_json::json!({ "slug": "export-source" })),
+            Some(&token),
+        )
+        .await
+        .unwrap();
+
+    let archive = exported.export_project;
+    assert_eq!(archive["version"], 2);
+    assert_eq!(archive["reports"].as_array().unwrap().len(), 1);
+    assert_eq!(archive["metrics"].as_array().unwrap().len(), 2);
+    assert_eq!(archive["benchmarks"].as_array().unwrap().len(), 2);
+
+    let imported: ImportProjectData = server
+        .graphql(
+            IMPORT_PROJECT,
+            Some(serde_json::json!({
+                "slug": "export-copy",
+                "archive": archive
+            })),
+            Some(&token),
+        )
+        .await
+        .unwrap();
+
+    let copy = imported.import_project;
+    assert_eq!(copy.slug, "export-copy");
+    assert_eq!(copy.name, "Export Source");
+    assert_eq!(copy.description.as_deref(), Some("Project to copy"));
+
+    let reexported: ExportProjectData = server
+        .graphql(
+            EXPORT_PROJECT,
+            Some(serde_json::json!({ "slug": "export-copy" })),
+            Some(&token),
+        )
+        .await
+        .unwrap();
+
+    let copy_archive = reexported.export_project;
+    assert_eq!(copy_archive["metrics"].as_array().unwrap().len(), 2);
+    assert_ne!(
+        copy_archive["reports"][0]["id"],
+        archive["reports"][0]["id"]
+    );
+}
+
+#[tokio::test]
+async fn test_import_version_1_archive() {
+    let server = test_server!();
+    let token = server.create_test_token("user-1");
+
+    let archive: serde_json::Value =
+        serde_json::from_str(include_str!("fixtures/archive_v1.json")).unwrap();
+    let imported: ImportProjectData = server
+        .graphql(
+            IMPORT_PROJECT,
+            Some(serde_json::json!({
+                "slug": "legacy-copy",
+                "archive": archive
+            })),
+            Some(&token),
+        )
+        .await
+        .unwrap();
+    assert_eq!(imported.import_project.name, "Legacy");
+
+    let exported: ExportProjectData = server
+        .graphql(
+            EXPORT_PROJECT,
+            Some(serde_json::json!({ "slug": "legacy-copy" })),
+            Some(&token),
+        )
+        .await
+        .unwrap();
+
+    let copy = exported.export_project;
+    assert_eq!(copy["version"], 2);
+    assert_eq!(copy["reports"][0]["finalized"], true);
+    assert_eq!(copy["reports"][0]["git_hash"], "abc123");
+    assert_eq!(copy["testbeds"][0]["fingerprint"], serde_json::Value::Null);
+    assert_eq!(copy["metrics"].as_array().unwrap().len(), 1);
+    assert_eq!(copy["alerts"].as_array().unwrap().len(), 1);
+    assert_eq!(copy["rollups"].as_array().unwrap().len(), 1);
+}
+
+#[tokio::test]
+async fn test_import_rejects_existing_slug() {
+    let server = test_server!();
+    let token = server.create_test_token("user-1");
+
+    let _: CreateProjectData = server
+        .graphql(
+            CREATE_PROJECT,
+            Some(serde_json::json!({
+                "input": {
+                    "slug": "import-taken",
+                    "name": "Import Taken"
+                }
+            })),
+            Some(&token),
+        )
+        .await
+        .unwrap();
+
+    let exported: ExportProjectData = server
+        .graphql(
+            EXPORT_PROJECT,
+            Some(serde_json::json!({ "slug": "import-taken" })),
+            Some(&token),
+        )
+        .await
+        .unwrap();
+
+    let result = server
+        .graphql::<ImportProjectData>(
+            IMPORT_PROJECT,
+            Some(serde_json::json!({
+                "slug": "import-taken",
+                "archive": exported.export_project
+            })),
+            Some(&token),
+        )
+        .await;
+
+    assert!(result.errors.is_some());
+}
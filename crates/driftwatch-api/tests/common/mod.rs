@@ -11,7 +11,8 @@ use driftwatch_api::{
     cache::AppCache,
     graphql::build_schema,
     loaders::{
-        BenchmarkLoader, BranchLoader, MeasureLoader, MetricLoader, TestbedLoader, ThresholdLoader,
+        BaselineLoader, BenchmarkLoader, BranchLoader, MeasureLoader, MetricLoader, ReportLoader,
+        TestbedLoader, ThresholdLoader,
     },
     migrations,
 };
@@ -291,6 +292,18 @@ async fn graphql_handler(
         },
         tokio::spawn,
     ));
+    request = request.data(DataLoader::new(
+        ReportLoader {
+            db: state.db.clone(),
+        },
+        tokio::spawn,
+    ));
+    request = request.data(DataLoader::new(
+        BaselineLoader {
+            db: state.db.clone(),
+        },
+        tokio::spawn,
+    ));
 
     if let Some(user) = user {
         request = request.data(user);
@@ -0,0 +1,168 @@
+use anyhow::{Context, Result};
+use serde::Deserialize;
+use std::collections::BTreeMap;
+
+use super::{Adapter, BenchmarkResult, MeasureResult};
+
+/// One measure of one benchmark from a Bencher Metric Format report.
+#[derive(Debug, Clone)]
+pub struct BmfResult {
+    pub name: String,
+    pub measure: String,
+    pub value: f64,
+    pub lower: Option<f64>,
+    pub upper: Option<f64>,
+}
+
+#[derive(Deserialize)]
+struct BmfMetric {
+    value: f64,
+    lower_value: Option<f64>,
+    upper_value: Option<f64>,
+}
+
+// BMF is `{ "<benchmark>": { "<measure>": { "value": .., "lower_value": .., "upper_value": .. } } }`
+type BmfReport = BTreeMap<String, BTreeMap<String, BmfMetric>>;
+
+/// Returns true if `output` looks like a BMF JSON document rather than
+/// human-readable harness output.
+pub fn is_bmf(output: &str) -> bool {
+    output.trim_start().starts_with('{')
+}
+
+pub fn parse_bmf_output(output: &str) -> Result<Vec<BmfResult>> {
+    let report: BmfReport =
+        serde_json::from_str(output.trim()).context("Invalid Bencher Metric Format JSON")?;
+
+    Ok(report
+        .into_iter()
+        .flat_map(|(name, measures)| {
+            measures
+                .into_iter()
+                .map(move |(measure, metric)| BmfResult {
+                    name: name.clone(),
+                    measure,
+                    value: metric.value,
+                    lower: metric.lower_value,
+                    upper: metric.upper_value,
+                })
+        })
+        .collect())
+}
+
+pub struct Bmf;
+
+impl Adapter for Bmf {
+    fn name(&self) -> &'static str {
+        "bmf"
+    }
+
+    fn detect(&self, output: &str) -> bool {
+        is_bmf(output)
+    }
+
+    fn parse(&self, output: &str) -> Result<Vec<BenchmarkResult>> {
+        let mut results: Vec<BenchmarkResult> = Vec::new();
+        for r in parse_bmf_output(output)? {
+            let measure = MeasureResult {
+                measure: r.measure,
+                units: None,
+                value: r.value,
+                lower: r.lower,
+                upper: r.upper,
+            };
+            // parse_bmf_output yields each benchmark's measures consecutively
+            match results.last_mut() {
+                Some(last) if last.name == r.name => last.measures.push(measure),
+                _ => results.push(BenchmarkResult {
+                    name: r.name,
+                    measures: vec![measure],
+                }),
+            }
+        }
+        Ok(results)
+    }
+
+    fn stdout_only(&self) -> bool {
+        true
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_bmf_output() {
+        let output = r#"
+{
+    "fibonacci/10": {
+        "latency": { "value": 1245.6, "lower_value": 1234.5, "upper_value": 1256.7 }
+    },
+    "fibonacci/20": {
+        "latency": { "value": 124.56 }
+    }
+}
+        "#;
+
+        let results = parse_bmf_output(output).unwrap();
+        assert_eq!(results.len(), 2);
+
+        assert_eq!(results[0].name, "fibonacci/10");
+        assert_eq!(results[0].measure, "latency");
+        assert_eq!(results[0].value, 1245.6);
+        assert_eq!(results[0].lower, Some(1234.5));
+        assert_eq!(results[0].upper, Some(1256.7));
+
+        assert_eq!(results[1].name, "fibonacci/20");
+        assert_eq!(results[1].lower, None);
+        assert_eq!(results[1].upper, None);
+    }
+
+    #[test]
+    fn test_parse_bmf_output_multiple_measures() {
+        let output = r#"{
+            "parse": {
+                "latency": { "value": 830.0 },
+                "throughput": { "value": 1.2e6, "lower_value": 1.1e6, "upper_value": 1.3e6 }
+            }
+        }"#;
+
+        let results = parse_bmf_output(output).unwrap();
+        assert_eq!(results.len(), 2);
+        assert!(results.iter().all(|r| r.name == "parse"));
+        assert_eq!(results[0].measure, "latency");
+        assert_eq!(results[1].measure, "throughput");
+        assert_eq!(results[1].upper, Some(1.3e6));
+    }
+
+    #[test]
+    fn test_parse_bmf_output_invalid() {
+        assert!(parse_bmf_output("fibonacci/10 time: [1 ns 2 ns 3 ns]").is_err());
+        assert!(parse_bmf_output(r#"{"bench": {"latency": {"lower_value": 1.0}}}"#).is_err());
+    }
+
+    #[test]
+    fn test_bmf_adapter_groups_measures() {
+        let output = r#"{
+            "parse": {
+                "latency": { "value": 830.0 },
+                "throughput": { "value": 1.2e6 }
+            },
+            "render": { "latency": { "value": 12.0 } }
+        }"#;
+
+        let results = Bmf.parse(output).unwrap();
+        assert_eq!(results.len(), 2);
+        assert_eq!(results[0].name, "parse");
+        assert_eq!(results[0].measures.len(), 2);
+        assert_eq!(results[1].name, "render");
+        assert_eq!(results[1].measures[0].measure, "latency");
+    }
+
+    #[test]
+    fn test_is_bmf() {
+        assert!(is_bmf("  \n{\"bench\": {}}"));
+        assert!(!is_bmf("Benchmarking fibonacci/10"));
+    }
+}
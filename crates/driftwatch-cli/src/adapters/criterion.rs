@@ -1,6 +1,9 @@
+use anyhow::Result;
 use regex::Regex;
 use std::sync::LazyLock;
 
+use super::{nanoseconds_per, Adapter, BenchmarkResult, MeasureResult};
+
 #[derive(Debug, Clone)]
 pub struct CriterionResult {
     pub name: String,
@@ -37,16 +40,37 @@ pub fn parse_criterion_output(output: &str) -> Vec<CriterionResult> {
         .collect()
 }
 
+pub struct Criterion;
+
+impl Adapter for Criterion {
+    fn name(&self) -> &'static str {
+        "criterion"
+    }
+
+    fn detect(&self, output: &str) -> bool {
+        CRITERION_REGEX.is_match(output)
+    }
+
+    fn parse(&self, output: &str) -> Result<Vec<BenchmarkResult>> {
+        Ok(parse_criterion_output(output)
+            .into_iter()
+            .map(|r| BenchmarkResult {
+                name: r.name,
+                measures: vec![MeasureResult {
+                    measure: "latency".to_string(),
+                    units: Some("ns".to_string()),
+                    value: r.value,
+                    lower: r.lower,
+                    upper: r.upper,
+                }],
+            })
+            .collect())
+    }
+}
+
 fn parse_time(value: &str, unit: &str) -> Option<f64> {
     let v: f64 = value.parse().ok()?;
-    let multiplier = match unit {
-        "ns" => 1.0,
-        "µs" | "us" => 1_000.0,
-        "ms" => 1_000_000.0,
-        "s" => 1_000_000_000.0,
-        _ => return None,
-    };
-    Some(v * multiplier)
+    Some(v * nanoseconds_per(unit)?)
 }
 
 #[cfg(test)]
@@ -0,0 +1,292 @@
+use anyhow::{Context, Result};
+use serde::Deserialize;
+use std::path::{Path, PathBuf};
+use std::time::SystemTime;
+
+use super::{Adapter, AdapterOptions, BenchmarkResult, MeasureResult};
+
+pub const DEFAULT_CRITERION_DIR: &str = "target/criterion";
+
+#[derive(Deserialize)]
+struct BenchmarkId {
+    full_id: String,
+    throughput: Option<Throughput>,
+}
+
+#[derive(Deserialize)]
+enum Throughput {
+    Bytes(u64),
+    BytesDecimal(u64),
+    Elements(u64),
+    ElementsAndBytes { elements: u64 },
+}
+
+#[derive(Deserialize)]
+struct Estimates {
+    mean: Estimate,
+    median: Estimate,
+    slope: Option<Estimate>,
+}
+
+#[derive(Deserialize)]
+struct Estimate {
+    confidence_interval: ConfidenceInterval,
+    point_estimate: f64,
+}
+
+#[derive(Deserialize)]
+struct ConfidenceInterval {
+    lower_bound: f64,
+    upper_bound: f64,
+}
+
+/// Reads the `new/estimates.json` files Criterion leaves under its output
+/// directory instead of scraping the human-readable output.
+pub struct CriterionJson {
+    dir: PathBuf,
+    throughput: bool,
+    since: Option<SystemTime>,
+}
+
+impl CriterionJson {
+    pub fn new(options: &AdapterOptions) -> Self {
+        Self {
+            dir: options
+                .criterion_dir
+                .clone()
+                .unwrap_or_else(|| PathBuf::from(DEFAULT_CRITERION_DIR)),
+            throughput: options.throughput,
+            since: options.since,
+        }
+    }
+
+    fn read_benchmark(&self, new_dir: &Path) -> Result<Option<BenchmarkResult>> {
+        let estimates_path = new_dir.join("estimates.json");
+        if let Some(since) = self.since {
+            let modified = std::fs::metadata(&estimates_path)?.modified()?;
+            if modified < since {
+                return Ok(None);
+            }
+        }
+
+        let id: BenchmarkId = read_json(&new_dir.join("benchmark.json"))?;
+        let estimates: Estimates = read_json(&estimates_path)?;
+
+        let mut measures = vec![
+            time_measure("latency", &estimates.mean),
+            time_measure("median", &estimates.median),
+        ];
+        if let Some(slope) = &estimates.slope {
+            measures.push(time_measure("slope", slope));
+        }
+
+        if self.throughput {
+            if let Some(throughput) = &id.throughput {
+                let (count, units) = match *throughput {
+                    Throughput::Bytes(n) | Throughput::BytesDecimal(n) => (n, "bytes/s"),
+                    Throughput::Elements(n) | Throughput::ElementsAndBytes { elements: n } => {
+                        (n, "elements/s")
+                    }
+                };
+                // Per-second rate from a per-iteration time in ns; the slowest
+                // time bound gives the lowest rate.
+                let rate = |ns: f64| count as f64 * 1e9 / ns;
+                let mean = &estimates.mean;
+                measures.push(MeasureResult {
+                    measure: "throughput".to_string(),
+                    units: Some(units.to_string()),
+                    value: rate(mean.point_estimate),
+                    lower: Some(rate(mean.confidence_interval.upper_bound)),
+                    upper: Some(rate(mean.confidence_interval.lower_bound)),
+                });
+            }
+        }
+
+        Ok(Some(BenchmarkResult {
+            name: id.full_id,
+            measures,
+        }))
+    }
+}
+
+impl Adapter for CriterionJson {
+    fn name(&self) -> &'static str {
+        "criterion-json"
+    }
+
+    // The estimates live on disk, so there is nothing in the output to sniff.
+    fn detect(&self, _output: &str) -> bool {
+        false
+    }
+
+    fn parse(&self, _output: &str) -> Result<Vec<BenchmarkResult>> {
+        let mut new_dirs = Vec::new();
+        find_new_dirs(&self.dir, &mut new_dirs)
+            .with_context(|| format!("Failed to read {}", self.dir.display()))?;
+        new_dirs.sort();
+
+        let mut results = Vec::new();
+        for new_dir in new_dirs {
+            if let Some(result) = self.read_benchmark(&new_dir)? {
+                results.push(result);
+            }
+        }
+        Ok(results)
+    }
+}
+
+fn time_measure(measure: &str, estimate: &Estimate) -> MeasureResult {
+    MeasureResult {
+        measure: measure.to_string(),
+        units: Some("ns".to_string()),
+        value: estimate.point_estimate,
+        lower: Some(estimate.confidence_interval.lower_bound),
+        upper: Some(estimate.confidence_interval.upper_bound),
+    }
+}
+
+// Collects every `<benchmark>/new` directory that holds an estimate. The
+// `base` and named baseline siblings are previous runs and are skipped.
+fn find_new_dirs(dir: &Path, found: &mut Vec<PathBuf>) -> Result<()> {
+    for entry in std::fs::read_dir(dir)? {
+        let path = entry?.path();
+        if !path.is_dir() {
+            continue;
+        }
+        if path.file_name().is_some_and(|n| n == "new") {
+            if path.join("estimates.json").is_file() {
+                found.push(path);
+            }
+        } else if path.file_name().is_none_or(|n| n != "report") {
+            find_new_dirs(&path, found)?;
+        }
+    }
+    Ok(())
+}
+
+fn read_json<T: serde::de::DeserializeOwned>(path: &Path) -> Result<T> {
+    let content = std::fs::read_to_string(path)
+        .with_context(|| format!("Failed to read {}", path.display()))?;
+    serde_json::from_str(&content).with_context(|| format!("Invalid JSON in {}", path.display()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn estimate(point: f64) -> String {
+        format!(
+            r#"{{"confidence_interval":{{"confidence_level":0.95,"lower_bound":{},"upper_bound":{}}},"point_estimate":{},"standard_error":1.0}}"#,
+            point - 10.0,
+            point + 10.0,
+            point
+        )
+    }
+
+    fn write_benchmark(root: &Path, dir: &str, full_id: &str, throughput: &str, slope: bool) {
+        let new_dir = root.join(dir).join("new");
+        std::fs::create_dir_all(&new_dir).unwrap();
+        std::fs::write(
+            new_dir.join("benchmark.json"),
+            format!(
+                r#"{{"group_id":"g","function_id":null,"value_str":null,"throughput":{},"full_id":"{}","directory_name":"{}","title":"{}"}}"#,
+                throughput, full_id, dir, full_id
+            ),
+        )
+        .unwrap();
+        let slope = if slope {
+            estimate(990.0)
+        } else {
+            "null".into()
+        };
+        std::fs::write(
+            new_dir.join("estimates.json"),
+            format!(
+                r#"{{"mean":{},"median":{},"median_abs_dev":{},"slope":{},"std_dev":{}}}"#,
+                estimate(1000.0),
+                estimate(980.0),
+                estimate(5.0),
+                slope,
+                estimate(20.0)
+            ),
+        )
+        .unwrap();
+    }
+
+    #[test]
+    fn test_parse_criterion_directory() {
+        let dir = tempfile::tempdir().unwrap();
+        write_benchmark(
+            dir.path(),
+            "parse/json/1024",
+            "parse/json/1024",
+            "null",
+            true,
+        );
+        write_benchmark(dir.path(), "hash", "hash", "null", false);
+        // Report output and older baselines are not results
+        std::fs::create_dir_all(dir.path().join("report/new")).unwrap();
+        std::fs::create_dir_all(dir.path().join("hash/base")).unwrap();
+
+        let adapter = CriterionJson::new(&AdapterOptions {
+            criterion_dir: Some(dir.path().to_path_buf()),
+            ..Default::default()
+        });
+        let results = adapter.parse("").unwrap();
+        assert_eq!(results.len(), 2);
+
+        assert_eq!(results[0].name, "hash");
+        assert_eq!(results[0].measures.len(), 2);
+
+        let parse = &results[1];
+        assert_eq!(parse.name, "parse/json/1024");
+        let names: Vec<_> = parse.measures.iter().map(|m| m.measure.as_str()).collect();
+        assert_eq!(names, ["latency", "median", "slope"]);
+        assert_eq!(parse.measures[0].value, 1000.0);
+        assert_eq!(parse.measures[0].lower, Some(990.0));
+        assert_eq!(parse.measures[0].upper, Some(1010.0));
+        assert_eq!(parse.measures[0].units.as_deref(), Some("ns"));
+    }
+
+    #[test]
+    fn test_parse_criterion_throughput() {
+        let dir = tempfile::tempdir().unwrap();
+        write_benchmark(dir.path(), "copy", "copy", r#"{"Bytes":1000}"#, false);
+
+        let mut options = AdapterOptions {
+            criterion_dir: Some(dir.path().to_path_buf()),
+            ..Default::default()
+        };
+        let results = CriterionJson::new(&options).parse("").unwrap();
+        assert!(results[0]
+            .measures
+            .iter()
+            .all(|m| m.measure != "throughput"));
+
+        options.throughput = true;
+        let results = CriterionJson::new(&options).parse("").unwrap();
+        let throughput = results[0]
+            .measures
+            .iter()
+            .find(|m| m.measure == "throughput")
+            .unwrap();
+        // 1000 bytes per 1000 ns
+        assert_eq!(throughput.value, 1e9);
+        assert_eq!(throughput.units.as_deref(), Some("bytes/s"));
+        assert!(throughput.lower.unwrap() < throughput.value);
+        assert!(throughput.upper.unwrap() > throughput.value);
+    }
+
+    #[test]
+    fn test_parse_criterion_skips_stale_results() {
+        let dir = tempfile::tempdir().unwrap();
+        write_benchmark(dir.path(), "hash", "hash", "null", false);
+
+        let adapter = CriterionJson::new(&AdapterOptions {
+            criterion_dir: Some(dir.path().to_path_buf()),
+            since: Some(SystemTime::now() + std::time::Duration::from_secs(60)),
+            ..Default::default()
+        });
+        assert!(adapter.parse("").unwrap().is_empty());
+    }
+}
@@ -0,0 +1,359 @@
+use anyhow::{bail, Context, Result};
+use regex::Regex;
+use serde::Deserialize;
+use serde_json::Value;
+use std::collections::HashMap;
+
+use super::{Adapter, BenchmarkResult, MeasureResult};
+
+/// The `[custom]` section of `driftwatch.toml`.
+///
+/// Exactly one of `regex` and `json` must be set. Values are multiplied by
+/// `unit_scale[<unit>]` when a unit is captured and listed there, by `scale`
+/// otherwise.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct CustomAdapterConfig {
+    pub measure: String,
+    pub units: Option<String>,
+    #[serde(default = "default_scale")]
+    pub scale: f64,
+    #[serde(default)]
+    pub unit_scale: HashMap<String, f64>,
+    /// Matched against every line; named groups `name` and `value` are
+    /// required, `unit`, `lower` and `upper` are optional.
+    pub regex: Option<String>,
+    pub json: Option<JsonPaths>,
+}
+
+/// JSONPath expressions; all but `results` are relative to each result.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct JsonPaths {
+    #[serde(default = "default_results_path")]
+    pub results: String,
+    pub name: String,
+    pub value: String,
+    pub unit: Option<String>,
+    pub lower: Option<String>,
+    pub upper: Option<String>,
+}
+
+fn default_scale() -> f64 {
+    1.0
+}
+
+fn default_results_path() -> String {
+    "$[*]".to_string()
+}
+
+enum Pattern {
+    Regex(Regex),
+    Json(JsonPaths),
+}
+
+/// A user-defined adapter, selected with `--adapter custom`.
+pub struct Custom {
+    config: CustomAdapterConfig,
+    pattern: Pattern,
+}
+
+impl Custom {
+    pub fn new(config: CustomAdapterConfig) -> Result<Self> {
+        let pattern = match (&config.regex, &config.json) {
+            (Some(regex), None) => {
+                let regex = Regex::new(regex).context("Invalid regex in [custom]")?;
+                for group in ["name", "value"] {
+                    if !regex.capture_names().any(|n| n == Some(group)) {
+                        bail!("The [custom] regex needs a named group `{}`", group);
+                    }
+                }
+                Pattern::Regex(regex)
+            }
+            (None, Some(json)) => {
+                for path in [&json.results, &json.name, &json.value] {
+                    parse_path(path)?;
+                }
+                for path in [&json.unit, &json.lower, &json.upper].into_iter().flatten() {
+                    parse_path(path)?;
+                }
+                Pattern::Json(json.clone())
+            }
+            _ => bail!("Set exactly one of `regex` or `json` in [custom]"),
+        };
+
+        Ok(Self { config, pattern })
+    }
+
+    fn scale(&self, unit: Option<&str>) -> f64 {
+        unit.and_then(|u| self.config.unit_scale.get(u))
+            .copied()
+            .unwrap_or(self.config.scale)
+    }
+
+    fn result(
+        &self,
+        name: String,
+        unit: Option<&str>,
+        value: f64,
+        lower: Option<f64>,
+        upper: Option<f64>,
+    ) -> BenchmarkResult {
+        let scale = self.scale(unit);
+        BenchmarkResult {
+            name,
+            measures: vec![MeasureResult {
+                measure: self.config.measure.clone(),
+                units: self.config.units.clone(),
+                value: value * scale,
+                lower: lower.map(|v| v * scale),
+                upper: upper.map(|v| v * scale),
+            }],
+        }
+    }
+
+    fn parse_regex(&self, regex: &Regex, output: &str) -> Vec<BenchmarkResult> {
+        output
+            .lines()
+            .filter_map(|line| {
+                let cap = regex.captures(line)?;
+                let number = |group: &str| cap.name(group)?.as_str().trim().parse::<f64>().ok();
+
+                Some(self.result(
+                    cap.name("name")?.as_str().trim().to_string(),
+                    cap.name("unit").map(|u| u.as_str().trim()),
+                    number("value")?,
+                    number("lower"),
+                    number("upper"),
+                ))
+            })
+            .collect()
+    }
+
+    fn parse_json(&self, paths: &JsonPaths, output: &str) -> Result<Vec<BenchmarkResult>> {
+        let root: Value = serde_json::from_str(output.trim()).context("Invalid JSON output")?;
+        let first = |item: &Value, path: Option<&str>| -> Result<Option<Value>> {
+            match path {
+                Some(path) => Ok(select(item, path)?.into_iter().next().cloned()),
+                None => Ok(None),
+            }
+        };
+
+        let mut results = Vec::new();
+        for item in select(&root, &paths.results)? {
+            let name = match first(item, Some(&paths.name))? {
+                Some(Value::String(name)) => name,
+                Some(name) => name.to_string(),
+                None => continue,
+            };
+            let Some(value) = first(item, Some(&paths.value))?
+                .as_ref()
+                .and_then(as_number)
+            else {
+                continue;
+            };
+            let unit = first(item, paths.unit.as_deref())?;
+            let lower = first(item, paths.lower.as_deref())?;
+            let upper = first(item, paths.upper.as_deref())?;
+
+            results.push(self.result(
+                name,
+                unit.as_ref().and_then(Value::as_str),
+                value,
+                lower.as_ref().and_then(as_number),
+                upper.as_ref().and_then(as_number),
+            ));
+        }
+        Ok(results)
+    }
+}
+
+impl Adapter for Custom {
+    fn name(&self) -> &'static str {
+        "custom"
+    }
+
+    // Only used when asked for; a user pattern could match anything.
+    fn detect(&self, _output: &str) -> bool {
+        false
+    }
+
+    fn parse(&self, output: &str) -> Result<Vec<BenchmarkResult>> {
+        match &self.pattern {
+            Pattern::Regex(regex) => Ok(self.parse_regex(regex, output)),
+            Pattern::Json(paths) => self.parse_json(paths, output),
+        }
+    }
+
+    fn stdout_only(&self) -> bool {
+        matches!(self.pattern, Pattern::Json(_))
+    }
+}
+
+fn as_number(value: &Value) -> Option<f64> {
+    match value {
+        Value::Number(n) => n.as_f64(),
+        Value::String(s) => s.trim().parse().ok(),
+        _ => None,
+    }
+}
+
+#[derive(Debug, PartialEq)]
+enum Step {
+    Key(String),
+    Index(usize),
+    Wildcard,
+}
+
+/// Parses the JSONPath subset we support: `$`, `.key`, `['key']`, `[0]`,
+/// `[*]` and `.*`.
+fn parse_path(path: &str) -> Result<Vec<Step>> {
+    let invalid = || format!("Unsupported JSONPath '{}'", path);
+    let mut rest = path.trim().strip_prefix('$').with_context(invalid)?;
+    let mut steps = Vec::new();
+
+    while !rest.is_empty() {
+        if let Some(after) = rest.strip_prefix('.') {
+            let end = after.find(['.', '[']).unwrap_or(after.len());
+            let key = &after[..end];
+            match key {
+                "" => bail!(invalid()),
+                "*" => steps.push(Step::Wildcard),
+                _ => steps.push(Step::Key(key.to_string())),
+            }
+            rest = &after[end..];
+        } else if let Some(after) = rest.strip_prefix('[') {
+            let end = after.find(']').with_context(invalid)?;
+            let inner = after[..end].trim();
+            if inner == "*" {
+                steps.push(Step::Wildcard);
+            } else if let Some(key) = inner
+                .strip_prefix('\'')
+                .and_then(|k| k.strip_suffix('\''))
+                .or_else(|| inner.strip_prefix('"').and_then(|k| k.strip_suffix('"')))
+            {
+                steps.push(Step::Key(key.to_string()));
+            } else {
+                steps.push(Step::Index(inner.parse().with_context(invalid)?));
+            }
+            rest = &after[end + 1..];
+        } else {
+            bail!(invalid());
+        }
+    }
+
+    Ok(steps)
+}
+
+fn select<'a>(root: &'a Value, path: &str) -> Result<Vec<&'a Value>> {
+    let mut current = vec![root];
+    for step in parse_path(path)? {
+        current = current
+            .into_iter()
+            .flat_map(|value| -> Vec<&Value> {
+                match (&step, value) {
+                    (Step::Key(key), Value::Object(map)) => map.get(key).into_iter().collect(),
+                    (Step::Index(i), Value::Array(items)) => items.get(*i).into_iter().collect(),
+                    (Step::Wildcard, Value::Array(items)) => items.iter().collect(),
+                    (Step::Wildcard, Value::Object(map)) => map.values().collect(),
+                    _ => Vec::new(),
+                }
+            })
+            .collect();
+    }
+    Ok(current)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn config(toml: &str) -> CustomAdapterConfig {
+        toml::from_str(toml).unwrap()
+    }
+
+    #[test]
+    fn test_custom_regex_adapter() {
+        let adapter = Custom::new(config(
+            r#"
+measure = "latency"
+units = "ns"
+regex = '^bench (?P<name>\S+): (?P<value>[0-9.]+) (?P<unit>\w+) \((?P<lower>[0-9.]+)-(?P<upper>[0-9.]+)\)$'
+unit_scale = { us = 1000.0, ms = 1000000.0 }
+"#,
+        ))
+        .unwrap();
+
+        let output = "starting\nbench parse: 1.5 us (1.2-1.8)\nbench load: 2 ms (1-3)\nbench raw: 7 ns (6-8)\n";
+        let results = adapter.parse(output).unwrap();
+        assert_eq!(results.len(), 3);
+
+        assert_eq!(results[0].name, "parse");
+        let parse = &results[0].measures[0];
+        assert_eq!(parse.measure, "latency");
+        assert_eq!(parse.units.as_deref(), Some("ns"));
+        assert_eq!(parse.value, 1500.0);
+        assert_eq!(parse.lower, Some(1200.0));
+        assert_eq!(parse.upper, Some(1800.0));
+
+        assert_eq!(results[1].measures[0].value, 2_000_000.0);
+        // Units missing from unit_scale fall back to `scale`
+        assert_eq!(results[2].measures[0].value, 7.0);
+    }
+
+    #[test]
+    fn test_custom_json_adapter() {
+        let adapter = Custom::new(config(
+            r#"
+measure = "throughput"
+units = "req/s"
+scale = 1000.0
+
+[json]
+results = "$.runs[*]"
+name = "$.label"
+value = "$['stats'].rps"
+lower = "$.stats.rps_min"
+"#,
+        ))
+        .unwrap();
+
+        let output = r#"{"runs": [
+            {"label": "get /", "stats": {"rps": 1.5, "rps_min": "1.2"}},
+            {"label": "post /", "stats": {"rps": 0.5}},
+            {"label": "broken"}
+        ]}"#;
+        let results = adapter.parse(output).unwrap();
+        assert_eq!(results.len(), 2);
+        assert_eq!(results[0].name, "get /");
+        assert_eq!(results[0].measures[0].value, 1500.0);
+        assert_eq!(results[0].measures[0].lower, Some(1200.0));
+        assert_eq!(results[1].measures[0].lower, None);
+    }
+
+    #[test]
+    fn test_custom_adapter_validation() {
+        assert!(Custom::new(config("measure = \"latency\"")).is_err());
+        assert!(Custom::new(config("measure = \"latency\"\nregex = '(?P<name>\\S+)'")).is_err());
+        assert!(Custom::new(config(
+            "measure = \"latency\"\n[json]\nname = \"name\"\nvalue = \"$.value\""
+        ))
+        .is_err());
+    }
+
+    #[test]
+    fn test_parse_path() {
+        assert_eq!(
+            parse_path("$.a['b c'][2][*].*").unwrap(),
+            [
+                Step::Key("a".into()),
+                Step::Key("b c".into()),
+                Step::Index(2),
+                Step::Wildcard,
+                Step::Wildcard
+            ]
+        );
+        assert!(parse_path("$..a").is_err());
+        assert!(parse_path("$[").is_err());
+    }
+}
@@ -0,0 +1,148 @@
+use anyhow::Result;
+
+use super::{nanoseconds_per, Adapter, BenchmarkResult, MeasureResult};
+
+// Every benchmark row ends one of these branches, e.g. `│  ├─ 10   1.2 ns │ ...`
+const BRANCHES: [&str; 2] = ["├─ ", "╰─ "];
+
+/// Parses Divan's tree table:
+///
+/// ```text
+/// example        fastest  │ slowest  │ median   │ mean     │ samples │ iters
+/// ╰─ fibonacci            │          │          │          │         │
+///    ├─ 1        1.2 ns   │ 3.4 ns   │ 1.3 ns   │ 1.4 ns   │ 100     │ 12800
+/// ```
+///
+/// Nested names are joined with `/`, so the row above is `fibonacci/1`.
+pub struct Divan;
+
+impl Adapter for Divan {
+    fn name(&self) -> &'static str {
+        "divan"
+    }
+
+    fn detect(&self, output: &str) -> bool {
+        output
+            .lines()
+            .any(|line| line.contains(" fastest ") && line.contains("│ slowest "))
+    }
+
+    fn parse(&self, output: &str) -> Result<Vec<BenchmarkResult>> {
+        let mut results = Vec::new();
+        let mut path: Vec<String> = Vec::new();
+
+        for line in output.lines() {
+            let Some((depth, row)) = split_tree_prefix(line) else {
+                continue;
+            };
+            let cells: Vec<&str> = row.split('│').collect();
+            let Some((name, fastest)) = split_name(cells[0]) else {
+                continue;
+            };
+
+            path.truncate(depth);
+            path.push(name.to_string());
+
+            // Group rows have no timings
+            let (Some(fastest), [slowest, median, mean, ..]) = (fastest, &cells[1..]) else {
+                continue;
+            };
+            let (Some(slowest), Some(median), Some(mean)) =
+                (parse_time(slowest), parse_time(median), parse_time(mean))
+            else {
+                continue;
+            };
+
+            results.push(BenchmarkResult {
+                name: path.join("/"),
+                measures: vec![
+                    MeasureResult {
+                        measure: "latency".to_string(),
+                        units: Some("ns".to_string()),
+                        value: mean,
+                        lower: Some(fastest),
+                        upper: Some(slowest),
+                    },
+                    MeasureResult {
+                        measure: "median".to_string(),
+                        units: Some("ns".to_string()),
+                        value: median,
+                        lower: None,
+                        upper: None,
+                    },
+                ],
+            });
+        }
+
+        Ok(results)
+    }
+}
+
+/// Returns the nesting depth of a benchmark row and the text after its branch.
+fn split_tree_prefix(line: &str) -> Option<(usize, &str)> {
+    let (index, branch) = BRANCHES
+        .iter()
+        .filter_map(|b| line.find(b).map(|i| (i, b)))
+        .min_by_key(|(i, _)| *i)?;
+    let prefix = &line[..index];
+    if !prefix.chars().all(|c| c == '│' || c == ' ') {
+        return None;
+    }
+    // Each level of nesting is three characters wide
+    Some((prefix.chars().count() / 3, &line[index + branch.len()..]))
+}
+
+/// Splits the first cell into the benchmark name and its fastest time, if any.
+fn split_name(cell: &str) -> Option<(&str, Option<f64>)> {
+    let cell = cell.trim();
+    let mut tokens = cell.rsplitn(3, char::is_whitespace);
+    let (unit, value, name) = (tokens.next()?, tokens.next(), tokens.next());
+
+    match (
+        value.and_then(|v| parse_time(&format!("{} {}", v, unit))),
+        name,
+    ) {
+        (Some(fastest), Some(name)) => Some((name.trim(), Some(fastest))),
+        _ if !cell.is_empty() => Some((cell, None)),
+        _ => None,
+    }
+}
+
+fn parse_time(cell: &str) -> Option<f64> {
+    let mut parts = cell.split_whitespace();
+    let value: f64 = parts.next()?.parse().ok()?;
+    Some(value * nanoseconds_per(parts.next()?)?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_divan_output() {
+        let output = r#"
+Timer precision: 20 ns
+example              fastest       │ slowest       │ median        │ mean          │ samples │ iters
+├─ fibonacci                       │               │               │               │         │
+│  ├─ 1              1.2 ns        │ 3.4 ns        │ 1.3 ns        │ 1.4 ns        │ 100     │ 12800
+│  ╰─ 10             1.1 µs        │ 2 µs          │ 1.2 µs        │ 1.25 µs       │ 100     │ 800
+╰─ sum                             │               │               │               │         │
+   ╰─ Vec<u64>                     │               │               │               │         │
+      ╰─ 1000        2.5 ms        │ 3 ms          │ 2.6 ms        │ 2.7 ms        │ 100     │ 100
+"#;
+
+        assert!(Divan.detect(output));
+        let results = Divan.parse(output).unwrap();
+        let names: Vec<_> = results.iter().map(|r| r.name.as_str()).collect();
+        assert_eq!(names, ["fibonacci/1", "fibonacci/10", "sum/Vec<u64>/1000"]);
+
+        let latency = &results[1].measures[0];
+        assert_eq!(latency.value, 1250.0);
+        assert_eq!(latency.lower, Some(1100.0));
+        assert_eq!(latency.upper, Some(2000.0));
+        assert_eq!(results[1].measures[1].measure, "median");
+        assert_eq!(results[1].measures[1].value, 1200.0);
+
+        assert_eq!(results[2].measures[0].value, 2_700_000.0);
+    }
+}
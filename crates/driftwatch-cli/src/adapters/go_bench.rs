@@ -0,0 +1,155 @@
+use anyhow::Result;
+use regex::Regex;
+use std::sync::LazyLock;
+
+use super::{Adapter, BenchmarkResult, MeasureResult};
+
+// `BenchmarkFib10-8   	 3000000	       412 ns/op	      16 B/op	       1 allocs/op`
+// The `-8` GOMAXPROCS suffix is dropped so results compare across machines.
+static GO_BENCH_REGEX: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"(?m)^(Benchmark\S+?)(?:-\d+)?\s+\d+\s+(\S.*)$").unwrap());
+
+struct Samples {
+    measure: String,
+    units: String,
+    values: Vec<f64>,
+}
+
+/// Parses `go test -bench` output. Each `<value> <unit>` pair on a result line
+/// becomes its own measure, and repeated lines from `-count=N` are averaged.
+pub struct GoBench;
+
+impl Adapter for GoBench {
+    fn name(&self) -> &'static str {
+        "go"
+    }
+
+    fn detect(&self, output: &str) -> bool {
+        GO_BENCH_REGEX
+            .captures_iter(output)
+            .any(|cap| cap[2].contains(" ns/op"))
+    }
+
+    fn parse(&self, output: &str) -> Result<Vec<BenchmarkResult>> {
+        // Benchmarks in first-seen order, with every measure they reported
+        let mut runs: Vec<(String, Vec<Samples>)> = Vec::new();
+
+        for cap in GO_BENCH_REGEX.captures_iter(output) {
+            let name = &cap[1];
+            let index = match runs.iter().position(|(n, _)| n == name) {
+                Some(index) => index,
+                None => {
+                    runs.push((name.to_string(), Vec::new()));
+                    runs.len() - 1
+                }
+            };
+
+            let fields: Vec<&str> = cap[2].split_whitespace().collect();
+            for pair in fields.chunks_exact(2) {
+                let Ok(value) = pair[0].parse::<f64>() else {
+                    continue;
+                };
+                let (measure, units) = measure_for(pair[1]);
+                let measures = &mut runs[index].1;
+                match measures.iter_mut().find(|s| s.measure == measure) {
+                    Some(samples) => samples.values.push(value),
+                    None => measures.push(Samples {
+                        measure,
+                        units,
+                        values: vec![value],
+                    }),
+                }
+            }
+        }
+
+        Ok(runs
+            .into_iter()
+            .filter(|(_, measures)| !measures.is_empty())
+            .map(|(name, measures)| BenchmarkResult {
+                name,
+                measures: measures.into_iter().map(summarize).collect(),
+            })
+            .collect())
+    }
+}
+
+/// Maps a Go benchmark unit onto a measure name and the units it is stored in.
+fn measure_for(unit: &str) -> (String, String) {
+    match unit {
+        "ns/op" => ("latency".to_string(), "ns".to_string()),
+        "B/op" => ("bytes-per-op".to_string(), "bytes".to_string()),
+        "allocs/op" => ("allocs-per-op".to_string(), "count".to_string()),
+        "MB/s" => ("throughput".to_string(), "MB/s".to_string()),
+        // Custom metrics reported with b.ReportMetric
+        other => (
+            other
+                .split(|c: char| !c.is_ascii_alphanumeric())
+                .filter(|part| !part.is_empty())
+                .collect::<Vec<_>>()
+                .join("-")
+                .to_ascii_lowercase(),
+            other.to_string(),
+        ),
+    }
+}
+
+/// Averages repeated runs, bounded by the fastest and slowest of them.
+fn summarize(samples: Samples) -> MeasureResult {
+    let values = &samples.values;
+    let mean = values.iter().sum::<f64>() / values.len() as f64;
+    let (lower, upper) = if values.len() > 1 {
+        (
+            Some(values.iter().copied().fold(f64::INFINITY, f64::min)),
+            Some(values.iter().copied().fold(f64::NEG_INFINITY, f64::max)),
+        )
+    } else {
+        (None, None)
+    };
+
+    MeasureResult {
+        measure: samples.measure,
+        units: Some(samples.units),
+        value: mean,
+        lower,
+        upper,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_go_bench_output() {
+        let output = "goos: linux
+goarch: amd64
+pkg: example.com/fib
+cpu: AMD Ryzen 9 5950X 16-Core Processor
+BenchmarkFib10-8          \t 3000000\t       412 ns/op\t      16 B/op\t       1 allocs/op
+BenchmarkFib10-8          \t 3000000\t       420 ns/op\t      16 B/op\t       1 allocs/op
+BenchmarkParse/small-8    \t  500000\t      2345 ns/op\t  54.59 MB/s\t    3.00 tokens/op
+PASS
+ok  \texample.com/fib\t3.512s
+";
+
+        assert!(GoBench.detect(output));
+        let results = GoBench.parse(output).unwrap();
+        assert_eq!(results.len(), 2);
+
+        let fib = &results[0];
+        assert_eq!(fib.name, "BenchmarkFib10");
+        let names: Vec<_> = fib.measures.iter().map(|m| m.measure.as_str()).collect();
+        assert_eq!(names, ["latency", "bytes-per-op", "allocs-per-op"]);
+        assert_eq!(fib.measures[0].value, 416.0);
+        assert_eq!(fib.measures[0].lower, Some(412.0));
+        assert_eq!(fib.measures[0].upper, Some(420.0));
+        assert_eq!(fib.measures[1].units.as_deref(), Some("bytes"));
+
+        let parse = &results[1];
+        assert_eq!(parse.name, "BenchmarkParse/small");
+        assert_eq!(parse.measures[1].measure, "throughput");
+        assert_eq!(parse.measures[2].measure, "tokens-op");
+        assert_eq!(parse.measures[2].units.as_deref(), Some("tokens/op"));
+        assert_eq!(parse.measures[0].lower, None);
+    }
+}
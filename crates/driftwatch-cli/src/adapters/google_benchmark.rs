@@ -0,0 +1,236 @@
+use anyhow::{Context, Result};
+use serde::Deserialize;
+
+use super::{nanoseconds_per, Adapter, BenchmarkResult, MeasureResult};
+
+#[derive(Deserialize)]
+struct Report {
+    context: serde_json::Value,
+    benchmarks: Vec<Run>,
+}
+
+#[derive(Deserialize)]
+struct Run {
+    name: String,
+    run_name: Option<String>,
+    run_type: Option<String>,
+    aggregate_name: Option<String>,
+    #[serde(default)]
+    error_occurred: bool,
+    real_time: Option<f64>,
+    cpu_time: Option<f64>,
+    time_unit: Option<String>,
+}
+
+impl Run {
+    fn run_name(&self) -> &str {
+        self.run_name.as_deref().unwrap_or(&self.name)
+    }
+
+    fn scale(&self) -> Result<f64> {
+        let unit = self.time_unit.as_deref().unwrap_or("ns");
+        nanoseconds_per(unit).with_context(|| format!("Unknown time_unit '{}'", unit))
+    }
+}
+
+#[derive(Default)]
+struct Samples {
+    iterations: Vec<f64>,
+    mean: Option<f64>,
+    stddev: Option<f64>,
+}
+
+impl Samples {
+    /// The `mean` aggregate with one standard deviation either side when
+    /// the benchmark ran with repetitions, the spread of the iteration runs
+    /// otherwise.
+    fn measure(&self, measure: &str) -> Option<MeasureResult> {
+        let (value, lower, upper) = match (self.mean, self.stddev) {
+            (Some(mean), Some(stddev)) => (mean, Some(mean - stddev), Some(mean + stddev)),
+            (Some(mean), None) => (mean, None, None),
+            _ if self.iterations.len() > 1 => {
+                let n = self.iterations.len() as f64;
+                let min = self
+                    .iterations
+                    .iter()
+                    .copied()
+                    .fold(f64::INFINITY, f64::min);
+                let max = self
+                    .iterations
+                    .iter()
+                    .copied()
+                    .fold(f64::NEG_INFINITY, f64::max);
+                (
+                    self.iterations.iter().sum::<f64>() / n,
+                    Some(min),
+                    Some(max),
+                )
+            }
+            _ => (*self.iterations.first()?, None, None),
+        };
+
+        Some(MeasureResult {
+            measure: measure.to_string(),
+            units: Some("ns".to_string()),
+            value,
+            lower,
+            upper,
+        })
+    }
+}
+
+/// Parses `--benchmark_format=json` output from Google Benchmark.
+pub struct GoogleBenchmark;
+
+impl Adapter for GoogleBenchmark {
+    fn name(&self) -> &'static str {
+        "google-benchmark"
+    }
+
+    fn detect(&self, output: &str) -> bool {
+        serde_json::from_str::<Report>(output.trim()).is_ok_and(|r| r.context.is_object())
+    }
+
+    fn parse(&self, output: &str) -> Result<Vec<BenchmarkResult>> {
+        let report: Report =
+            serde_json::from_str(output.trim()).context("Invalid Google Benchmark JSON")?;
+
+        // Repetitions produce one entry per iteration run plus aggregates,
+        // all sharing a run_name; keep them together in first-seen order.
+        let mut runs: Vec<(String, Samples, Samples)> = Vec::new();
+        for run in &report.benchmarks {
+            if run.error_occurred {
+                continue;
+            }
+            let scale = run.scale()?;
+
+            let index = match runs.iter().position(|(name, _, _)| name == run.run_name()) {
+                Some(index) => index,
+                None => {
+                    runs.push((
+                        run.run_name().to_string(),
+                        Samples::default(),
+                        Samples::default(),
+                    ));
+                    runs.len() - 1
+                }
+            };
+            let (_, real, cpu) = &mut runs[index];
+
+            for (samples, time) in [(real, run.real_time), (cpu, run.cpu_time)] {
+                let Some(time) = time.map(|t| t * scale) else {
+                    continue;
+                };
+                match (run.run_type.as_deref(), run.aggregate_name.as_deref()) {
+                    (Some("aggregate"), Some("mean")) => samples.mean = Some(time),
+                    (Some("aggregate"), Some("stddev")) => samples.stddev = Some(time),
+                    (Some("aggregate"), _) => {}
+                    _ => samples.iterations.push(time),
+                }
+            }
+        }
+
+        Ok(runs
+            .into_iter()
+            .map(|(name, real, cpu)| BenchmarkResult {
+                name,
+                measures: [real.measure("latency"), cpu.measure("cpu-time")]
+                    .into_iter()
+                    .flatten()
+                    .collect(),
+            })
+            .filter(|r| !r.measures.is_empty())
+            .collect())
+    }
+
+    fn stdout_only(&self) -> bool {
+        true
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_google_benchmark_output() {
+        let output = r#"{
+  "context": { "date": "2024-12-28T10:00:00+00:00", "num_cpus": 8 },
+  "benchmarks": [
+    {
+      "name": "BM_StringCopy/64",
+      "run_name": "BM_StringCopy/64",
+      "run_type": "iteration",
+      "iterations": 1000000,
+      "real_time": 1.5,
+      "cpu_time": 1.4,
+      "time_unit": "us"
+    },
+    {
+      "name": "BM_Sort/1024",
+      "run_name": "BM_Sort/1024",
+      "run_type": "iteration",
+      "real_time": 20.0,
+      "cpu_time": 19.0,
+      "time_unit": "ns"
+    },
+    {
+      "name": "BM_Sort/1024",
+      "run_name": "BM_Sort/1024",
+      "run_type": "iteration",
+      "real_time": 24.0,
+      "cpu_time": 23.0,
+      "time_unit": "ns"
+    },
+    {
+      "name": "BM_Sort/1024_mean",
+      "run_name": "BM_Sort/1024",
+      "run_type": "aggregate",
+      "aggregate_name": "mean",
+      "real_time": 22.0,
+      "cpu_time": 21.0,
+      "time_unit": "ns"
+    },
+    {
+      "name": "BM_Sort/1024_stddev",
+      "run_name": "BM_Sort/1024",
+      "run_type": "aggregate",
+      "aggregate_name": "stddev",
+      "real_time": 2.0,
+      "cpu_time": 2.0,
+      "time_unit": "ns"
+    },
+    {
+      "name": "BM_Broken",
+      "run_type": "iteration",
+      "error_occurred": true,
+      "error_message": "boom"
+    }
+  ]
+}"#;
+
+        assert!(GoogleBenchmark.detect(output));
+        let results = GoogleBenchmark.parse(output).unwrap();
+        assert_eq!(results.len(), 2);
+
+        let copy = &results[0];
+        assert_eq!(copy.name, "BM_StringCopy/64");
+        assert_eq!(copy.measures[0].measure, "latency");
+        assert_eq!(copy.measures[0].value, 1500.0);
+        assert_eq!(copy.measures[0].lower, None);
+        assert_eq!(copy.measures[1].measure, "cpu-time");
+        assert_eq!(copy.measures[1].value, 1400.0);
+
+        let sort = &results[1];
+        assert_eq!(sort.measures[0].value, 22.0);
+        assert_eq!(sort.measures[0].lower, Some(20.0));
+        assert_eq!(sort.measures[0].upper, Some(24.0));
+        assert_eq!(sort.measures[1].value, 21.0);
+    }
+
+    #[test]
+    fn test_detect_google_benchmark() {
+        assert!(!GoogleBenchmark.detect(r#"{"bench": {"latency": {"value": 1.0}}}"#));
+        assert!(!GoogleBenchmark.detect("BM_Sort/1024   22 ns   21 ns   1000"));
+    }
+}
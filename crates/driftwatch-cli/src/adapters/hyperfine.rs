@@ -0,0 +1,131 @@
+use anyhow::{Context, Result};
+use serde::Deserialize;
+
+use super::{Adapter, BenchmarkResult, MeasureResult};
+
+const NANOS_PER_SECOND: f64 = 1e9;
+
+#[derive(Deserialize)]
+struct Export {
+    results: Vec<CommandResult>,
+}
+
+// hyperfine reports every time in seconds
+#[derive(Deserialize)]
+struct CommandResult {
+    command: String,
+    mean: f64,
+    stddev: Option<f64>,
+    median: Option<f64>,
+    min: f64,
+    max: f64,
+}
+
+/// Parses the file written by `hyperfine --export-json`.
+pub struct Hyperfine;
+
+impl Adapter for Hyperfine {
+    fn name(&self) -> &'static str {
+        "hyperfine"
+    }
+
+    fn detect(&self, output: &str) -> bool {
+        serde_json::from_str::<Export>(output.trim()).is_ok()
+    }
+
+    fn parse(&self, output: &str) -> Result<Vec<BenchmarkResult>> {
+        let export: Export =
+            serde_json::from_str(output.trim()).context("Invalid hyperfine JSON export")?;
+        let time = |measure: &str, seconds: f64| MeasureResult {
+            measure: measure.to_string(),
+            units: Some("ns".to_string()),
+            value: seconds * NANOS_PER_SECOND,
+            lower: None,
+            upper: None,
+        };
+
+        Ok(export
+            .results
+            .into_iter()
+            .map(|r| {
+                // A single run has no stddev; min and max still bound the mean
+                let (lower, upper) = match r.stddev {
+                    Some(stddev) => ((r.mean - stddev).max(r.min), (r.mean + stddev).min(r.max)),
+                    None => (r.min, r.max),
+                };
+                let mut measures = vec![MeasureResult {
+                    lower: Some(lower * NANOS_PER_SECOND),
+                    upper: Some(upper * NANOS_PER_SECOND),
+                    ..time("latency", r.mean)
+                }];
+                if let Some(median) = r.median {
+                    measures.push(time("median", median));
+                }
+                measures.push(time("min", r.min));
+                measures.push(time("max", r.max));
+
+                BenchmarkResult {
+                    name: r.command,
+                    measures,
+                }
+            })
+            .collect())
+    }
+
+    fn stdout_only(&self) -> bool {
+        true
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_hyperfine_export() {
+        let output = r#"{
+  "results": [
+    {
+      "command": "rg foo",
+      "mean": 0.010,
+      "stddev": 0.001,
+      "median": 0.0098,
+      "user": 0.004,
+      "system": 0.005,
+      "min": 0.0095,
+      "max": 0.015,
+      "times": [0.0095, 0.010, 0.015],
+      "exit_codes": [0, 0, 0]
+    },
+    {
+      "command": "grep foo",
+      "mean": 0.5,
+      "stddev": null,
+      "median": 0.5,
+      "min": 0.5,
+      "max": 0.5
+    }
+  ]
+}"#;
+
+        assert!(Hyperfine.detect(output));
+        let results = Hyperfine.parse(output).unwrap();
+        assert_eq!(results.len(), 2);
+
+        let rg = &results[0];
+        assert_eq!(rg.name, "rg foo");
+        let names: Vec<_> = rg.measures.iter().map(|m| m.measure.as_str()).collect();
+        assert_eq!(names, ["latency", "median", "min", "max"]);
+        assert!((rg.measures[0].value - 10_000_000.0).abs() < 1e-3);
+        // mean - stddev is clamped to the fastest run
+        assert!((rg.measures[0].lower.unwrap() - 9_500_000.0).abs() < 1e-3);
+        assert!((rg.measures[0].upper.unwrap() - 11_000_000.0).abs() < 1e-3);
+
+        assert_eq!(results[1].measures[0].lower, Some(500_000_000.0));
+    }
+
+    #[test]
+    fn test_detect_hyperfine() {
+        assert!(!Hyperfine.detect(r#"{"bench": {"latency": {"value": 1.0}}}"#));
+    }
+}
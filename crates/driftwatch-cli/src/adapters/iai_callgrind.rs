@@ -0,0 +1,150 @@
+use anyhow::Result;
+use regex::Regex;
+use std::sync::LazyLock;
+
+use super::{Adapter, BenchmarkResult, MeasureResult};
+
+// Counter lines are indented under the benchmark name, e.g.
+// `  Instructions:                1734|1701            (+1.94%)`.
+// Newer iai-callgrind versions append the previous value after `|`, the
+// original iai prints only the current one.
+static COUNTER_REGEX: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r"^\s+([A-Za-z0-9][A-Za-z0-9 +/]*):\s+([0-9]+)(?:\||\s|$)").unwrap()
+});
+
+static DETECT_REGEX: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"(?m)^\s+Instructions:\s+[0-9]+").unwrap());
+
+/// Parses iai-callgrind (and iai) output, one measure per reported counter.
+pub struct IaiCallgrind;
+
+impl Adapter for IaiCallgrind {
+    fn name(&self) -> &'static str {
+        "iai-callgrind"
+    }
+
+    fn detect(&self, output: &str) -> bool {
+        DETECT_REGEX.is_match(output)
+    }
+
+    fn parse(&self, output: &str) -> Result<Vec<BenchmarkResult>> {
+        let mut results: Vec<BenchmarkResult> = Vec::new();
+
+        for line in output.lines() {
+            if line.trim().is_empty() {
+                continue;
+            }
+            if !line.starts_with(char::is_whitespace) {
+                results.push(BenchmarkResult {
+                    name: line.trim().to_string(),
+                    measures: Vec::new(),
+                });
+                continue;
+            }
+
+            let (Some(current), Some(cap)) = (results.last_mut(), COUNTER_REGEX.captures(line))
+            else {
+                continue;
+            };
+            let Ok(value) = cap[2].parse::<f64>() else {
+                continue;
+            };
+            let measure = measure_name(&cap[1]);
+            let units = if measure == "estimated-cycles" {
+                "cycles"
+            } else {
+                "count"
+            };
+            current.measures.push(MeasureResult {
+                measure,
+                units: Some(units.to_string()),
+                value,
+                lower: None,
+                upper: None,
+            });
+        }
+
+        // Summary lines such as `Iai-Callgrind result: Ok` carry no counters
+        results.retain(|r| !r.measures.is_empty());
+        Ok(results)
+    }
+}
+
+/// `L1 Hits` -> `l1-hits`, `Total read+write` -> `total-read-write`
+fn measure_name(label: &str) -> String {
+    label
+        .split(|c: char| !c.is_ascii_alphanumeric())
+        .filter(|part| !part.is_empty())
+        .map(str::to_ascii_lowercase)
+        .collect::<Vec<_>>()
+        .join("-")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_iai_callgrind_output() {
+        let output = r#"
+lib_bench_fib::bench_fib_group::bench_fib short:10
+  Instructions:                1734|1701            (+1.94004%) [+1.01940x]
+  L1 Hits:                     2359|2326            (+1.41875%) [+1.01419x]
+  LL Hits:                        0|0               (No change)
+  RAM Hits:                       3|3               (No change)
+  Total read+write:            2362|2329            (+1.41692%) [+1.01417x]
+  Estimated Cycles:            2464|2431            (+1.35747%) [+1.01357x]
+lib_bench_fib::bench_fib_group::bench_fib long:30
+  Instructions:            26214734|N/A             (*********)
+  L1 Hits:                 35638616|N/A             (*********)
+
+Iai-Callgrind result: Ok. 2 without regressions; 0 regressed; 2 benchmarks finished in 0.49s
+"#;
+
+        assert!(IaiCallgrind.detect(output));
+        let results = IaiCallgrind.parse(output).unwrap();
+        assert_eq!(results.len(), 2);
+
+        let short = &results[0];
+        assert_eq!(
+            short.name,
+            "lib_bench_fib::bench_fib_group::bench_fib short:10"
+        );
+        let names: Vec<_> = short.measures.iter().map(|m| m.measure.as_str()).collect();
+        assert_eq!(
+            names,
+            [
+                "instructions",
+                "l1-hits",
+                "ll-hits",
+                "ram-hits",
+                "total-read-write",
+                "estimated-cycles"
+            ]
+        );
+        assert_eq!(short.measures[0].value, 1734.0);
+        assert_eq!(short.measures[0].units.as_deref(), Some("count"));
+        assert_eq!(short.measures[5].units.as_deref(), Some("cycles"));
+
+        assert_eq!(results[1].measures.len(), 2);
+        assert_eq!(results[1].measures[0].value, 26214734.0);
+    }
+
+    #[test]
+    fn test_parse_iai_output() {
+        let output = r#"
+bench_fibonacci_short
+  Instructions:                1735
+  L1 Accesses:                2364
+  L2 Accesses:                   1
+  RAM Accesses:                  1
+  Estimated Cycles:           2404
+"#;
+
+        let results = IaiCallgrind.parse(output).unwrap();
+        assert_eq!(results.len(), 1);
+        assert_eq!(results[0].measures.len(), 5);
+        assert_eq!(results[0].measures[1].measure, "l1-accesses");
+        assert_eq!(results[0].measures[4].value, 2404.0);
+    }
+}
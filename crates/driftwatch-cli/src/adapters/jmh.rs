@@ -0,0 +1,155 @@
+use anyhow::{Context, Result};
+use serde::Deserialize;
+use std::collections::BTreeMap;
+
+use super::{nanoseconds_per, Adapter, BenchmarkResult, MeasureResult};
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct Run {
+    benchmark: String,
+    mode: String,
+    #[serde(default)]
+    params: BTreeMap<String, String>,
+    primary_metric: Metric,
+}
+
+// JMH writes `"NaN"` for bounds it could not compute, hence the raw values
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct Metric {
+    score: f64,
+    #[serde(default)]
+    score_confidence: Vec<serde_json::Value>,
+    score_unit: String,
+}
+
+/// Parses JMH's `-rf json` result file.
+pub struct Jmh;
+
+impl Adapter for Jmh {
+    fn name(&self) -> &'static str {
+        "jmh"
+    }
+
+    fn detect(&self, output: &str) -> bool {
+        serde_json::from_str::<Vec<Run>>(output.trim()).is_ok_and(|runs| !runs.is_empty())
+    }
+
+    fn parse(&self, output: &str) -> Result<Vec<BenchmarkResult>> {
+        let runs: Vec<Run> =
+            serde_json::from_str(output.trim()).context("Invalid JMH JSON result")?;
+
+        runs.into_iter()
+            .map(|run| {
+                let metric = &run.primary_metric;
+                let (measure, units, scale) = normalize(&run.mode, &metric.score_unit)?;
+                let bound = |i: usize| {
+                    metric
+                        .score_confidence
+                        .get(i)
+                        .and_then(|v| v.as_f64())
+                        .map(|v| v * scale)
+                };
+
+                // Parameterised runs share a method name, e.g. `Sort.run/size=10`
+                let mut name = run.benchmark;
+                for (key, value) in &run.params {
+                    name.push_str(&format!("/{}={}", key, value));
+                }
+
+                Ok(BenchmarkResult {
+                    name,
+                    measures: vec![MeasureResult {
+                        measure: measure.to_string(),
+                        units: Some(units.to_string()),
+                        value: metric.score * scale,
+                        lower: bound(0),
+                        upper: bound(1),
+                    }],
+                })
+            })
+            .collect()
+    }
+
+    fn stdout_only(&self) -> bool {
+        true
+    }
+}
+
+/// Maps a JMH mode and score unit onto a measure, its units and the factor
+/// that converts the score into them: times go to `ns`, rates to `ops/s`.
+fn normalize(mode: &str, unit: &str) -> Result<(&'static str, &'static str, f64)> {
+    let unknown = || format!("Unsupported JMH score unit '{}'", unit);
+
+    if mode == "thrpt" {
+        let per = unit.strip_prefix("ops/").with_context(unknown)?;
+        let nanos = nanoseconds_per(per).with_context(unknown)?;
+        Ok(("throughput", "ops/s", 1e9 / nanos))
+    } else {
+        let time = unit.strip_suffix("/op").with_context(unknown)?;
+        let nanos = nanoseconds_per(time).with_context(unknown)?;
+        Ok(("latency", "ns", nanos))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_jmh_json() {
+        let output = r#"[
+  {
+    "jmhVersion": "1.37",
+    "benchmark": "org.sample.Sort.run",
+    "mode": "avgt",
+    "threads": 1,
+    "forks": 2,
+    "params": { "size": "10" },
+    "primaryMetric": {
+      "score": 1.5,
+      "scoreError": 0.1,
+      "scoreConfidence": [1.4, 1.6],
+      "scoreUnit": "us/op",
+      "rawData": [[1.5, 1.5]]
+    },
+    "secondaryMetrics": {}
+  },
+  {
+    "benchmark": "org.sample.Hash.run",
+    "mode": "thrpt",
+    "primaryMetric": {
+      "score": 2.0,
+      "scoreError": "NaN",
+      "scoreConfidence": ["NaN", "NaN"],
+      "scoreUnit": "ops/ms"
+    }
+  }
+]"#;
+
+        assert!(Jmh.detect(output));
+        let results = Jmh.parse(output).unwrap();
+        assert_eq!(results.len(), 2);
+
+        assert_eq!(results[0].name, "org.sample.Sort.run/size=10");
+        let latency = &results[0].measures[0];
+        assert_eq!(latency.measure, "latency");
+        assert_eq!(latency.value, 1500.0);
+        assert_eq!(latency.lower, Some(1400.0));
+        assert_eq!(latency.upper, Some(1600.0));
+
+        let throughput = &results[1].measures[0];
+        assert_eq!(throughput.measure, "throughput");
+        assert_eq!(throughput.units.as_deref(), Some("ops/s"));
+        assert_eq!(throughput.value, 2000.0);
+        assert_eq!(throughput.lower, None);
+    }
+
+    #[test]
+    fn test_parse_jmh_unknown_unit() {
+        let output = r#"[{"benchmark": "a", "mode": "avgt",
+            "primaryMetric": {"score": 1.0, "scoreUnit": "fortnights/op"}}]"#;
+        assert!(Jmh.parse(output).is_err());
+    }
+}
@@ -0,0 +1,84 @@
+use anyhow::Result;
+use regex::Regex;
+use std::sync::LazyLock;
+
+use super::{Adapter, BenchmarkResult, MeasureResult};
+
+// `test bench_add_two ... bench:       1,234 ns/iter (+/- 56)`
+// Newer nightlies print fractional values such as `1,234.50`.
+static LIBTEST_REGEX: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(
+        r"(?m)^test\s+(\S+)\s+\.\.\.\s+bench:\s+([0-9,.]+)\s+ns/iter\s+\(\+/-\s+([0-9,.]+)\)",
+    )
+    .unwrap()
+});
+
+/// Parses the output of libtest's unstable `#[bench]` harness.
+pub struct Libtest;
+
+impl Adapter for Libtest {
+    fn name(&self) -> &'static str {
+        "libtest"
+    }
+
+    fn detect(&self, output: &str) -> bool {
+        LIBTEST_REGEX.is_match(output)
+    }
+
+    fn parse(&self, output: &str) -> Result<Vec<BenchmarkResult>> {
+        Ok(LIBTEST_REGEX
+            .captures_iter(output)
+            .filter_map(|cap| {
+                let value = parse_number(&cap[2])?;
+                let deviation = parse_number(&cap[3])?;
+
+                Some(BenchmarkResult {
+                    name: cap[1].to_string(),
+                    measures: vec![MeasureResult {
+                        measure: "latency".to_string(),
+                        units: Some("ns".to_string()),
+                        value,
+                        lower: Some((value - deviation).max(0.0)),
+                        upper: Some(value + deviation),
+                    }],
+                })
+            })
+            .collect())
+    }
+}
+
+fn parse_number(s: &str) -> Option<f64> {
+    s.replace(',', "").parse().ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_libtest_output() {
+        let output = r#"
+running 3 tests
+test tests::bench_add_two   ... bench:       1,234 ns/iter (+/- 56)
+test tests::bench_fast      ... bench:           2 ns/iter (+/- 5)
+test tests::bench_fraction  ... bench:      12.50 ns/iter (+/- 0.25)
+test tests::not_a_bench ... ok
+
+test result: ok. 0 passed; 0 failed; 0 ignored; 3 measured; 0 filtered out
+"#;
+
+        assert!(Libtest.detect(output));
+        let results = Libtest.parse(output).unwrap();
+        assert_eq!(results.len(), 3);
+
+        let add = &results[0].measures[0];
+        assert_eq!(results[0].name, "tests::bench_add_two");
+        assert_eq!(add.value, 1234.0);
+        assert_eq!(add.lower, Some(1178.0));
+        assert_eq!(add.upper, Some(1290.0));
+
+        // The lower bound never goes below zero
+        assert_eq!(results[1].measures[0].lower, Some(0.0));
+        assert_eq!(results[2].measures[0].value, 12.5);
+    }
+}
@@ -1 +1,130 @@
+use anyhow::Result;
+use std::path::PathBuf;
+use std::time::SystemTime;
+
+pub mod bmf;
 pub mod criterion;
+pub mod criterion_json;
+pub mod custom;
+pub mod divan;
+pub mod go_bench;
+pub mod google_benchmark;
+pub mod hyperfine;
+pub mod iai_callgrind;
+pub mod jmh;
+pub mod libtest;
+pub mod pytest_benchmark;
+
+/// One benchmark parsed from harness output, with every measure it reported.
+#[derive(Debug, Clone)]
+pub struct BenchmarkResult {
+    pub name: String,
+    pub measures: Vec<MeasureResult>,
+}
+
+#[derive(Debug, Clone)]
+pub struct MeasureResult {
+    pub measure: String,
+    pub units: Option<String>,
+    pub value: f64,
+    pub lower: Option<f64>,
+    pub upper: Option<f64>,
+}
+
+impl MeasureResult {
+    /// Rates such as throughput improve as they rise; times, sizes and
+    /// counts as they fall.
+    pub fn higher_is_better(&self) -> bool {
+        self.units.as_deref().is_some_and(|u| u.ends_with("/s"))
+    }
+}
+
+/// Turns the output of a benchmark harness into results that can be submitted.
+pub trait Adapter {
+    /// Name used to select the adapter with `--adapter`.
+    fn name(&self) -> &'static str;
+
+    /// Returns true if `output` looks like it was produced by this harness.
+    fn detect(&self, output: &str) -> bool;
+
+    fn parse(&self, output: &str) -> Result<Vec<BenchmarkResult>>;
+
+    /// Structured formats are read from stdout alone so that build noise on
+    /// stderr does not corrupt the document.
+    fn stdout_only(&self) -> bool {
+        false
+    }
+}
+
+/// Settings for adapters that read more than the command output.
+#[derive(Debug, Clone, Default)]
+pub struct AdapterOptions {
+    /// Criterion's output directory, `target/criterion` when unset.
+    pub criterion_dir: Option<PathBuf>,
+    /// Submit throughput for Criterion benchmarks that declare one.
+    pub throughput: bool,
+    /// Result files written before this time are left over from earlier runs.
+    pub since: Option<SystemTime>,
+    /// The `custom` adapter from `driftwatch.toml`, if one is defined.
+    pub custom: Option<custom::CustomAdapterConfig>,
+}
+
+pub struct Registry {
+    adapters: Vec<Box<dyn Adapter>>,
+}
+
+impl Registry {
+    /// Every known adapter, in the order `auto` tries them. BMF accepts any
+    /// JSON object, so the JSON formats with a known shape go first.
+    pub fn new(options: &AdapterOptions) -> Result<Self> {
+        let mut adapters: Vec<Box<dyn Adapter>> = vec![
+            Box::new(google_benchmark::GoogleBenchmark),
+            Box::new(hyperfine::Hyperfine),
+            Box::new(pytest_benchmark::PytestBenchmark),
+            Box::new(jmh::Jmh),
+            Box::new(bmf::Bmf),
+            Box::new(criterion::Criterion),
+            Box::new(criterion_json::CriterionJson::new(options)),
+            Box::new(iai_callgrind::IaiCallgrind),
+            Box::new(libtest::Libtest),
+            Box::new(divan::Divan),
+            Box::new(go_bench::GoBench),
+        ];
+        if let Some(config) = &options.custom {
+            adapters.push(Box::new(custom::Custom::new(config.clone())?));
+        }
+
+        Ok(Self { adapters })
+    }
+
+    pub fn find(&self, name: &str) -> Option<&dyn Adapter> {
+        self.adapters
+            .iter()
+            .map(|a| a.as_ref())
+            .find(|a| a.name() == name)
+    }
+
+    pub fn detect(&self, output: &str) -> Option<&dyn Adapter> {
+        self.adapters
+            .iter()
+            .map(|a| a.as_ref())
+            .find(|a| a.detect(output))
+    }
+
+    pub fn names(&self) -> Vec<&'static str> {
+        self.adapters.iter().map(|a| a.name()).collect()
+    }
+}
+
+/// Multiplier from a harness time unit to nanoseconds, the units every
+/// adapter submits `latency` in.
+pub fn nanoseconds_per(unit: &str) -> Option<f64> {
+    match unit {
+        "ps" => Some(0.001),
+        "ns" => Some(1.0),
+        "µs" | "us" => Some(1_000.0),
+        "ms" => Some(1_000_000.0),
+        "s" => Some(1_000_000_000.0),
+        _ => None,
+    }
+}
@@ -0,0 +1,126 @@
+use anyhow::{Context, Result};
+use serde::Deserialize;
+
+use super::{Adapter, BenchmarkResult, MeasureResult};
+
+const NANOS_PER_SECOND: f64 = 1e9;
+
+#[derive(Deserialize)]
+struct Report {
+    machine_info: serde_json::Value,
+    benchmarks: Vec<Benchmark>,
+}
+
+#[derive(Deserialize)]
+struct Benchmark {
+    fullname: String,
+    stats: Stats,
+}
+
+// pytest-benchmark reports every time in seconds
+#[derive(Deserialize)]
+struct Stats {
+    min: f64,
+    max: f64,
+    mean: f64,
+    stddev: f64,
+    median: f64,
+}
+
+/// Parses the file written by `pytest --benchmark-json`.
+pub struct PytestBenchmark;
+
+impl Adapter for PytestBenchmark {
+    fn name(&self) -> &'static str {
+        "pytest-benchmark"
+    }
+
+    fn detect(&self, output: &str) -> bool {
+        serde_json::from_str::<Report>(output.trim()).is_ok_and(|r| r.machine_info.is_object())
+    }
+
+    fn parse(&self, output: &str) -> Result<Vec<BenchmarkResult>> {
+        let report: Report =
+            serde_json::from_str(output.trim()).context("Invalid pytest-benchmark JSON")?;
+        let time = |measure: &str, seconds: f64| MeasureResult {
+            measure: measure.to_string(),
+            units: Some("ns".to_string()),
+            value: seconds * NANOS_PER_SECOND,
+            lower: None,
+            upper: None,
+        };
+
+        Ok(report
+            .benchmarks
+            .into_iter()
+            .map(|b| {
+                let s = b.stats;
+                let lower = (s.mean - s.stddev).max(s.min);
+                let upper = (s.mean + s.stddev).min(s.max);
+
+                BenchmarkResult {
+                    name: b.fullname,
+                    measures: vec![
+                        MeasureResult {
+                            lower: Some(lower * NANOS_PER_SECOND),
+                            upper: Some(upper * NANOS_PER_SECOND),
+                            ..time("latency", s.mean)
+                        },
+                        time("median", s.median),
+                        time("min", s.min),
+                        time("max", s.max),
+                    ],
+                }
+            })
+            .collect())
+    }
+
+    fn stdout_only(&self) -> bool {
+        true
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_pytest_benchmark_json() {
+        let output = r#"{
+  "machine_info": { "node": "ci", "python_version": "3.12.1" },
+  "commit_info": { "id": "abc123" },
+  "benchmarks": [
+    {
+      "group": null,
+      "name": "test_parse[small]",
+      "fullname": "tests/test_parse.py::test_parse[small]",
+      "params": { "size": "small" },
+      "stats": {
+        "min": 0.000010,
+        "max": 0.000020,
+        "mean": 0.000012,
+        "stddev": 0.000003,
+        "rounds": 1000,
+        "median": 0.000011,
+        "iqr": 0.000001,
+        "ops": 83333.3
+      }
+    }
+  ],
+  "datetime": "2024-12-28T10:00:00.000000",
+  "version": "4.0.0"
+}"#;
+
+        assert!(PytestBenchmark.detect(output));
+        let results = PytestBenchmark.parse(output).unwrap();
+        assert_eq!(results.len(), 1);
+        assert_eq!(results[0].name, "tests/test_parse.py::test_parse[small]");
+
+        let latency = &results[0].measures[0];
+        assert!((latency.value - 12_000.0).abs() < 1e-6);
+        // mean - stddev is clamped to the fastest round
+        assert!((latency.lower.unwrap() - 10_000.0).abs() < 1e-6);
+        assert!((latency.upper.unwrap() - 15_000.0).abs() < 1e-6);
+        assert_eq!(results[0].measures.len(), 4);
+    }
+}
@@ -0,0 +1,187 @@
+use clap::ValueEnum;
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+use std::collections::HashMap;
+
+use crate::api::MetricInput;
+
+const BOOTSTRAP_RESAMPLES: usize = 1000;
+// Fixed so the same runs always produce the same bounds
+const BOOTSTRAP_SEED: u64 = 0x5eed;
+
+/// How the values of repeated runs are combined.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum Aggregation {
+    Mean,
+    /// Median of the per-run values, which are usually means themselves
+    Median,
+}
+
+/// What the submitted lower and upper values are.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum Bounds {
+    /// The fastest and slowest run
+    MinMax,
+    /// A 95% bootstrap confidence interval of the aggregate
+    Bootstrap,
+}
+
+impl Aggregation {
+    fn apply(self, values: &[f64]) -> f64 {
+        match self {
+            Aggregation::Mean => values.iter().sum::<f64>() / values.len() as f64,
+            Aggregation::Median => {
+                let mut sorted = values.to_vec();
+                sorted.sort_by(f64::total_cmp);
+                let mid = sorted.len() / 2;
+                if sorted.len().is_multiple_of(2) {
+                    (sorted[mid - 1] + sorted[mid]) / 2.0
+                } else {
+                    sorted[mid]
+                }
+            }
+        }
+    }
+}
+
+/// Combines the metrics of several runs of the same command into one metric
+/// per benchmark and measure, in first-seen order.
+///
+/// A metric seen in only one run is passed through with its own bounds.
+pub fn aggregate(
+    runs: Vec<Vec<MetricInput>>,
+    aggregation: Aggregation,
+    bounds: Bounds,
+) -> Vec<MetricInput> {
+    let mut groups: Vec<(MetricInput, Vec<f64>)> = Vec::new();
+    let mut index: HashMap<(String, String), usize> = HashMap::new();
+
+    for metric in runs.into_iter().flatten() {
+        let key = (metric.benchmark.clone(), metric.measure.clone());
+        match index.get(&key) {
+            Some(&i) => groups[i].1.push(metric.value),
+            None => {
+                index.insert(key, groups.len());
+                let value = metric.value;
+                groups.push((metric, vec![value]));
+            }
+        }
+    }
+
+    groups
+        .into_iter()
+        .map(|(first, values)| {
+            if values.len() < 2 {
+                return first;
+            }
+            let (lower, upper) = match bounds {
+                Bounds::MinMax => (
+                    values.iter().copied().fold(f64::INFINITY, f64::min),
+                    values.iter().copied().fold(f64::NEG_INFINITY, f64::max),
+                ),
+                Bounds::Bootstrap => bootstrap(&values, aggregation),
+            };
+            MetricInput {
+                value: aggregation.apply(&values),
+                lower_value: Some(lower),
+                upper_value: Some(upper),
+                ..first
+            }
+        })
+        .collect()
+}
+
+/// The 2.5th and 97.5th percentiles of the aggregate over resamples of
+/// `values` drawn with replacement.
+fn bootstrap(values: &[f64], aggregation: Aggregation) -> (f64, f64) {
+    let mut rng = StdRng::seed_from_u64(BOOTSTRAP_SEED);
+    let mut sample = vec![0.0; values.len()];
+    let mut estimates: Vec<f64> = (0..BOOTSTRAP_RESAMPLES)
+        .map(|_| {
+            for slot in sample.iter_mut() {
+                *slot = values[rng.gen_range(0..values.len())];
+            }
+            aggregation.apply(&sample)
+        })
+        .collect();
+    estimates.sort_by(f64::total_cmp);
+
+    let percentile = |p: f64| estimates[((estimates.len() - 1) as f64 * p).round() as usize];
+    (percentile(0.025), percentile(0.975))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn metric(benchmark: &str, measure: &str, value: f64) -> MetricInput {
+        MetricInput {
+            benchmark: benchmark.to_string(),
+            measure: measure.to_string(),
+            units: Some("ns".to_string()),
+            higher_is_better: None,
+            value,
+            lower_value: Some(value - 1.0),
+            upper_value: Some(value + 1.0),
+        }
+    }
+
+    #[test]
+    fn test_aggregate_mean_min_max() {
+        let runs = vec![
+            vec![metric("a", "latency", 10.0), metric("b", "latency", 5.0)],
+            vec![metric("a", "latency", 14.0)],
+            vec![metric("a", "latency", 12.0), metric("c", "latency", 1.0)],
+        ];
+        let metrics = aggregate(runs, Aggregation::Mean, Bounds::MinMax);
+        let names: Vec<_> = metrics.iter().map(|m| m.benchmark.as_str()).collect();
+        assert_eq!(names, ["a", "b", "c"]);
+
+        assert_eq!(metrics[0].value, 12.0);
+        assert_eq!(metrics[0].lower_value, Some(10.0));
+        assert_eq!(metrics[0].upper_value, Some(14.0));
+        assert_eq!(metrics[0].units.as_deref(), Some("ns"));
+
+        // Seen once: the adapter's own bounds are kept
+        assert_eq!(metrics[1].value, 5.0);
+        assert_eq!(metrics[1].lower_value, Some(4.0));
+    }
+
+    #[test]
+    fn test_aggregate_median_ignores_outlier() {
+        let runs = [10.0, 11.0, 100.0, 12.0]
+            .into_iter()
+            .map(|v| vec![metric("a", "latency", v)])
+            .collect();
+        let metrics = aggregate(runs, Aggregation::Median, Bounds::MinMax);
+        assert_eq!(metrics[0].value, 11.5);
+        assert_eq!(metrics[0].upper_value, Some(100.0));
+    }
+
+    #[test]
+    fn test_aggregate_bootstrap_bounds() {
+        let values = [9.0, 10.0, 10.5, 11.0, 9.5, 10.2, 30.0];
+        let runs: Vec<_> = values
+            .into_iter()
+            .map(|v| vec![metric("a", "latency", v)])
+            .collect();
+        let metrics = aggregate(runs, Aggregation::Median, Bounds::Bootstrap);
+        let m = &metrics[0];
+        let (lower, upper) = (m.lower_value.unwrap(), m.upper_value.unwrap());
+        assert_eq!(m.value, 10.2);
+        assert!(lower >= 9.0 && lower <= m.value, "lower = {}", lower);
+        assert!(upper >= m.value && upper < 30.0, "upper = {}", upper);
+
+        // Deterministic
+        let again = aggregate(
+            values
+                .into_iter()
+                .map(|v| vec![metric("a", "latency", v)])
+                .collect(),
+            Aggregation::Median,
+            Bounds::Bootstrap,
+        );
+        assert_eq!(again[0].lower_value, m.lower_value);
+        assert_eq!(again[0].upper_value, m.upper_value);
+    }
+}
@@ -1,18 +1,34 @@
 use anyhow::{Context, Result};
 use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
 use std::fs;
 use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+use crate::fingerprint::Fingerprint;
 
 pub const DEFAULT_API_URL: &str = "https://driftwatch.dev";
+/// Server `DRIFTWATCH_TOKEN` is used with when neither `--api-url` nor
+/// `DRIFTWATCH_API_URL` names one: a local `driftwatch serve`
+pub const LOCAL_API_URL: &str = "http://localhost:4000";
 pub const DEFAULT_GRPC_URL: &str = "http://localhost:50051";
 
-#[derive(Debug, Serialize, Deserialize)]
+pub const DEFAULT_PROFILE: &str = "default";
+
+/// How long a request may take before it counts as failed and is retried.
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(60);
+
+/// Credentials and endpoints for one driftwatch server.
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Config {
     pub token: String,
     #[serde(default = "default_api_url")]
     pub api_url: String,
     #[serde(default = "default_grpc_url")]
     pub grpc_url: String,
+    /// Email of the account the token belongs to, recorded at login
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub user: Option<String>,
 }
 
 fn default_api_url() -> String {
@@ -24,29 +40,47 @@ fn default_grpc_url() -> String {
 }
 
 impl Config {
-    pub fn load() -> Result<Self> {
-        let api_url =
-            std::env::var("DRIFTWATCH_API_URL").unwrap_or_else(|_| DEFAULT_API_URL.to_string());
-        let grpc_url =
-            std::env::var("DRIFTWATCH_GRPC_URL").unwrap_or_else(|_| DEFAULT_GRPC_URL.to_string());
+    /// Loads the profile named by `profile` (from `--profile` or
+    /// `DRIFTWATCH_PROFILE`), falling back to the active one. Without a named
+    /// profile, `DRIFTWATCH_TOKEN` is used instead of the config file.
+    pub fn load(profile: Option<&str>) -> Result<Self> {
+        Self::resolve(profile, |name| std::env::var(name).ok(), ConfigFile::load)
+    }
 
-        if let Ok(token) = std::env::var("DRIFTWATCH_TOKEN") {
+    fn resolve(
+        profile: Option<&str>,
+        var: impl Fn(&str) -> Option<String>,
+        load_file: impl FnOnce() -> Result<ConfigFile>,
+    ) -> Result<Self> {
+        let api_url = var("DRIFTWATCH_API_URL");
+        let grpc_url = var("DRIFTWATCH_GRPC_URL");
+
+        if let (None, Some(token)) = (profile, var("DRIFTWATCH_TOKEN")) {
             return Ok(Config {
                 token,
-                api_url,
-                grpc_url,
+                api_url: api_url.unwrap_or_else(|| LOCAL_API_URL.to_string()),
+                grpc_url: grpc_url.unwrap_or_else(default_grpc_url),
+                user: None,
             });
         }
 
-        let config_path = get_config_path()?;
-        let config_str = fs::read_to_string(&config_path)
-            .context("Not authenticated. Run 'driftwatch auth login' first.")?;
-        let mut config: Config = toml::from_str(&config_str).context("Invalid config file")?;
+        let file = load_file()?;
+        let name = file.profile_name(profile);
+        let mut config = file.profiles.get(name).cloned().with_context(|| {
+            if file.profiles.is_empty() {
+                "Not authenticated. Run 'driftwatch auth login' first.".to_string()
+            } else {
+                format!(
+                    "No profile '{}'. Run 'driftwatch auth login --profile {}' first.",
+                    name, name
+                )
+            }
+        })?;
 
-        if std::env::var("DRIFTWATCH_API_URL").is_ok() {
+        if let Some(api_url) = api_url {
             config.api_url = api_url;
         }
-        if std::env::var("DRIFTWATCH_GRPC_URL").is_ok() {
+        if let Some(grpc_url) = grpc_url {
             config.grpc_url = grpc_url;
         }
 
@@ -54,6 +88,62 @@ impl Config {
     }
 }
 
+/// The user-level config file, holding one `Config` per named profile.
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct ConfigFile {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub active_profile: Option<String>,
+    #[serde(default)]
+    pub profiles: BTreeMap<String, Config>,
+}
+
+impl ConfigFile {
+    pub fn path() -> Result<PathBuf> {
+        get_config_path()
+    }
+
+    /// Reads the config file, or an empty one if it does not exist yet.
+    pub fn load() -> Result<Self> {
+        let config_path = get_config_path()?;
+        if !config_path.exists() {
+            return Ok(Self::default());
+        }
+        let config_str = fs::read_to_string(&config_path).context("Failed to read config file")?;
+        Self::parse(&config_str)
+    }
+
+    fn parse(config_str: &str) -> Result<Self> {
+        let value: toml::Table = toml::from_str(config_str).context("Invalid config file")?;
+        // Files written before profiles existed hold a single flat config
+        if value.contains_key("token") {
+            let config: Config = toml::from_str(config_str).context("Invalid config file")?;
+            return Ok(Self {
+                active_profile: None,
+                profiles: BTreeMap::from([(DEFAULT_PROFILE.to_string(), config)]),
+            });
+        }
+        toml::from_str(config_str).context("Invalid config file")
+    }
+
+    pub fn save(&self) -> Result<PathBuf> {
+        let config_path = get_config_path()?;
+        if let Some(parent) = config_path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        fs::write(&config_path, toml::to_string_pretty(self)?)?;
+        Ok(config_path)
+    }
+
+    pub fn active_profile(&self) -> &str {
+        self.active_profile.as_deref().unwrap_or(DEFAULT_PROFILE)
+    }
+
+    /// The explicitly requested profile, or the active one.
+    pub fn profile_name<'a>(&'a self, profile: Option<&'a str>) -> &'a str {
+        profile.unwrap_or_else(|| self.active_profile())
+    }
+}
+
 fn get_config_path() -> Result<PathBuf> {
     let config_dir = dirs::config_dir()
         .context("Could not determine config directory")?
@@ -61,6 +151,44 @@ fn get_config_path() -> Result<PathBuf> {
     Ok(config_dir.join("config.toml"))
 }
 
+/// Fields of a report returned by the report mutations.
+const REPORT_FIELDS: &str = r#"
+    id
+    gitHash
+    finalized
+    alerts {
+        id
+        baselineValue
+        currentValue
+        percentChange
+        metric {
+            value
+            benchmark { id name }
+            measure { id name units higherIsBetter }
+        }
+    }
+    warnings
+    metrics {
+        value
+        lower
+        upper
+        baselineValue
+        benchmark { id name }
+        measure { id name units higherIsBetter }
+    }
+"#;
+
+/// Fields of a threshold returned by the threshold queries and mutations.
+const THRESHOLD_FIELDS: &str = r#"
+    id
+    upperBoundary
+    lowerBoundary
+    minSampleSize
+    measure { id name units higherIsBetter }
+    branch { id name }
+    testbed { id name }
+"#;
+
 pub struct ApiClient {
     client: reqwest::Client,
     base_url: String,
@@ -70,7 +198,10 @@ pub struct ApiClient {
 impl ApiClient {
     pub fn new(base_url: &str, token: &str) -> Self {
         Self {
-            client: reqwest::Client::new(),
+            client: reqwest::Client::builder()
+                .timeout(REQUEST_TIMEOUT)
+                .build()
+                .expect("HTTP client configuration is valid"),
             base_url: base_url.trim_end_matches('/').to_string(),
             token: token.to_string(),
         }
@@ -95,6 +226,12 @@ impl ApiClient {
             .context("Failed to send request")?;
 
         let status = response.status();
+        if status.is_server_error()
+            || status == reqwest::StatusCode::REQUEST_TIMEOUT
+            || status == reqwest::StatusCode::TOO_MANY_REQUESTS
+        {
+            return Err(HttpStatusError(status).into());
+        }
         let body: GraphQLResponse<T> = response.json().await.context("Failed to parse response")?;
 
         if let Some(errors) = body.errors {
@@ -141,7 +278,7 @@ impl ApiClient {
                     branches { id name }
                     testbeds { id name }
                     benchmarks { id name }
-                    measures { id name units }
+                    measures { id name units higherIsBetter }
                 }
             }
         "#;
@@ -198,53 +335,259 @@ impl ApiClient {
         Ok(response.create_project)
     }
 
-    pub async fn create_report(
+    pub async fn export_project(&self, slug: &str) -> Result<serde_json::Value> {
+        let query = r#"
+            query ExportProject($slug: String!) {
+                exportProject(slug: $slug)
+            }
+        "#;
+
+        #[derive(Deserialize)]
+        struct Response {
+            #[serde(rename = "exportProject")]
+            export_project: serde_json::Value,
+        }
+
+        let response: Response = self
+            .graphql(query, serde_json::json!({ "slug": slug }))
+            .await?;
+        Ok(response.export_project)
+    }
+
+    pub async fn import_project(
         &self,
-        project_slug: &str,
-        branch: &str,
-        testbed: &str,
-        git_hash: Option<&str>,
-        pr_number: Option<i32>,
-        metrics: Vec<MetricInput>,
-    ) -> Result<Report> {
+        slug: &str,
+        name: Option<&str>,
+        archive: serde_json::Value,
+    ) -> Result<Project> {
         let query = r#"
-            mutation CreateReport($input: CreateReportInput!) {
-                createReport(input: $input) {
+            mutation ImportProject($slug: String!, $name: String, $archive: JSON!) {
+                importProject(slug: $slug, name: $name, archive: $archive) {
                     id
-                    gitHash
-                    alerts {
-                        id
-                        baselineValue
-                        percentChange
-                    }
+                    slug
+                    name
+                    description
+                    public
                 }
             }
         "#;
 
         #[derive(Deserialize)]
         struct Response {
-            #[serde(rename = "createReport")]
-            create_report: Report,
+            #[serde(rename = "importProject")]
+            import_project: Project,
         }
 
         let response: Response = self
             .graphql(
                 query,
                 serde_json::json!({
-                    "input": {
-                        "projectSlug": project_slug,
-                        "branch": branch,
-                        "testbed": testbed,
-                        "gitHash": git_hash,
-                        "prNumber": pr_number,
-                        "metrics": metrics
-                    }
+                    "slug": slug,
+                    "name": name,
+                    "archive": archive
                 }),
             )
             .await?;
+        Ok(response.import_project)
+    }
+
+    /// Submits a report. Resubmitting it with the same idempotency key returns
+    /// the report stored the first time.
+    pub async fn create_report(&self, input: &CreateReportInput) -> Result<Report> {
+        let query = format!(
+            r#"
+            mutation CreateReport($input: CreateReportInput!) {{
+                createReport(input: $input) {{ {} }}
+            }}
+        "#,
+            REPORT_FIELDS
+        );
+
+        #[derive(Deserialize)]
+        struct Response {
+            #[serde(rename = "createReport")]
+            create_report: Report,
+        }
+
+        let response: Response = self
+            .graphql(&query, serde_json::json!({ "input": input }))
+            .await?;
         Ok(response.create_report)
     }
 
+    /// Opens a report for `append_metrics`; opening it again with the same
+    /// idempotency key returns the same report.
+    pub async fn open_report(&self, input: &OpenReportInput) -> Result<Report> {
+        let query = format!(
+            r#"
+            mutation OpenReport($input: OpenReportInput!) {{
+                openReport(input: $input) {{ {} }}
+            }}
+        "#,
+            REPORT_FIELDS
+        );
+
+        #[derive(Deserialize)]
+        struct Response {
+            #[serde(rename = "openReport")]
+            open_report: Report,
+        }
+
+        let response: Response = self
+            .graphql(&query, serde_json::json!({ "input": input }))
+            .await?;
+        Ok(response.open_report)
+    }
+
+    pub async fn append_metrics(&self, report_id: &str, metrics: &[MetricInput]) -> Result<Report> {
+        let query = format!(
+            r#"
+            mutation AppendMetrics($reportId: ID!, $metrics: [MetricInput!]!) {{
+                appendMetrics(reportId: $reportId, metrics: $metrics) {{ {} }}
+            }}
+        "#,
+            REPORT_FIELDS
+        );
+
+        #[derive(Deserialize)]
+        struct Response {
+            #[serde(rename = "appendMetrics")]
+            append_metrics: Report,
+        }
+
+        let response: Response = self
+            .graphql(
+                &query,
+                serde_json::json!({ "reportId": report_id, "metrics": metrics }),
+            )
+            .await?;
+        Ok(response.append_metrics)
+    }
+
+    /// Completes an open report; its alerts are raised now.
+    pub async fn finalize_report(&self, report_id: &str) -> Result<Report> {
+        let query = format!(
+            r#"
+            mutation FinalizeReport($reportId: ID!) {{
+                finalizeReport(reportId: $reportId) {{ {} }}
+            }}
+        "#,
+            REPORT_FIELDS
+        );
+
+        #[derive(Deserialize)]
+        struct Response {
+            #[serde(rename = "finalizeReport")]
+            finalize_report: Report,
+        }
+
+        let response: Response = self
+            .graphql(&query, serde_json::json!({ "reportId": report_id }))
+            .await?;
+        Ok(response.finalize_report)
+    }
+
+    pub async fn list_thresholds(&self, project_slug: &str) -> Result<Vec<Threshold>> {
+        let query = format!(
+            r#"
+            query ListThresholds($slug: String!) {{
+                project(slug: $slug) {{
+                    thresholds {{ {} }}
+                }}
+            }}
+        "#,
+            THRESHOLD_FIELDS
+        );
+
+        #[derive(Deserialize)]
+        struct Response {
+            project: Option<ProjectThresholds>,
+        }
+
+        #[derive(Deserialize)]
+        struct ProjectThresholds {
+            thresholds: Vec<Threshold>,
+        }
+
+        let response: Response = self
+            .graphql(&query, serde_json::json!({ "slug": project_slug }))
+            .await?;
+        let project = response
+            .project
+            .with_context(|| format!("Project not found: {}", project_slug))?;
+        Ok(project.thresholds)
+    }
+
+    pub async fn create_threshold(&self, input: &CreateThresholdInput) -> Result<Threshold> {
+        let query = format!(
+            r#"
+            mutation CreateThreshold($input: CreateThresholdInput!) {{
+                createThreshold(input: $input) {{ {} }}
+            }}
+        "#,
+            THRESHOLD_FIELDS
+        );
+
+        #[derive(Deserialize)]
+        struct Response {
+            #[serde(rename = "createThreshold")]
+            create_threshold: Threshold,
+        }
+
+        let response: Response = self
+            .graphql(&query, serde_json::json!({ "input": input }))
+            .await?;
+        Ok(response.create_threshold)
+    }
+
+    pub async fn update_threshold(
+        &self,
+        id: &str,
+        input: &UpdateThresholdInput,
+    ) -> Result<Threshold> {
+        let query = format!(
+            r#"
+            mutation UpdateThreshold($id: ID!, $input: UpdateThresholdInput!) {{
+                updateThreshold(id: $id, input: $input) {{ {} }}
+            }}
+        "#,
+            THRESHOLD_FIELDS
+        );
+
+        #[derive(Deserialize)]
+        struct Response {
+            #[serde(rename = "updateThreshold")]
+            update_threshold: Threshold,
+        }
+
+        let response: Response = self
+            .graphql(&query, serde_json::json!({ "id": id, "input": input }))
+            .await?;
+        Ok(response.update_threshold)
+    }
+
+    pub async fn delete_threshold(&self, id: &str) -> Result<()> {
+        let query = r#"
+            mutation DeleteThreshold($id: ID!) {
+                deleteThreshold(id: $id)
+            }
+        "#;
+
+        #[derive(Deserialize)]
+        struct Response {
+            #[serde(rename = "deleteThreshold")]
+            delete_threshold: bool,
+        }
+
+        let response: Response = self.graphql(query, serde_json::json!({ "id": id })).await?;
+        anyhow::ensure!(
+            response.delete_threshold,
+            "Threshold {} was not deleted",
+            id
+        );
+        Ok(())
+    }
+
     pub async fn get_flamegraph_upload_url(
         &self,
         project_slug: &str,
@@ -356,6 +699,11 @@ impl ApiClient {
     }
 }
 
+/// The server answered with an HTTP error instead of a GraphQL response.
+#[derive(Debug, thiserror::Error)]
+#[error("Server responded with {0}")]
+pub struct HttpStatusError(pub reqwest::StatusCode);
+
 #[derive(Debug, Deserialize)]
 struct GraphQLResponse<T> {
     data: Option<T>,
@@ -418,12 +766,69 @@ pub struct Measure {
     pub id: String,
     pub name: String,
     pub units: Option<String>,
+    #[serde(rename = "higherIsBetter", default)]
+    pub higher_is_better: bool,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct CreateReportInput {
+    #[serde(rename = "projectSlug")]
+    pub project_slug: String,
+    pub branch: String,
+    pub testbed: String,
+    #[serde(rename = "gitHash")]
+    pub git_hash: Option<String>,
+    #[serde(rename = "prNumber")]
+    pub pr_number: Option<i32>,
+    #[serde(rename = "baseBranch")]
+    pub base_branch: Option<String>,
+    #[serde(rename = "ciProvider")]
+    pub ci_provider: Option<String>,
+    #[serde(rename = "ciRunUrl")]
+    pub ci_run_url: Option<String>,
+    /// The same for every attempt to submit the report, including from the
+    /// spool, so the server stores it once
+    #[serde(rename = "idempotencyKey")]
+    pub idempotency_key: String,
+    #[serde(rename = "testbedFingerprint")]
+    pub testbed_fingerprint: Option<Fingerprint>,
+    pub metrics: Vec<MetricInput>,
 }
 
 #[derive(Debug, Serialize)]
+pub struct OpenReportInput {
+    #[serde(rename = "projectSlug")]
+    pub project_slug: String,
+    pub branch: String,
+    pub testbed: String,
+    #[serde(rename = "gitHash")]
+    pub git_hash: Option<String>,
+    #[serde(rename = "prNumber")]
+    pub pr_number: Option<i32>,
+    #[serde(rename = "baseBranch")]
+    pub base_branch: Option<String>,
+    #[serde(rename = "ciProvider")]
+    pub ci_provider: Option<String>,
+    #[serde(rename = "ciRunUrl")]
+    pub ci_run_url: Option<String>,
+    #[serde(rename = "idempotencyKey")]
+    pub idempotency_key: Option<String>,
+    #[serde(rename = "testbedFingerprint")]
+    pub testbed_fingerprint: Option<Fingerprint>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
 pub struct MetricInput {
     pub benchmark: String,
     pub measure: String,
+    pub units: Option<String>,
+    /// Only used by the server when this metric creates the measure
+    #[serde(
+        rename = "higherIsBetter",
+        default,
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub higher_is_better: Option<bool>,
     pub value: f64,
     #[serde(rename = "lowerValue")]
     pub lower_value: Option<f64>,
@@ -437,7 +842,13 @@ pub struct Report {
     pub id: String,
     #[serde(rename = "gitHash")]
     pub git_hash: Option<String>,
+    pub finalized: bool,
     pub alerts: Vec<Alert>,
+    #[serde(default)]
+    pub metrics: Vec<Metric>,
+    /// e.g. that the testbed's hardware changed since its previous report
+    #[serde(default)]
+    pub warnings: Vec<String>,
 }
 
 #[allow(dead_code)]
@@ -446,8 +857,71 @@ pub struct Alert {
     pub id: String,
     #[serde(rename = "baselineValue")]
     pub baseline_value: f64,
+    #[serde(rename = "currentValue")]
+    pub current_value: f64,
     #[serde(rename = "percentChange")]
     pub percent_change: f64,
+    pub metric: Metric,
+}
+
+#[allow(dead_code)]
+#[derive(Debug, Deserialize)]
+pub struct Metric {
+    pub value: f64,
+    #[serde(default)]
+    pub lower: Option<f64>,
+    #[serde(default)]
+    pub upper: Option<f64>,
+    #[serde(rename = "baselineValue", default)]
+    pub baseline_value: Option<f64>,
+    pub benchmark: Benchmark,
+    pub measure: Measure,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct Threshold {
+    pub id: String,
+    pub measure: Measure,
+    /// `None` when the threshold applies to every branch
+    pub branch: Option<Branch>,
+    /// `None` when the threshold applies to every testbed
+    pub testbed: Option<Testbed>,
+    /// Allowed increase over the baseline in percent
+    #[serde(rename = "upperBoundary")]
+    pub upper_boundary: Option<f64>,
+    /// Allowed decrease below the baseline in percent
+    #[serde(rename = "lowerBoundary")]
+    pub lower_boundary: Option<f64>,
+    #[serde(rename = "minSampleSize")]
+    pub min_sample_size: i32,
+}
+
+/// Measure, branch and testbed are resolved by name on the server.
+#[derive(Debug, Serialize)]
+pub struct CreateThresholdInput {
+    #[serde(rename = "projectSlug")]
+    pub project_slug: String,
+    pub measure: String,
+    pub branch: Option<String>,
+    pub testbed: Option<String>,
+    #[serde(rename = "upperBoundary")]
+    pub upper_boundary: Option<f64>,
+    #[serde(rename = "lowerBoundary")]
+    pub lower_boundary: Option<f64>,
+    #[serde(rename = "minSampleSize")]
+    pub min_sample_size: Option<i32>,
+}
+
+/// Fields that are `None` are left unchanged; `Some(None)` removes a
+/// boundary.
+#[derive(Debug, Default, Serialize)]
+pub struct UpdateThresholdInput {
+    #[serde(rename = "upperBoundary", skip_serializing_if = "Option::is_none")]
+    pub upper_boundary: Option<Option<f64>>,
+    #[serde(rename = "lowerBoundary", skip_serializing_if = "Option::is_none")]
+    pub lower_boundary: Option<Option<f64>>,
+    #[serde(rename = "minSampleSize", skip_serializing_if = "Option::is_none")]
+    pub min_sample_size: Option<i32>,
 }
 
 #[allow(dead_code)]
@@ -471,3 +945,78 @@ pub struct Flamegraph {
     #[serde(rename = "fileSize")]
     pub file_size: i64,
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_legacy_config_file() {
+        let file = ConfigFile::parse(
+            r#"
+token = "abc123"
+api_url = "https://staging.example.com"
+"#,
+        )
+        .unwrap();
+
+        assert_eq!(file.active_profile(), DEFAULT_PROFILE);
+        let config = &file.profiles[DEFAULT_PROFILE];
+        assert_eq!(config.token, "abc123");
+        assert_eq!(config.api_url, "https://staging.example.com");
+        assert_eq!(config.grpc_url, DEFAULT_GRPC_URL);
+    }
+
+    #[test]
+    fn test_parse_profiles_config_file() {
+        let file = ConfigFile::parse(
+            r#"
+active_profile = "prod"
+
+[profiles.staging]
+token = "stage"
+api_url = "https://staging.example.com"
+
+[profiles.prod]
+token = "prod"
+user = "ci@example.com"
+"#,
+        )
+        .unwrap();
+
+        assert_eq!(file.active_profile(), "prod");
+        assert_eq!(file.profile_name(None), "prod");
+        assert_eq!(file.profile_name(Some("staging")), "staging");
+        assert_eq!(file.profiles["prod"].api_url, DEFAULT_API_URL);
+        assert_eq!(
+            file.profiles["prod"].user.as_deref(),
+            Some("ci@example.com")
+        );
+
+        let round_trip = ConfigFile::parse(&toml::to_string_pretty(&file).unwrap()).unwrap();
+        assert_eq!(round_trip.profiles.len(), 2);
+        assert_eq!(round_trip.active_profile(), "prod");
+    }
+
+    #[test]
+    fn test_named_profile_wins_over_token_env() {
+        let file = || {
+            ConfigFile::parse(
+                r#"
+[profiles.staging]
+token = "stage"
+api_url = "https://staging.example.com"
+"#,
+            )
+        };
+        let var = |name: &str| (name == "DRIFTWATCH_TOKEN").then(|| "from-env".to_string());
+
+        let config = Config::resolve(Some("staging"), var, file).unwrap();
+        assert_eq!(config.token, "stage");
+        assert_eq!(config.api_url, "https://staging.example.com");
+
+        let config = Config::resolve(None, var, file).unwrap();
+        assert_eq!(config.token, "from-env");
+        assert_eq!(config.api_url, LOCAL_API_URL);
+    }
+}
@@ -0,0 +1,546 @@
+/// What a CI system tells us about the build we are running in.
+#[derive(Debug, Clone, PartialEq)]
+pub struct CiEnv {
+    /// e.g. `github-actions`, `gitlab`
+    pub provider: &'static str,
+    /// Source branch of a pull request, the pushed branch otherwise
+    pub branch: Option<String>,
+    pub head_sha: Option<String>,
+    pub pr_number: Option<i32>,
+    /// Branch the pull request targets
+    pub base_branch: Option<String>,
+    pub run_url: Option<String>,
+    /// Identifies the job and its attempt; re-running the job changes it
+    pub job_id: Option<String>,
+}
+
+impl CiEnv {
+    /// Detects the CI system from the process environment.
+    pub fn detect() -> Option<Self> {
+        Self::from_env(|name| std::env::var(name).ok())
+    }
+
+    /// Detects the CI system using `var` to look up environment variables;
+    /// empty values are treated as unset.
+    pub fn from_env(var: impl Fn(&str) -> Option<String>) -> Option<Self> {
+        let var = |name: &str| var(name).filter(|v| !v.trim().is_empty());
+
+        // A bare GITHUB_REF is accepted for scripts that only export that
+        if var("GITHUB_ACTIONS").is_some() || var("GITHUB_REF").is_some() {
+            Some(github_actions(&var))
+        } else if var("GITLAB_CI").is_some() {
+            Some(gitlab(&var))
+        } else if var("BUILDKITE").is_some() {
+            Some(buildkite(&var))
+        } else if var("CIRCLECI").is_some() {
+            Some(circleci(&var))
+        } else if var("TF_BUILD").is_some() {
+            Some(azure_pipelines(&var))
+        } else if var("JENKINS_URL").is_some() {
+            Some(jenkins(&var))
+        } else {
+            None
+        }
+    }
+
+    /// Key that makes every submission of `submission` from this job for
+    /// `testbed` return the same report, so retries and a flushed spool don't
+    /// add another one. `submission` tells apart the reports of one job, such
+    /// as those of matrix legs, which share the job's id.
+    pub fn idempotency_key(&self, testbed: &str, submission: &str) -> Option<String> {
+        let job_id = self.job_id.as_ref()?;
+        Some(format!(
+            "{}:{}:{}:{}",
+            self.provider, job_id, testbed, submission
+        ))
+    }
+}
+
+/// Commit checked out in the current directory, outside of CI.
+pub fn git_head() -> Option<String> {
+    std::process::Command::new("git")
+        .args(["rev-parse", "HEAD"])
+        .output()
+        .ok()
+        .filter(|o| o.status.success())
+        .and_then(|o| String::from_utf8(o.stdout).ok())
+        .map(|s| s.trim().to_string())
+}
+
+fn github_actions(var: &impl Fn(&str) -> Option<String>) -> CiEnv {
+    let run_url = match (
+        var("GITHUB_SERVER_URL"),
+        var("GITHUB_REPOSITORY"),
+        var("GITHUB_RUN_ID"),
+    ) {
+        (Some(server), Some(repo), Some(run)) => {
+            Some(format!("{}/{}/actions/runs/{}", server, repo, run))
+        }
+        _ => None,
+    };
+
+    CiEnv {
+        provider: "github-actions",
+        // GITHUB_REF_NAME is `123/merge` on pull_request events
+        branch: var("GITHUB_HEAD_REF").or_else(|| var("GITHUB_REF_NAME")),
+        head_sha: github_pull_request_head(var).or_else(|| var("GITHUB_SHA")),
+        pr_number: var("GITHUB_REF")
+            .and_then(|r| parse_pr_from_github_ref(&r))
+            .or_else(|| var("GITHUB_PR_NUMBER").and_then(|n| parse_pr_number(&n))),
+        base_branch: var("GITHUB_BASE_REF"),
+        run_url,
+        // GITHUB_JOB is the same for every job of a matrix; the testbed in
+        // the idempotency key tells those apart
+        job_id: joined_ids(
+            var("GITHUB_RUN_ID"),
+            [var("GITHUB_RUN_ATTEMPT"), var("GITHUB_JOB")],
+        ),
+    }
+}
+
+/// Head commit of the pull request a `pull_request` or `pull_request_target`
+/// event is for. GITHUB_SHA is then the merge commit GitHub made for the run,
+/// which nobody pushed.
+fn github_pull_request_head(var: &impl Fn(&str) -> Option<String>) -> Option<String> {
+    let event = var("GITHUB_EVENT_NAME")?;
+    if event != "pull_request" && event != "pull_request_target" {
+        return None;
+    }
+    let payload = std::fs::read_to_string(var("GITHUB_EVENT_PATH")?).ok()?;
+    let payload: serde_json::Value = serde_json::from_str(&payload).ok()?;
+    payload["pull_request"]["head"]["sha"]
+        .as_str()
+        .map(str::to_string)
+}
+
+fn gitlab(var: &impl Fn(&str) -> Option<String>) -> CiEnv {
+    CiEnv {
+        provider: "gitlab",
+        branch: var("CI_MERGE_REQUEST_SOURCE_BRANCH_NAME")
+            .or_else(|| var("CI_COMMIT_BRANCH"))
+            .or_else(|| var("CI_COMMIT_REF_NAME")),
+        head_sha: var("CI_COMMIT_SHA"),
+        pr_number: var("CI_MERGE_REQUEST_IID").and_then(|n| parse_pr_number(&n)),
+        base_branch: var("CI_MERGE_REQUEST_TARGET_BRANCH_NAME"),
+        run_url: var("CI_PIPELINE_URL").or_else(|| var("CI_JOB_URL")),
+        // A retried job gets a new id
+        job_id: var("CI_JOB_ID"),
+    }
+}
+
+fn buildkite(var: &impl Fn(&str) -> Option<String>) -> CiEnv {
+    // BUILDKITE_PULL_REQUEST is `false` outside pull request builds
+    let pr_number = var("BUILDKITE_PULL_REQUEST").and_then(|n| parse_pr_number(&n));
+    CiEnv {
+        provider: "buildkite",
+        branch: var("BUILDKITE_BRANCH"),
+        head_sha: var("BUILDKITE_COMMIT").filter(|sha| sha != "HEAD"),
+        pr_number,
+        base_branch: pr_number.and(var("BUILDKITE_PULL_REQUEST_BASE_BRANCH")),
+        run_url: var("BUILDKITE_BUILD_URL"),
+        job_id: joined_ids(var("BUILDKITE_JOB_ID"), [var("BUILDKITE_RETRY_COUNT")]),
+    }
+}
+
+fn circleci(var: &impl Fn(&str) -> Option<String>) -> CiEnv {
+    CiEnv {
+        provider: "circleci",
+        branch: var("CIRCLE_BRANCH"),
+        head_sha: var("CIRCLE_SHA1"),
+        // CircleCI only exposes the pull request URL, e.g. `.../pull/123`
+        pr_number: var("CIRCLE_PR_NUMBER")
+            .or_else(|| var("CIRCLE_PULL_REQUEST").and_then(|url| last_segment(&url)))
+            .and_then(|n| parse_pr_number(&n)),
+        base_branch: None,
+        run_url: var("CIRCLE_BUILD_URL"),
+        job_id: var("CIRCLE_WORKFLOW_JOB_ID").or_else(|| var("CIRCLE_BUILD_NUM")),
+    }
+}
+
+fn jenkins(var: &impl Fn(&str) -> Option<String>) -> CiEnv {
+    CiEnv {
+        provider: "jenkins",
+        // CHANGE_* are set by multibranch pipelines for pull requests
+        branch: var("CHANGE_BRANCH")
+            .or_else(|| var("BRANCH_NAME"))
+            .or_else(|| var("GIT_BRANCH").map(|b| strip_remote(&b))),
+        head_sha: var("GIT_COMMIT"),
+        pr_number: var("CHANGE_ID").and_then(|n| parse_pr_number(&n)),
+        base_branch: var("CHANGE_TARGET"),
+        run_url: var("BUILD_URL"),
+        // `jenkins-<job>-<build number>`
+        job_id: var("BUILD_TAG"),
+    }
+}
+
+fn azure_pipelines(var: &impl Fn(&str) -> Option<String>) -> CiEnv {
+    let run_url = match (
+        var("SYSTEM_COLLECTIONURI"),
+        var("SYSTEM_TEAMPROJECT"),
+        var("BUILD_BUILDID"),
+    ) {
+        (Some(collection), Some(project), Some(build)) => Some(format!(
+            "{}/{}/_build/results?buildId={}",
+            collection.trim_end_matches('/'),
+            project,
+            build
+        )),
+        _ => None,
+    };
+
+    CiEnv {
+        provider: "azure-pipelines",
+        branch: var("SYSTEM_PULLREQUEST_SOURCEBRANCH")
+            .or_else(|| var("BUILD_SOURCEBRANCH"))
+            .map(|b| strip_heads(&b)),
+        head_sha: var("SYSTEM_PULLREQUEST_SOURCECOMMITID").or_else(|| var("BUILD_SOURCEVERSION")),
+        // The number is only set for GitHub-hosted repositories; Azure Repos
+        // use the pull request id
+        pr_number: var("SYSTEM_PULLREQUEST_PULLREQUESTNUMBER")
+            .or_else(|| var("SYSTEM_PULLREQUEST_PULLREQUESTID"))
+            .and_then(|n| parse_pr_number(&n)),
+        base_branch: var("SYSTEM_PULLREQUEST_TARGETBRANCH").map(|b| strip_heads(&b)),
+        run_url,
+        job_id: joined_ids(
+            var("BUILD_BUILDID"),
+            [var("SYSTEM_JOBID"), var("SYSTEM_JOBATTEMPT")],
+        ),
+    }
+}
+
+/// `id` followed by whichever of `qualifiers` are set, joined with `-`.
+fn joined_ids<const N: usize>(
+    id: Option<String>,
+    qualifiers: [Option<String>; N],
+) -> Option<String> {
+    let mut id = id?;
+    for qualifier in qualifiers.into_iter().flatten() {
+        id.push('-');
+        id.push_str(&qualifier);
+    }
+    Some(id)
+}
+
+/// Parse PR number from GITHUB_REF environment variable format
+/// e.g., "refs/pull/123/merge" -> Some(123)
+pub fn parse_pr_from_github_ref(github_ref: &str) -> Option<i32> {
+    github_ref
+        .strip_prefix("refs/pull/")
+        .and_then(|s| s.split('/').next())
+        .and_then(parse_pr_number)
+}
+
+fn parse_pr_number(number: &str) -> Option<i32> {
+    number.trim().parse::<i32>().ok().filter(|&n| n > 0)
+}
+
+fn last_segment(url: &str) -> Option<String> {
+    url.trim_end_matches('/')
+        .rsplit('/')
+        .next()
+        .map(str::to_string)
+}
+
+/// `refs/heads/feature/x` -> `feature/x`
+fn strip_heads(branch: &str) -> String {
+    branch
+        .strip_prefix("refs/heads/")
+        .unwrap_or(branch)
+        .to_string()
+}
+
+/// `origin/feature/x` -> `feature/x`
+fn strip_remote(branch: &str) -> String {
+    branch.strip_prefix("origin/").unwrap_or(branch).to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashMap;
+
+    fn detect(vars: &[(&str, &str)]) -> Option<CiEnv> {
+        let vars: HashMap<_, _> = vars.iter().copied().collect();
+        CiEnv::from_env(|name| vars.get(name).map(|v| v.to_string()))
+    }
+
+    #[test]
+    fn test_parse_pr_from_github_ref_valid() {
+        assert_eq!(parse_pr_from_github_ref("refs/pull/123/merge"), Some(123));
+        assert_eq!(parse_pr_from_github_ref("refs/pull/1/merge"), Some(1));
+        assert_eq!(
+            parse_pr_from_github_ref("refs/pull/99999/merge"),
+            Some(99999)
+        );
+        assert_eq!(parse_pr_from_github_ref("refs/pull/42/head"), Some(42));
+    }
+
+    #[test]
+    fn test_parse_pr_from_github_ref_invalid() {
+        assert_eq!(parse_pr_from_github_ref("refs/heads/main"), None);
+        assert_eq!(parse_pr_from_github_ref("refs/tags/v1.0.0"), None);
+        assert_eq!(parse_pr_from_github_ref(""), None);
+        assert_eq!(parse_pr_from_github_ref("refs/pull/"), None);
+        assert_eq!(parse_pr_from_github_ref("refs/pull/abc/merge"), None);
+    }
+
+    #[test]
+    fn test_parse_pr_from_github_ref_edge_cases() {
+        // Not starting with refs/pull/
+        assert_eq!(parse_pr_from_github_ref("pull/123/merge"), None);
+        assert_eq!(parse_pr_from_github_ref(" refs/pull/123/merge"), None);
+
+        // Large PR numbers
+        assert_eq!(
+            parse_pr_from_github_ref("refs/pull/2147483647/merge"),
+            Some(2147483647)
+        );
+
+        // Negative numbers (should be rejected)
+        assert_eq!(parse_pr_from_github_ref("refs/pull/-1/merge"), None);
+
+        // Zero (should be rejected - PR numbers start at 1)
+        assert_eq!(parse_pr_from_github_ref("refs/pull/0/merge"), None);
+    }
+
+    #[test]
+    fn test_detect_github_actions() {
+        let ci = detect(&[
+            ("GITHUB_ACTIONS", "true"),
+            ("GITHUB_REF", "refs/pull/42/merge"),
+            ("GITHUB_REF_NAME", "42/merge"),
+            ("GITHUB_HEAD_REF", "feature/fast"),
+            ("GITHUB_BASE_REF", "main"),
+            ("GITHUB_SHA", "abc123"),
+            ("GITHUB_SERVER_URL", "https://github.com"),
+            ("GITHUB_REPOSITORY", "acme/app"),
+            ("GITHUB_RUN_ID", "7"),
+            ("GITHUB_RUN_ATTEMPT", "2"),
+            ("GITHUB_JOB", "bench"),
+        ])
+        .unwrap();
+        assert_eq!(
+            ci,
+            CiEnv {
+                provider: "github-actions",
+                branch: Some("feature/fast".into()),
+                head_sha: Some("abc123".into()),
+                pr_number: Some(42),
+                base_branch: Some("main".into()),
+                run_url: Some("https://github.com/acme/app/actions/runs/7".into()),
+                job_id: Some("7-2-bench".into()),
+            }
+        );
+        assert_eq!(
+            ci.idempotency_key("linux", "9f2c").as_deref(),
+            Some("github-actions:7-2-bench:linux:9f2c")
+        );
+
+        // Pushes leave GITHUB_HEAD_REF empty
+        let ci = detect(&[
+            ("GITHUB_ACTIONS", "true"),
+            ("GITHUB_REF", "refs/heads/main"),
+            ("GITHUB_REF_NAME", "main"),
+            ("GITHUB_HEAD_REF", ""),
+        ])
+        .unwrap();
+        assert_eq!(ci.branch.as_deref(), Some("main"));
+        assert_eq!(ci.pr_number, None);
+        assert_eq!(ci.run_url, None);
+        assert_eq!(ci.job_id, None);
+        assert_eq!(ci.idempotency_key("linux", "9f2c"), None);
+    }
+
+    #[test]
+    fn test_detect_github_actions_pull_request_head() {
+        let dir = tempfile::tempdir().unwrap();
+        let event_path = dir.path().join("event.json");
+        std::fs::write(
+            &event_path,
+            r#"{
+                "action": "synchronize",
+                "number": 42,
+                "pull_request": {
+                    "number": 42,
+                    "head": { "ref": "feature/fast", "sha": "head-sha" },
+                    "base": { "ref": "main", "sha": "base-sha" }
+                }
+            }"#,
+        )
+        .unwrap();
+        let event_path = event_path.to_str().unwrap();
+
+        for event in ["pull_request", "pull_request_target"] {
+            let ci = detect(&[
+                ("GITHUB_ACTIONS", "true"),
+                ("GITHUB_EVENT_NAME", event),
+                ("GITHUB_EVENT_PATH", event_path),
+                ("GITHUB_REF", "refs/pull/42/merge"),
+                ("GITHUB_SHA", "merge-sha"),
+            ])
+            .unwrap();
+            assert_eq!(ci.head_sha.as_deref(), Some("head-sha"));
+        }
+
+        // Other events use GITHUB_SHA, as does a missing event file
+        let ci = detect(&[
+            ("GITHUB_ACTIONS", "true"),
+            ("GITHUB_EVENT_NAME", "push"),
+            ("GITHUB_EVENT_PATH", event_path),
+            ("GITHUB_SHA", "push-sha"),
+        ])
+        .unwrap();
+        assert_eq!(ci.head_sha.as_deref(), Some("push-sha"));
+        let ci = detect(&[
+            ("GITHUB_ACTIONS", "true"),
+            ("GITHUB_EVENT_NAME", "pull_request"),
+            ("GITHUB_EVENT_PATH", "/nonexistent/event.json"),
+            ("GITHUB_SHA", "merge-sha"),
+        ])
+        .unwrap();
+        assert_eq!(ci.head_sha.as_deref(), Some("merge-sha"));
+    }
+
+    #[test]
+    fn test_detect_gitlab() {
+        let ci = detect(&[
+            ("GITLAB_CI", "true"),
+            ("CI_COMMIT_REF_NAME", "feature"),
+            ("CI_MERGE_REQUEST_SOURCE_BRANCH_NAME", "feature"),
+            ("CI_MERGE_REQUEST_TARGET_BRANCH_NAME", "develop"),
+            ("CI_MERGE_REQUEST_IID", "17"),
+            ("CI_COMMIT_SHA", "def456"),
+            (
+                "CI_PIPELINE_URL",
+                "https://gitlab.com/acme/app/-/pipelines/9",
+            ),
+            ("CI_JOB_ID", "31"),
+        ])
+        .unwrap();
+        assert_eq!(ci.provider, "gitlab");
+        assert_eq!(ci.branch.as_deref(), Some("feature"));
+        assert_eq!(ci.pr_number, Some(17));
+        assert_eq!(ci.base_branch.as_deref(), Some("develop"));
+        assert_eq!(
+            ci.run_url.as_deref(),
+            Some("https://gitlab.com/acme/app/-/pipelines/9")
+        );
+        assert_eq!(ci.job_id.as_deref(), Some("31"));
+    }
+
+    #[test]
+    fn test_detect_buildkite() {
+        let ci = detect(&[
+            ("BUILDKITE", "true"),
+            ("BUILDKITE_BRANCH", "main"),
+            ("BUILDKITE_COMMIT", "HEAD"),
+            ("BUILDKITE_PULL_REQUEST", "false"),
+            ("BUILDKITE_PULL_REQUEST_BASE_BRANCH", ""),
+            (
+                "BUILDKITE_BUILD_URL",
+                "https://buildkite.com/acme/app/builds/3",
+            ),
+            ("BUILDKITE_JOB_ID", "0190-ab"),
+            ("BUILDKITE_RETRY_COUNT", "1"),
+        ])
+        .unwrap();
+        assert_eq!(ci.provider, "buildkite");
+        assert_eq!(ci.branch.as_deref(), Some("main"));
+        assert_eq!(ci.head_sha, None);
+        assert_eq!(ci.pr_number, None);
+        assert_eq!(ci.base_branch, None);
+        assert_eq!(ci.job_id.as_deref(), Some("0190-ab-1"));
+
+        let ci = detect(&[
+            ("BUILDKITE", "true"),
+            ("BUILDKITE_PULL_REQUEST", "5"),
+            ("BUILDKITE_PULL_REQUEST_BASE_BRANCH", "main"),
+        ])
+        .unwrap();
+        assert_eq!(ci.pr_number, Some(5));
+        assert_eq!(ci.base_branch.as_deref(), Some("main"));
+    }
+
+    #[test]
+    fn test_detect_circleci() {
+        let ci = detect(&[
+            ("CIRCLECI", "true"),
+            ("CIRCLE_BRANCH", "fix"),
+            ("CIRCLE_SHA1", "0123"),
+            ("CIRCLE_PULL_REQUEST", "https://github.com/acme/app/pull/88"),
+            ("CIRCLE_BUILD_URL", "https://circleci.com/gh/acme/app/12"),
+            ("CIRCLE_BUILD_NUM", "12"),
+        ])
+        .unwrap();
+        assert_eq!(ci.provider, "circleci");
+        assert_eq!(ci.branch.as_deref(), Some("fix"));
+        assert_eq!(ci.pr_number, Some(88));
+        assert_eq!(ci.base_branch, None);
+        assert_eq!(ci.job_id.as_deref(), Some("12"));
+    }
+
+    #[test]
+    fn test_detect_jenkins() {
+        let ci = detect(&[
+            ("JENKINS_URL", "https://ci.example.com/"),
+            ("GIT_BRANCH", "origin/release"),
+            ("GIT_COMMIT", "cafe"),
+            ("BUILD_URL", "https://ci.example.com/job/app/4/"),
+            ("BUILD_TAG", "jenkins-app-4"),
+        ])
+        .unwrap();
+        assert_eq!(ci.provider, "jenkins");
+        assert_eq!(ci.branch.as_deref(), Some("release"));
+        assert_eq!(ci.pr_number, None);
+        assert_eq!(ci.job_id.as_deref(), Some("jenkins-app-4"));
+
+        let ci = detect(&[
+            ("JENKINS_URL", "https://ci.example.com/"),
+            ("BRANCH_NAME", "PR-12"),
+            ("CHANGE_ID", "12"),
+            ("CHANGE_BRANCH", "feature"),
+            ("CHANGE_TARGET", "main"),
+        ])
+        .unwrap();
+        assert_eq!(ci.branch.as_deref(), Some("feature"));
+        assert_eq!(ci.pr_number, Some(12));
+        assert_eq!(ci.base_branch.as_deref(), Some("main"));
+    }
+
+    #[test]
+    fn test_detect_azure_pipelines() {
+        let ci = detect(&[
+            ("TF_BUILD", "True"),
+            ("BUILD_SOURCEBRANCH", "refs/pull/31/merge"),
+            ("BUILD_SOURCEVERSION", "merge-sha"),
+            ("SYSTEM_PULLREQUEST_SOURCEBRANCH", "refs/heads/feature/a"),
+            ("SYSTEM_PULLREQUEST_SOURCECOMMITID", "head-sha"),
+            ("SYSTEM_PULLREQUEST_TARGETBRANCH", "refs/heads/main"),
+            ("SYSTEM_PULLREQUEST_PULLREQUESTID", "1001"),
+            ("SYSTEM_PULLREQUEST_PULLREQUESTNUMBER", "31"),
+            ("SYSTEM_COLLECTIONURI", "https://dev.azure.com/acme/"),
+            ("SYSTEM_TEAMPROJECT", "app"),
+            ("BUILD_BUILDID", "55"),
+            ("SYSTEM_JOBID", "9f2c"),
+            ("SYSTEM_JOBATTEMPT", "1"),
+        ])
+        .unwrap();
+        assert_eq!(
+            ci,
+            CiEnv {
+                provider: "azure-pipelines",
+                branch: Some("feature/a".into()),
+                head_sha: Some("head-sha".into()),
+                pr_number: Some(31),
+                base_branch: Some("main".into()),
+                run_url: Some("https://dev.azure.com/acme/app/_build/results?buildId=55".into()),
+                job_id: Some("55-9f2c-1".into()),
+            }
+        );
+    }
+
+    #[test]
+    fn test_detect_no_ci() {
+        assert_eq!(detect(&[("CI", "true"), ("HOME", "/root")]), None);
+    }
+}
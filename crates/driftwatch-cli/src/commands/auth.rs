@@ -9,13 +9,12 @@ use hyper::service::service_fn;
 use hyper::{Request, Response, StatusCode};
 use hyper_util::rt::TokioIo;
 use std::fs;
-use std::path::PathBuf;
 use std::sync::Arc;
 use tokio::net::TcpListener as TokioTcpListener;
 use tokio::sync::oneshot;
 use tonic::transport::Channel;
 
-use crate::api::{Config, DEFAULT_API_URL, DEFAULT_GRPC_URL};
+use crate::api::{Config, ConfigFile, DEFAULT_API_URL, DEFAULT_GRPC_URL};
 
 #[derive(Subcommand)]
 pub enum AuthCommands {
@@ -33,25 +32,30 @@ pub enum AuthCommands {
         #[arg(long, env = "DRIFTWATCH_GRPC_URL", default_value = DEFAULT_GRPC_URL)]
         grpc_url: String,
     },
-    /// Show authentication status
+    /// Show authentication status of every profile
     Status,
-    /// Remove stored credentials
+    /// Remove stored credentials of the selected profile
     Logout,
 }
 
-pub async fn handle(command: AuthCommands) -> Result<()> {
+pub async fn handle(command: AuthCommands, profile: Option<&str>) -> Result<()> {
     match command {
         AuthCommands::Login {
             token,
             api_url,
             grpc_url,
-        } => login(token, &api_url, &grpc_url).await,
-        AuthCommands::Status => status().await,
-        AuthCommands::Logout => logout().await,
+        } => login(token, &api_url, &grpc_url, profile).await,
+        AuthCommands::Status => status(profile).await,
+        AuthCommands::Logout => logout(profile).await,
     }
 }
 
-async fn login(token: Option<String>, api_url: &str, grpc_url: &str) -> Result<()> {
+async fn login(
+    token: Option<String>,
+    api_url: &str,
+    grpc_url: &str,
+    profile: Option<&str>,
+) -> Result<()> {
     let token = match token {
         Some(t) => {
             println!("Using provided API token...");
@@ -84,22 +88,25 @@ async fn login(token: Option<String>, api_url: &str, grpc_url: &str) -> Result<(
         .user
         .ok_or_else(|| anyhow!("No user returned"))?;
 
-    let config = Config {
-        token,
-        api_url: api_url.to_string(),
-        grpc_url: grpc_url.to_string(),
-    };
-    let config_path = get_config_path()?;
-
-    if let Some(parent) = config_path.parent() {
-        fs::create_dir_all(parent)?;
+    let mut file = ConfigFile::load()?;
+    let name = file.profile_name(profile).to_string();
+    file.profiles.insert(
+        name.clone(),
+        Config {
+            token,
+            api_url: api_url.to_string(),
+            grpc_url: grpc_url.to_string(),
+            user: Some(user.email.clone()),
+        },
+    );
+    // The first profile logged into becomes the active one
+    if file.active_profile.is_none() {
+        file.active_profile = Some(name.clone());
     }
-
-    let config_str = toml::to_string_pretty(&config)?;
-    fs::write(&config_path, config_str)?;
+    let config_path = file.save()?;
 
     println!();
-    println!("Authenticated as: {}", user.email);
+    println!("Authenticated as: {} (profile '{}')", user.email, name);
     println!("Config saved to: {:?}", config_path);
 
     Ok(())
@@ -234,40 +241,70 @@ async fn handle_callback(
         .unwrap())
 }
 
-async fn status() -> Result<()> {
-    match Config::load() {
-        Ok(config) => {
-            println!("Authenticated");
-            println!("API URL: {}", config.api_url);
-            println!("gRPC URL: {}", config.grpc_url);
-            println!("Token: {}...", &config.token[..8.min(config.token.len())]);
+async fn status(profile: Option<&str>) -> Result<()> {
+    if std::env::var("DRIFTWATCH_TOKEN").is_ok() {
+        let config = Config::load(profile)?;
+        println!("Authenticated via DRIFTWATCH_TOKEN");
+        println!("API URL: {}", config.api_url);
+        println!("gRPC URL: {}", config.grpc_url);
+        return Ok(());
+    }
+
+    let file = ConfigFile::load()?;
+    if file.profiles.is_empty() {
+        println!("Not authenticated");
+        println!();
+        println!("Run 'driftwatch auth login' to authenticate via browser");
+        println!("Or 'driftwatch auth login --token <token>' to use an API token");
+        return Ok(());
+    }
+
+    let selected = file.profile_name(profile);
+    for (name, config) in &file.profiles {
+        let marker = if name == selected { "*" } else { " " };
+        println!("{} {}", marker, name);
+        if config.token.is_empty() {
+            println!("    Not logged in");
+        } else {
+            println!(
+                "    User: {}",
+                config.user.as_deref().unwrap_or("(unknown, log in again)")
+            );
         }
-        Err(_) => {
-            println!("Not authenticated");
-            println!();
-            println!("Run 'driftwatch auth login' to authenticate via browser");
-            println!("Or 'driftwatch auth login --token <token>' to use an API token");
+        println!("    API URL: {}", config.api_url);
+        println!("    gRPC URL: {}", config.grpc_url);
+        if !config.token.is_empty() {
+            println!(
+                "    Token: {}...",
+                &config.token[..8.min(config.token.len())]
+            );
         }
     }
+    if !file.profiles.contains_key(selected) {
+        println!();
+        println!("Selected profile '{}' is not logged in", selected);
+    }
     Ok(())
 }
 
-async fn logout() -> Result<()> {
-    let config_path = get_config_path()?;
+async fn logout(profile: Option<&str>) -> Result<()> {
+    let mut file = ConfigFile::load()?;
+    let name = file.profile_name(profile).to_string();
+
+    if file.profiles.remove(&name).is_none() {
+        println!("Not logged in (profile '{}')", name);
+        return Ok(());
+    }
 
-    if config_path.exists() {
-        fs::remove_file(&config_path)?;
-        println!("Logged out successfully");
+    if file.profiles.is_empty() {
+        fs::remove_file(ConfigFile::path()?)?;
     } else {
-        println!("Not logged in");
+        if file.active_profile() == name {
+            file.active_profile = file.profiles.keys().next().cloned();
+        }
+        file.save()?;
     }
+    println!("Logged out of profile '{}'", name);
 
     Ok(())
 }
-
-fn get_config_path() -> Result<PathBuf> {
-    let config_dir = dirs::config_dir()
-        .context("Could not determine config directory")?
-        .join("driftwatch");
-    Ok(config_dir.join("config.toml"))
-}
@@ -1,9 +1,7 @@
-use anyhow::{Context, Result};
+use anyhow::{bail, Result};
 use clap::Subcommand;
-use std::fs;
-use std::path::PathBuf;
 
-use crate::api::Config;
+use crate::api::{Config, ConfigFile, DEFAULT_API_URL, DEFAULT_GRPC_URL};
 
 #[derive(Subcommand)]
 pub enum ConfigCommands {
@@ -19,34 +17,40 @@ pub enum ConfigCommands {
     },
     /// Show current configuration
     Show,
+    /// Make a profile the active one
+    Use {
+        /// Profile name
+        profile: String,
+    },
 }
 
-pub async fn handle(command: ConfigCommands) -> Result<()> {
+pub async fn handle(command: ConfigCommands, profile: Option<&str>) -> Result<()> {
     match command {
-        ConfigCommands::Set { api_url, grpc_url } => set(api_url, grpc_url).await,
-        ConfigCommands::Show => show().await,
+        ConfigCommands::Set { api_url, grpc_url } => set(api_url, grpc_url, profile).await,
+        ConfigCommands::Show => show(profile).await,
+        ConfigCommands::Use { profile } => use_profile(&profile).await,
     }
 }
 
-async fn set(api_url: Option<String>, grpc_url: Option<String>) -> Result<()> {
+async fn set(
+    api_url: Option<String>,
+    grpc_url: Option<String>,
+    profile: Option<&str>,
+) -> Result<()> {
     if api_url.is_none() && grpc_url.is_none() {
         println!("No configuration options provided.");
         println!("Usage: driftwatch config set --api-url <url> --grpc-url <url>");
         return Ok(());
     }
 
-    let config_path = get_config_path()?;
-
-    let mut config = if config_path.exists() {
-        let config_str = fs::read_to_string(&config_path).context("Failed to read config file")?;
-        toml::from_str(&config_str).context("Invalid config file")?
-    } else {
-        Config {
-            token: String::new(),
-            api_url: String::new(),
-            grpc_url: String::new(),
-        }
-    };
+    let mut file = ConfigFile::load()?;
+    let name = file.profile_name(profile).to_string();
+    let config = file.profiles.entry(name.clone()).or_insert_with(|| Config {
+        token: String::new(),
+        api_url: DEFAULT_API_URL.to_string(),
+        grpc_url: DEFAULT_GRPC_URL.to_string(),
+        user: None,
+    });
 
     if let Some(url) = api_url {
         config.api_url = url.trim_end_matches('/').to_string();
@@ -58,16 +62,15 @@ async fn set(api_url: Option<String>, grpc_url: Option<String>) -> Result<()> {
         println!("gRPC URL set to: {}", config.grpc_url);
     }
 
-    if let Some(parent) = config_path.parent() {
-        fs::create_dir_all(parent)?;
+    let authenticated = !config.token.is_empty();
+    if file.active_profile.is_none() {
+        file.active_profile = Some(name.clone());
     }
+    let config_path = file.save()?;
 
-    let config_str = toml::to_string_pretty(&config)?;
-    fs::write(&config_path, config_str)?;
+    println!("Config saved to: {:?} (profile '{}')", config_path, name);
 
-    println!("Config saved to: {:?}", config_path);
-
-    if config.token.is_empty() {
+    if !authenticated {
         println!();
         println!("Note: You are not authenticated. Run 'driftwatch auth login' to authenticate.");
     }
@@ -75,8 +78,8 @@ async fn set(api_url: Option<String>, grpc_url: Option<String>) -> Result<()> {
     Ok(())
 }
 
-async fn show() -> Result<()> {
-    let config_path = get_config_path()?;
+async fn show(profile: Option<&str>) -> Result<()> {
+    let config_path = ConfigFile::path()?;
 
     if !config_path.exists() {
         println!("No configuration file found.");
@@ -84,11 +87,18 @@ async fn show() -> Result<()> {
         return Ok(());
     }
 
-    let config_str = fs::read_to_string(&config_path).context("Failed to read config file")?;
-    let config: Config = toml::from_str(&config_str).context("Invalid config file")?;
+    let file = ConfigFile::load()?;
+    let name = file.profile_name(profile);
 
     println!("Config file: {:?}", config_path);
+    println!("Profile: {}", name);
     println!();
+
+    let Some(config) = file.profiles.get(name) else {
+        println!("Profile '{}' is not configured.", name);
+        return Ok(());
+    };
+
     println!("API URL: {}", config.api_url);
     println!("gRPC URL: {}", config.grpc_url);
     if !config.token.is_empty() {
@@ -97,12 +107,33 @@ async fn show() -> Result<()> {
         println!("Token: (not set)");
     }
 
+    let others: Vec<&str> = file
+        .profiles
+        .keys()
+        .map(String::as_str)
+        .filter(|p| *p != name)
+        .collect();
+    if !others.is_empty() {
+        println!();
+        println!("Other profiles: {}", others.join(", "));
+    }
+
     Ok(())
 }
 
-fn get_config_path() -> Result<PathBuf> {
-    let config_dir = dirs::config_dir()
-        .context("Could not determine config directory")?
-        .join("driftwatch");
-    Ok(config_dir.join("config.toml"))
+async fn use_profile(profile: &str) -> Result<()> {
+    let mut file = ConfigFile::load()?;
+    if !file.profiles.contains_key(profile) {
+        bail!(
+            "No profile '{}'. Run 'driftwatch auth login --profile {}' first.",
+            profile,
+            profile
+        );
+    }
+
+    file.active_profile = Some(profile.to_string());
+    file.save()?;
+    println!("Now using profile '{}'", profile);
+
+    Ok(())
 }
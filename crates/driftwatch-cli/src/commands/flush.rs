@@ -0,0 +1,114 @@
+use anyhow::Result;
+use clap::Args;
+use std::path::PathBuf;
+
+use crate::api::{ApiClient, Config};
+use crate::commands::run::upload_flamegraphs;
+use crate::retry::{is_transient, Backoff, DEFAULT_RETRIES};
+use crate::spool::Spool;
+
+#[derive(Args)]
+pub struct FlushArgs {
+    /// Spool directory written by `driftwatch run`
+    #[arg(long, value_name = "DIR", env = "DRIFTWATCH_SPOOL_DIR")]
+    pub spool_dir: Option<PathBuf>,
+
+    /// Retries of each failed submission, with exponential backoff
+    #[arg(long, value_name = "N", default_value_t = DEFAULT_RETRIES)]
+    pub retries: u32,
+
+    /// List the pending reports without submitting them
+    #[arg(long)]
+    pub dry_run: bool,
+}
+
+/// Resubmits the reports `driftwatch run` could not submit, oldest first.
+pub async fn handle(args: FlushArgs, api_url: Option<&str>, profile: Option<&str>) -> Result<()> {
+    let spool = Spool::new(args.spool_dir)?;
+    let pending = spool.pending()?;
+    if pending.is_empty() {
+        println!("No pending reports in {}", spool.dir().display());
+        return Ok(());
+    }
+
+    println!(
+        "{} pending report(s) in {}",
+        pending.len(),
+        spool.dir().display()
+    );
+    if args.dry_run {
+        for (_, pending) in &pending {
+            let report = &pending.report;
+            println!(
+                "  {} {} {}/{} ({} results, {} flamegraph(s), spooled {})",
+                report.idempotency_key,
+                report.project_slug,
+                report.branch,
+                report.testbed,
+                report.metrics.len(),
+                pending.flamegraphs.len(),
+                pending.created_at.format("%Y-%m-%d %H:%M:%S UTC")
+            );
+        }
+        return Ok(());
+    }
+
+    let config = Config::load(profile)?;
+    let client = ApiClient::new(api_url.unwrap_or(&config.api_url), &config.token);
+    let backoff = Backoff::new(args.retries);
+
+    let total = pending.len();
+    let mut flushed = 0;
+    for (path, pending) in pending {
+        let report = &pending.report;
+        println!(
+            "\nSubmitting {} ({} results for {})...",
+            report.idempotency_key,
+            report.metrics.len(),
+            report.project_slug
+        );
+        let submitted = backoff
+            .run("Submitting results", || client.create_report(report))
+            .await;
+        let created = match submitted {
+            Ok(created) => created,
+            Err(e) if is_transient(&e) => {
+                // The server is still unreachable; the rest would fail too
+                eprintln!("Error: {:#}", e);
+                break;
+            }
+            Err(e) => {
+                eprintln!("Error: {:#}", e);
+                continue;
+            }
+        };
+        println!("  Report submitted: {}", created.id);
+
+        // The report is in, so a flamegraph failure must not leave it spooled
+        if !pending.flamegraphs.is_empty() {
+            if let Err(e) = upload_flamegraphs(
+                &client,
+                &backoff,
+                &report.project_slug,
+                &created.id,
+                &pending.flamegraphs,
+            )
+            .await
+            {
+                eprintln!("Warning: Flamegraphs not uploaded: {:#}", e);
+            }
+        }
+        spool.remove(&path)?;
+        flushed += 1;
+    }
+
+    println!("\nFlushed {} of {} report(s)", flushed, total);
+    if flushed < total {
+        anyhow::bail!(
+            "{} report(s) are still pending in {}",
+            total - flushed,
+            spool.dir().display()
+        );
+    }
+    Ok(())
+}
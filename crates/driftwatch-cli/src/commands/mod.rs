@@ -1,4 +1,7 @@
 pub mod auth;
 pub mod config;
+pub mod flush;
 pub mod project;
+pub mod report;
 pub mod run;
+pub mod threshold;
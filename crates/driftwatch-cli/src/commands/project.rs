@@ -1,5 +1,7 @@
-use anyhow::Result;
+use anyhow::{Context, Result};
 use clap::Subcommand;
+use std::io::Read;
+use std::path::{Path, PathBuf};
 
 use crate::api::{ApiClient, Config};
 
@@ -19,11 +21,32 @@ pub enum ProjectCommands {
     Show {
         slug: String,
     },
+    /// Export a project and its full history as a JSON archive
+    Export {
+        slug: String,
+        /// Write the archive to this file instead of stdout
+        #[arg(long, short)]
+        output: Option<PathBuf>,
+    },
+    /// Recreate an exported project under a new slug
+    Import {
+        /// Archive produced by `project export` (`-` for stdin)
+        file: PathBuf,
+        #[arg(long)]
+        slug: String,
+        /// Project name (defaults to the archived name)
+        #[arg(long)]
+        name: Option<String>,
+    },
 }
 
-pub async fn handle(command: ProjectCommands, api_url: &str) -> Result<()> {
-    let config = Config::load()?;
-    let client = ApiClient::new(api_url, &config.token);
+pub async fn handle(
+    command: ProjectCommands,
+    api_url: Option<&str>,
+    profile: Option<&str>,
+) -> Result<()> {
+    let config = Config::load(profile)?;
+    let client = ApiClient::new(api_url.unwrap_or(&config.api_url), &config.token);
 
     match command {
         ProjectCommands::List => list(&client).await,
@@ -34,6 +57,10 @@ pub async fn handle(command: ProjectCommands, api_url: &str) -> Result<()> {
             public,
         } => create(&client, &slug, &name, description.as_deref(), public).await,
         ProjectCommands::Show { slug } => show(&client, &slug).await,
+        ProjectCommands::Export { slug, output } => export(&client, &slug, output).await,
+        ProjectCommands::Import { file, slug, name } => {
+            import(&client, &file, &slug, name.as_deref()).await
+        }
     }
 }
 
@@ -107,3 +134,39 @@ async fn show(client: &ApiClient, slug: &str) -> Result<()> {
 
     Ok(())
 }
+
+async fn export(client: &ApiClient, slug: &str, output: Option<PathBuf>) -> Result<()> {
+    let archive = client.export_project(slug).await?;
+    let json = serde_json::to_string_pretty(&archive)?;
+
+    match output {
+        Some(path) => {
+            std::fs::write(&path, json)
+                .with_context(|| format!("Failed to write {}", path.display()))?;
+            eprintln!("Exported {} to {}", slug, path.display());
+        }
+        None => println!("{}", json),
+    }
+
+    Ok(())
+}
+
+async fn import(client: &ApiClient, file: &Path, slug: &str, name: Option<&str>) -> Result<()> {
+    let contents = if file.as_os_str() == "-" {
+        let mut buf = String::new();
+        std::io::stdin()
+            .read_to_string(&mut buf)
+            .context("Failed to read archive from stdin")?;
+        buf
+    } else {
+        std::fs::read_to_string(file)
+            .with_context(|| format!("Failed to read {}", file.display()))?
+    };
+
+    let archive: serde_json::Value =
+        serde_json::from_str(&contents).context("Archive is not valid JSON")?;
+
+    let project = client.import_project(slug, name, archive).await?;
+    println!("Imported project: {} ({})", project.name, project.slug);
+    Ok(())
+}
@@ -0,0 +1,155 @@
+use anyhow::{Context, Result};
+use clap::{Args, Subcommand};
+use std::process::ExitCode;
+
+use crate::api::{ApiClient, Config, OpenReportInput};
+use crate::ci::{self, CiEnv};
+use crate::commands::run::{print_alerts, print_warnings};
+use crate::fingerprint;
+use crate::gate::{FailOn, Gate, DEFAULT_FAIL_EXIT_CODE};
+use crate::project_config::ProjectConfig;
+
+#[derive(Subcommand)]
+pub enum ReportCommands {
+    /// Open a report for several `run --append-to` submissions, e.g. CI shards
+    Open(OpenArgs),
+    /// Complete an open report and evaluate its thresholds
+    Finalize(FinalizeArgs),
+}
+
+#[derive(Args)]
+pub struct OpenArgs {
+    /// Project slug (defaults to `project` in driftwatch.toml)
+    #[arg(long, short)]
+    pub project: Option<String>,
+
+    /// Branch name (detected from CI, `main` otherwise)
+    #[arg(long, short)]
+    pub branch: Option<String>,
+
+    #[arg(long, short)]
+    pub testbed: Option<String>,
+
+    /// Do not send the hardware fingerprint of this machine
+    #[arg(long)]
+    pub no_fingerprint: bool,
+
+    /// Commit hash (detected from CI or `git rev-parse HEAD`)
+    #[arg(long)]
+    pub hash: Option<String>,
+
+    /// PR number (detected from CI)
+    #[arg(long)]
+    pub pr: Option<i32>,
+
+    /// Opening again with the same key returns the same report, so every shard
+    /// can open it, e.g. with the CI run id
+    #[arg(long, value_name = "KEY", env = "DRIFTWATCH_IDEMPOTENCY_KEY")]
+    pub idempotency_key: Option<String>,
+}
+
+#[derive(Args)]
+pub struct FinalizeArgs {
+    pub report_id: String,
+
+    /// Exit with a failure code when the report has alerts, regressions or any change
+    #[arg(long, value_enum, value_name = "WHEN")]
+    pub fail_on: Option<FailOn>,
+
+    /// Regex of benchmarks whose failures are reported without failing
+    #[arg(long, value_name = "PATTERN", requires = "fail_on")]
+    pub allow_failure_for: Vec<String>,
+
+    /// Exit code when --fail-on is triggered (errors exit with 1)
+    #[arg(
+        long,
+        value_name = "CODE",
+        default_value_t = DEFAULT_FAIL_EXIT_CODE,
+        value_parser = clap::value_parser!(u8).range(2..)
+    )]
+    pub fail_exit_code: u8,
+}
+
+pub async fn handle(
+    command: ReportCommands,
+    api_url: Option<&str>,
+    profile: Option<&str>,
+) -> Result<ExitCode> {
+    let config = Config::load(profile)?;
+    let client = ApiClient::new(api_url.unwrap_or(&config.api_url), &config.token);
+
+    match command {
+        ReportCommands::Open(args) => open(&client, args).await.map(|()| ExitCode::SUCCESS),
+        ReportCommands::Finalize(args) => finalize(&client, args).await,
+    }
+}
+
+async fn open(client: &ApiClient, args: OpenArgs) -> Result<()> {
+    let project_config = ProjectConfig::discover()?;
+    let ci = CiEnv::detect();
+
+    let input = OpenReportInput {
+        project_slug: args
+            .project
+            .or_else(|| project_config.project.clone())
+            .context("No project given. Pass --project or set `project` in driftwatch.toml")?,
+        branch: args
+            .branch
+            .or_else(|| ci.as_ref().and_then(|c| c.branch.clone()))
+            .unwrap_or_else(|| "main".to_string()),
+        testbed: args
+            .testbed
+            .or_else(|| project_config.testbed.clone())
+            .unwrap_or_else(|| std::env::consts::OS.to_string()),
+        git_hash: args
+            .hash
+            .or_else(|| ci.as_ref().and_then(|c| c.head_sha.clone()))
+            .or_else(ci::git_head),
+        pr_number: args.pr.or_else(|| ci.as_ref().and_then(|c| c.pr_number)),
+        base_branch: ci.as_ref().and_then(|c| c.base_branch.clone()),
+        ci_provider: ci.as_ref().map(|c| c.provider.to_string()),
+        ci_run_url: ci.as_ref().and_then(|c| c.run_url.clone()),
+        idempotency_key: args.idempotency_key,
+        testbed_fingerprint: if args.no_fingerprint {
+            None
+        } else {
+            fingerprint::collect()
+        },
+    };
+
+    let report = client.open_report(&input).await?;
+    if report.finalized {
+        anyhow::bail!("Report {} is already finalized", report.id);
+    }
+
+    eprintln!(
+        "Opened report for {} on {} ({})",
+        input.project_slug, input.branch, input.testbed
+    );
+    print_warnings(&report);
+    // Only the id on stdout, for `REPORT=$(driftwatch report open ...)`
+    println!("{}", report.id);
+    Ok(())
+}
+
+async fn finalize(client: &ApiClient, args: FinalizeArgs) -> Result<ExitCode> {
+    let gate = args
+        .fail_on
+        .map(|fail_on| Gate::new(fail_on, &args.allow_failure_for))
+        .transpose()?;
+
+    let report = client.finalize_report(&args.report_id).await?;
+    println!(
+        "Report finalized: {} ({} results)",
+        report.id,
+        report.metrics.len()
+    );
+    print_alerts(&report);
+
+    if let Some(gate) = gate {
+        if gate.print_summary(&gate.offenses(&report)) {
+            return Ok(ExitCode::from(args.fail_exit_code));
+        }
+    }
+    Ok(ExitCode::SUCCESS)
+}
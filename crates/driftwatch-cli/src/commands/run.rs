@@ -1,26 +1,53 @@
 use anyhow::{Context, Result};
-use clap::Args;
-use std::path::PathBuf;
-use std::process::Command;
-
-use crate::adapters::criterion::parse_criterion_output;
-use crate::api::{ApiClient, Config, MetricInput};
+use chrono::Utc;
+use clap::{Args, ValueEnum};
+use sha2::{Digest, Sha256};
+use std::collections::{HashMap, HashSet};
+use std::io::{Read, Write};
+use std::path::{Path, PathBuf};
+use std::process::ExitCode;
+use std::time::{Duration, SystemTime};
+use uuid::Uuid;
+
+use crate::adapters::{criterion_json, AdapterOptions, Registry};
+use crate::aggregate::{aggregate, Aggregation, Bounds};
+use crate::api::{ApiClient, Config, CreateReportInput, MetricInput, Report};
+use crate::ci::{self, CiEnv};
+use crate::filter::BenchmarkFilter;
+use crate::fingerprint;
+use crate::gate::{FailOn, Gate, DEFAULT_FAIL_EXIT_CODE};
+use crate::output::{
+    self, progress, BenchmarkResult, Format, RunSummary, Submission, Verdict, SCHEMA_VERSION,
+};
+use crate::process::{self, CommandOutput, RunOptions};
+use crate::project_config::ProjectConfig;
+use crate::resources;
+use crate::retry::{is_transient, Backoff, DEFAULT_RETRIES};
+use crate::spool::{PendingReport, Spool};
 
 #[derive(Args)]
 pub struct RunArgs {
+    /// Project slug (defaults to `project` in driftwatch.toml)
     #[arg(long, short)]
-    pub project: String,
+    pub project: Option<String>,
 
-    #[arg(long, short, default_value = "main")]
-    pub branch: String,
+    /// Branch name (detected from CI, `main` otherwise)
+    #[arg(long, short)]
+    pub branch: Option<String>,
 
     #[arg(long, short)]
     pub testbed: Option<String>,
 
+    /// Do not send the CPU, memory, kernel and toolchain of this machine with
+    /// the report (only collected on Linux)
+    #[arg(long)]
+    pub no_fingerprint: bool,
+
+    /// Commit hash (detected from CI or `git rev-parse HEAD`)
     #[arg(long)]
     pub hash: Option<String>,
 
-    /// GitHub PR number for posting comments (auto-detected from GITHUB_REF)
+    /// PR number for posting comments (detected from CI)
     #[arg(long)]
     pub pr: Option<i32>,
 
@@ -31,268 +58,919 @@ pub struct RunArgs {
     #[arg(long)]
     pub dry_run: bool,
 
-    #[arg(trailing_var_arg = true, required = true)]
+    /// Benchmark output format, or `auto` to detect it from the output [default: auto]
+    #[arg(long)]
+    pub adapter: Option<String>,
+
+    /// Criterion output directory read by the `criterion-json` adapter
+    #[arg(long, value_name = "DIR")]
+    pub criterion_dir: Option<PathBuf>,
+
+    /// Also submit throughput for benchmarks that declare one
+    #[arg(long)]
+    pub throughput: bool,
+
+    /// Read benchmark results from a file (`-` for stdin) instead of running a
+    /// command. Repeat for several files; `ADAPTER=FILE` parses one with its
+    /// own adapter
+    #[arg(long, value_name = "[ADAPTER=]FILE", conflicts_with = "command")]
+    pub file: Vec<String>,
+
+    /// Only submit benchmarks whose name matches one of these regexes
+    /// (replaces `include` in driftwatch.toml)
+    #[arg(long, value_name = "PATTERN")]
+    pub include: Vec<String>,
+
+    /// Never submit benchmarks whose name matches one of these regexes
+    /// (replaces `exclude` in driftwatch.toml)
+    #[arg(long, value_name = "PATTERN")]
+    pub exclude: Vec<String>,
+
+    /// Submit benchmarks matching PATTERN under a new name, e.g.
+    /// `'^parser/(.*)=>parse/$1'`; the first matching rule applies (replaces
+    /// `rename` in driftwatch.toml)
+    #[arg(long, value_name = "PATTERN=>REPLACEMENT")]
+    pub rename: Vec<String>,
+
+    /// Run a named benchmark command from driftwatch.toml
+    #[arg(long, value_name = "NAME", conflicts_with_all = ["command", "file"])]
+    pub bench: Option<String>,
+
+    /// Run the command N times and submit the aggregated results
+    #[arg(
+        long,
+        value_name = "N",
+        default_value_t = 1,
+        value_parser = clap::value_parser!(u32).range(1..),
+        conflicts_with = "file"
+    )]
+    pub iterations: u32,
+
+    /// Run the command once before the measured runs and discard its results
+    #[arg(long, conflicts_with = "file")]
+    pub warmup: bool,
+
+    /// Also submit the command's wall time, CPU time, peak memory and context
+    /// switches as measures of the `driftwatch/run` benchmark (Linux only)
+    #[arg(long, conflicts_with = "file")]
+    pub measure_resources: bool,
+
+    /// Kill the command and everything it started after this long, e.g. `90s`,
+    /// `40m` or `2h`, and exit with 124
+    #[arg(
+        long,
+        value_name = "DURATION",
+        value_parser = process::parse_duration,
+        conflicts_with = "file"
+    )]
+    pub timeout: Option<Duration>,
+
+    /// Submit the results a failed or timed-out command printed before exiting
+    /// with its exit code
+    #[arg(long, conflicts_with = "file")]
+    pub submit_on_failure: bool,
+
+    /// How the values of repeated runs are combined
+    #[arg(long, value_enum, default_value_t = Aggregation::Mean)]
+    pub aggregate: Aggregation,
+
+    /// What is submitted as lower and upper values for repeated runs
+    #[arg(long, value_enum, default_value_t = Bounds::MinMax)]
+    pub bounds: Bounds,
+
+    /// Exit with a failure code when the report has alerts, regressions or any change
+    #[arg(long, value_enum, value_name = "WHEN")]
+    pub fail_on: Option<FailOn>,
+
+    /// Regex of benchmarks whose failures are reported without failing the run
+    #[arg(long, value_name = "PATTERN", requires = "fail_on")]
+    pub allow_failure_for: Vec<String>,
+
+    /// Exit code when --fail-on is triggered, even if the command failed
+    /// (errors exit with 1)
+    #[arg(
+        long,
+        value_name = "CODE",
+        default_value_t = DEFAULT_FAIL_EXIT_CODE,
+        value_parser = clap::value_parser!(u8).range(2..)
+    )]
+    pub fail_exit_code: u8,
+
+    /// Print the results and verdict as JSON, a markdown table or JUnit XML
+    #[arg(long, value_enum, default_value_t = Format::Text)]
+    pub format: Format,
+
+    /// Write the --format output to a file instead of stdout; not with
+    /// `--format text`, which is the progress printed as the run goes
+    #[arg(long, value_name = "FILE")]
+    pub output: Option<PathBuf>,
+
+    /// Retries of a failed submission, with exponential backoff
+    #[arg(long, value_name = "N", default_value_t = DEFAULT_RETRIES)]
+    pub retries: u32,
+
+    /// Where reports that could not be submitted are kept for `driftwatch flush`
+    #[arg(long, value_name = "DIR", env = "DRIFTWATCH_SPOOL_DIR")]
+    pub spool_dir: Option<PathBuf>,
+
+    /// Key that makes resubmitting this report return the original [default:
+    /// the CI job, attempt and testbed and a digest of the command and
+    /// benchmark names in CI, a random UUID otherwise].
+    /// Ignored with `--append-to`, whose report was keyed when it was opened
+    #[arg(long, value_name = "KEY", env = "DRIFTWATCH_IDEMPOTENCY_KEY")]
+    pub idempotency_key: Option<String>,
+
+    /// Add the results to a report opened with `driftwatch report open`
+    /// instead of submitting a report of their own
+    #[arg(long, value_name = "REPORT_ID", conflicts_with = "fail_on")]
+    pub append_to: Option<String>,
+
+    /// Fail instead of spooling a report that could not be submitted
+    #[arg(long, conflicts_with = "spool_dir")]
+    pub no_spool: bool,
+
+    #[arg(trailing_var_arg = true, required_unless_present_any = ["file", "bench"])]
     pub command: Vec<String>,
 }
 
-/// Parse PR number from GITHUB_REF environment variable format
-/// e.g., "refs/pull/123/merge" -> Some(123)
-pub fn parse_pr_from_github_ref(github_ref: &str) -> Option<i32> {
-    if github_ref.starts_with("refs/pull/") {
-        github_ref
-            .strip_prefix("refs/pull/")
-            .and_then(|s| s.split('/').next())
-            .and_then(|n| n.parse::<i32>().ok())
-            .filter(|&n| n > 0)
-    } else {
-        None
+impl RunArgs {
+    /// A combination of arguments clap can't rule out by itself, reported by
+    /// `main` as a usage error.
+    pub fn conflict(&self) -> Option<&'static str> {
+        (self.output.is_some() && self.format == Format::Text)
+            .then_some("the argument '--output <FILE>' cannot be used with '--format text'")
     }
 }
 
-/// Detect PR number from environment variables
-pub fn detect_pr_number() -> Option<i32> {
-    // Try GITHUB_REF first (e.g., "refs/pull/123/merge")
-    std::env::var("GITHUB_REF")
-        .ok()
-        .and_then(|r| parse_pr_from_github_ref(&r))
-        // Also try GITHUB_PR_NUMBER if set directly
-        .or_else(|| {
-            std::env::var("GITHUB_PR_NUMBER")
-                .ok()
-                .and_then(|n| n.parse().ok())
-        })
-}
+pub async fn handle(
+    args: RunArgs,
+    api_url: Option<&str>,
+    profile: Option<&str>,
+) -> Result<ExitCode> {
+    if args.format != Format::Text && args.output.is_none() {
+        output::progress_to_stderr();
+    }
+    if args.measure_resources && !cfg!(target_os = "linux") {
+        anyhow::bail!("--measure-resources is only supported on Linux");
+    }
 
-pub async fn handle(args: RunArgs, api_url: &str) -> Result<()> {
-    let config = Config::load()?;
-    let client = ApiClient::new(api_url, &config.token);
+    let config = Config::load(profile)?;
+    let client = ApiClient::new(api_url.unwrap_or(&config.api_url), &config.token);
+    let project_config = ProjectConfig::discover()?;
 
+    let project = args
+        .project
+        .or_else(|| project_config.project.clone())
+        .context("No project given. Pass --project or set `project` in driftwatch.toml")?;
     let testbed = args
         .testbed
+        .or_else(|| project_config.testbed.clone())
         .unwrap_or_else(|| std::env::consts::OS.to_string());
+    let adapter = args
+        .adapter
+        .or_else(|| project_config.adapter.clone())
+        .unwrap_or_else(|| "auto".to_string());
+    let flamegraphs = if args.flamegraph.is_empty() {
+        project_config.flamegraphs.clone()
+    } else {
+        args.flamegraph
+    };
+    let or_config = |flags: Vec<String>, config: &[String]| {
+        if flags.is_empty() {
+            config.to_vec()
+        } else {
+            flags
+        }
+    };
+    let filter = BenchmarkFilter::new(
+        &or_config(args.include, &project_config.include),
+        &or_config(args.exclude, &project_config.exclude),
+        &or_config(args.rename, &project_config.rename),
+    )?;
+    // Commands from driftwatch.toml run next to it, wherever driftwatch is
+    // started from
+    let (command, command_dir) = match &args.bench {
+        Some(name) => (
+            vec![project_config.command(name)?.to_string()],
+            project_config.dir().map(Path::to_path_buf),
+        ),
+        None => (args.command, None),
+    };
+
+    let gate = args
+        .fail_on
+        .map(|fail_on| Gate::new(fail_on, &args.allow_failure_for))
+        .transpose()?;
+    let ci = CiEnv::detect();
+
+    let branch = args
+        .branch
+        .or_else(|| ci.as_ref().and_then(|c| c.branch.clone()))
+        .unwrap_or_else(|| "main".to_string());
+    let git_hash = args
+        .hash
+        .or_else(|| ci.as_ref().and_then(|c| c.head_sha.clone()))
+        .or_else(ci::git_head);
+
+    let pr_number = args.pr.or_else(|| ci.as_ref().and_then(|c| c.pr_number));
+    let fingerprint = if args.no_fingerprint {
+        None
+    } else {
+        fingerprint::collect()
+    };
+
+    let mut summary = RunSummary {
+        schema_version: SCHEMA_VERSION,
+        project: project.clone(),
+        branch: branch.clone(),
+        testbed: testbed.clone(),
+        git_hash: git_hash.clone(),
+        pr_number,
+        ci_provider: ci.as_ref().map(|c| c.provider.to_string()),
+        submission: Submission::Skipped,
+        report_id: None,
+        results: Vec::new(),
+        verdict: None,
+    };
 
-    let git_hash = args.hash.or_else(|| {
-        Command::new("git")
-            .args(["rev-parse", "HEAD"])
-            .output()
-            .ok()
-            .and_then(|o| String::from_utf8(o.stdout).ok())
-            .map(|s| s.trim().to_string())
-    });
-
-    // Auto-detect PR number from GitHub Actions environment
-    let pr_number = args.pr.or_else(detect_pr_number);
-
-    println!("Running benchmarks...");
-    println!("  Project: {}", args.project);
-    println!("  Branch: {}", args.branch);
-    println!("  Testbed: {}", testbed);
+    progress!("Running benchmarks...");
+    if let Some(ref path) = project_config.path {
+        progress!("  Config: {}", path.display());
+    }
+    if let Some(ref ci) = ci {
+        progress!("  CI: {}", ci.provider);
+    }
+    progress!("  Project: {}", project);
+    progress!("  Branch: {}", branch);
+    progress!("  Testbed: {}", testbed);
+    if let Some(ref fingerprint) = fingerprint {
+        progress!("  Hardware: {}", fingerprint.describe());
+    }
     if let Some(ref hash) = git_hash {
-        println!("  Git hash: {}", hash);
+        progress!("  Git hash: {}", hash);
     }
     if let Some(pr) = pr_number {
-        println!("  PR: #{}", pr);
+        progress!("  PR: #{}", pr);
+    }
+    if let Some(base) = ci.as_ref().and_then(|c| c.base_branch.as_deref()) {
+        progress!("  Base branch: {}", base);
+    }
+    if !flamegraphs.is_empty() {
+        progress!("  Flamegraphs: {} file(s)", flamegraphs.len());
+    }
+    if !args.file.is_empty() {
+        progress!("  Results from: {}", args.file.join(", "));
+    }
+    if args.iterations > 1 {
+        progress!(
+            "  Iterations: {} ({}, {} bounds)",
+            args.iterations,
+            value_name(args.aggregate),
+            value_name(args.bounds)
+        );
+    }
+    if args.measure_resources {
+        progress!("  Resource usage: submitted as {}", resources::BENCHMARK);
+    }
+    progress!();
+
+    let mut options = AdapterOptions {
+        criterion_dir: args.criterion_dir.clone().or_else(|| {
+            command_dir
+                .as_ref()
+                .map(|dir| dir.join(criterion_json::DEFAULT_CRITERION_DIR))
+        }),
+        throughput: args.throughput,
+        since: None,
+        custom: project_config.custom.clone(),
+    };
+    let mut registry = Registry::new(&options)?;
+    // Only kept for a results file; a command's output was shown as it ran
+    let mut unparsed = String::new();
+    let mut runs = Vec::new();
+    let mut failure = None;
+    if !args.file.is_empty() {
+        let files: Vec<_> = args
+            .file
+            .iter()
+            .map(|spec| results_file(spec, &registry))
+            .collect();
+        if files.iter().filter(|(_, path)| is_stdin(path)).count() > 1 {
+            anyhow::bail!("stdin (`-`) can only be given once with --file");
+        }
+        let mut metrics = Vec::new();
+        // Which file each benchmark and measure came from; one result in two
+        // files is an error rather than a silent pick
+        let mut sources: HashMap<(String, String), String> = HashMap::new();
+        for (file_adapter, path) in files {
+            let contents = read_results_file(path)?;
+            let parsed = parse_output(&registry, file_adapter.unwrap_or(&adapter), &contents, "")?;
+            let source = if is_stdin(path) {
+                "stdin".to_string()
+            } else {
+                path.display().to_string()
+            };
+            if parsed.is_empty() && args.file.len() > 1 {
+                eprintln!("Warning: No benchmark results found in {}", source);
+            }
+            let mut seen = HashSet::new();
+            for metric in &parsed {
+                let key = (metric.benchmark.clone(), metric.measure.clone());
+                if !seen.insert(key.clone()) {
+                    continue;
+                }
+                if let Some(other) = sources.insert(key, source.clone()) {
+                    anyhow::bail!(
+                        "{} ({}) is in both {} and {}",
+                        metric.benchmark,
+                        metric.measure,
+                        other,
+                        source
+                    );
+                }
+            }
+            if args.file.len() == 1 {
+                unparsed = contents;
+            }
+            metrics.extend(parsed);
+        }
+        runs.push(metrics);
+    } else {
+        let run_options = RunOptions {
+            timeout: args.timeout,
+            measure_resources: args.measure_resources,
+            current_dir: command_dir,
+        };
+        if args.warmup {
+            progress!("Warmup run (results discarded)...");
+            let output = process::run(
+                &command,
+                &RunOptions {
+                    measure_resources: false,
+                    ..run_options.clone()
+                },
+            )?;
+            if !output.success() {
+                failure = Some(output);
+            }
+        }
+        for i in 1..=args.iterations {
+            if failure.is_some() {
+                break;
+            }
+            if args.iterations > 1 {
+                progress!("Run {}/{}...", i, args.iterations);
+            }
+            options.since = Some(SystemTime::now());
+            registry = Registry::new(&options)?;
+            let output = process::run(&command, &run_options)?;
+            let mut metrics = parse_output(&registry, &adapter, &output.stdout, &output.stderr)?;
+            if let Some(ref resources) = output.resources {
+                metrics.extend(resources.metrics());
+            }
+            runs.push(metrics);
+            if !output.success() {
+                failure = Some(output);
+            }
+        }
+        if args.iterations > 1 {
+            progress!();
+        }
     }
-    if !args.flamegraph.is_empty() {
-        println!("  Flamegraphs: {} file(s)", args.flamegraph.len());
+
+    // The command's exit code is passed on, with or without its results
+    let command_exit_code = failure.as_ref().map(CommandOutput::exit_code);
+    let success = command_exit_code.map_or(ExitCode::SUCCESS, ExitCode::from);
+    if let Some(failure) = failure {
+        eprintln!("Error: Benchmark command {}", failure.failure());
+        if !args.submit_on_failure {
+            emit(&summary, args.format, args.output.as_deref())?;
+            return Ok(success);
+        }
+        progress!("Submitting the results of the runs so far (--submit-on-failure).\n");
     }
-    println!();
 
-    let cmd = args.command.join(" ");
-    let output = if cfg!(target_os = "windows") {
-        Command::new("cmd")
-            .args(["/C", &cmd])
-            .output()
-            .context("Failed to execute benchmark command")?
+    let parsed = if runs.len() > 1 {
+        aggregate(runs, args.aggregate, args.bounds)
     } else {
-        Command::new("sh")
-            .args(["-c", &cmd])
-            .output()
-            .context("Failed to execute benchmark command")?
+        runs.into_iter().flatten().collect()
     };
+    let parsed_count = parsed.len();
 
-    let stdout = String::from_utf8_lossy(&output.stdout);
-    let stderr = String::from_utf8_lossy(&output.stderr);
-
-    let combined_output = format!("{}\n{}", stdout, stderr);
+    let metrics: Vec<MetricInput> = filter
+        .apply(parsed)?
+        .into_iter()
+        .map(|mut m| {
+            m.measure = project_config.measure_name(&m.measure).to_string();
+            m
+        })
+        .collect();
 
-    let results = parse_criterion_output(&combined_output);
+    if metrics.len() < parsed_count && !metrics.is_empty() {
+        progress!(
+            "Excluded {} of {} benchmark results by the include/exclude filters.",
+            parsed_count - metrics.len(),
+            parsed_count
+        );
+    }
+    if metrics.is_empty() && parsed_count > 0 {
+        progress!(
+            "All {} benchmark results were excluded by the include/exclude filters.",
+            parsed_count
+        );
+        emit(&summary, args.format, args.output.as_deref())?;
+        return Ok(success);
+    }
 
-    if results.is_empty() {
-        println!("No benchmark results found in output.");
-        println!("Make sure you're running Criterion benchmarks.");
-        if !stdout.is_empty() {
-            println!("\nStdout:\n{}", stdout);
-        }
-        if !stderr.is_empty() {
-            println!("\nStderr:\n{}", stderr);
+    if metrics.is_empty() {
+        progress!("No benchmark results found in output.");
+        progress!(
+            "Make sure the output is in a supported format ({}).",
+            registry.names().join(", ")
+        );
+        if !unparsed.is_empty() {
+            progress!("\nStdout:\n{}", unparsed);
         }
-        return Ok(());
+        emit(&summary, args.format, args.output.as_deref())?;
+        return Ok(success);
     }
 
-    println!("Found {} benchmark results:", results.len());
-    for result in &results {
-        let lower = result
-            .lower
+    progress!("Found {} benchmark results:", metrics.len());
+    for metric in &metrics {
+        let lower = metric
+            .lower_value
             .map(|v| format!("{:.2}", v))
             .unwrap_or_default();
-        let upper = result
-            .upper
+        let upper = metric
+            .upper_value
             .map(|v| format!("{:.2}", v))
             .unwrap_or_default();
-        println!(
-            "  {} : {:.2} ns [{} - {}]",
-            result.name, result.value, lower, upper
+        let units = metric
+            .units
+            .as_deref()
+            .map(|u| format!(" {}", u))
+            .unwrap_or_default();
+        progress!(
+            "  {} ({}) : {:.2}{} [{} - {}]",
+            metric.benchmark,
+            metric.measure,
+            metric.value,
+            units,
+            lower,
+            upper
         );
     }
-    println!();
+    progress!();
 
     if args.dry_run {
-        println!("Dry run - not submitting results.");
-        return Ok(());
+        progress!("Dry run - not submitting results.");
+        summary.submission = Submission::DryRun;
+        summary.results = metrics.iter().map(BenchmarkResult::unchecked).collect();
+        emit(&summary, args.format, args.output.as_deref())?;
+        return Ok(success);
     }
 
-    let metrics: Vec<MetricInput> = results
-        .into_iter()
-        .map(|r| MetricInput {
-            benchmark: r.name,
-            measure: "latency".to_string(),
-            value: r.value,
-            lower_value: r.lower,
-            upper_value: r.upper,
+    progress!("Submitting results...");
+    let idempotency_key = args
+        .idempotency_key
+        .or_else(|| {
+            let submission = submission_digest(&command, &metrics);
+            ci.as_ref()
+                .and_then(|c| c.idempotency_key(&testbed, &submission))
         })
-        .collect();
-
-    println!("Submitting results...");
-    let report = client
-        .create_report(
-            &args.project,
-            &args.branch,
-            &testbed,
-            git_hash.as_deref(),
-            pr_number,
-            metrics,
-        )
-        .await?;
-
-    println!("Report submitted: {}", report.id);
-
-    if !report.alerts.is_empty() {
-        println!("\n{} alerts generated:", report.alerts.len());
-        for alert in &report.alerts {
-            let direction = if alert.percent_change > 0.0 { "+" } else { "" };
-            println!(
-                "  - {}{:.1}% change (baseline: {:.2})",
-                direction, alert.percent_change, alert.baseline_value
-            );
+        .unwrap_or_else(|| Uuid::new_v4().to_string());
+    let input = CreateReportInput {
+        project_slug: project.clone(),
+        branch,
+        testbed,
+        git_hash,
+        pr_number,
+        base_branch: ci.as_ref().and_then(|c| c.base_branch.clone()),
+        ci_provider: ci.as_ref().map(|c| c.provider.to_string()),
+        ci_run_url: ci.as_ref().and_then(|c| c.run_url.clone()),
+        idempotency_key,
+        testbed_fingerprint: fingerprint,
+        metrics,
+    };
+    let backoff = Backoff::new(args.retries);
+
+    if let Some(ref report_id) = args.append_to {
+        let report = backoff
+            .run("Appending results", || {
+                client.append_metrics(report_id, &input.metrics)
+            })
+            .await?;
+        progress!(
+            "Appended {} results to report {}",
+            input.metrics.len(),
+            report.id
+        );
+        progress!("Alerts are raised when `driftwatch report finalize` completes it.");
+        if !flamegraphs.is_empty() {
+            progress!("\nUploading {} flamegraph(s)...", flamegraphs.len());
+            upload_flamegraphs(&client, &backoff, &project, &report.id, &flamegraphs).await?;
         }
+        summary.results = input
+            .metrics
+            .iter()
+            .map(BenchmarkResult::unchecked)
+            .collect();
+        summary.submission = Submission::Appended;
+        summary.report_id = Some(report.id);
+        emit(&summary, args.format, args.output.as_deref())?;
+        return Ok(success);
     }
 
-    // Upload flamegraphs if provided
-    if !args.flamegraph.is_empty() {
-        println!("\nUploading {} flamegraph(s)...", args.flamegraph.len());
-
-        for flamegraph_path in &args.flamegraph {
-            // Validate file exists and is SVG
-            if !flamegraph_path.exists() {
-                eprintln!(
-                    "Warning: Flamegraph file not found: {}",
-                    flamegraph_path.display()
+    let submitted = backoff
+        .run("Submitting results", || client.create_report(&input))
+        .await;
+    let report = match submitted {
+        Ok(report) => report,
+        Err(e) if is_transient(&e) && !args.no_spool => {
+            eprintln!("Warning: Could not submit results: {:#}", e);
+            let spool = Spool::new(args.spool_dir)?;
+            summary.submission = Submission::Spooled;
+            summary.results = input
+                .metrics
+                .iter()
+                .map(BenchmarkResult::unchecked)
+                .collect();
+            let path = spool.save(&PendingReport {
+                created_at: Utc::now(),
+                report: input,
+                flamegraphs: flamegraphs
+                    .iter()
+                    .map(|p| std::path::absolute(p).unwrap_or_else(|_| p.clone()))
+                    .collect(),
+            })?;
+            progress!("Report spooled to {}", path.display());
+            progress!("Submit it later with `driftwatch flush`.");
+            emit(&summary, args.format, args.output.as_deref())?;
+            if gate.is_some() {
+                anyhow::bail!(
+                    "--fail-on could not be checked because the report was not submitted"
                 );
-                continue;
             }
+            return Ok(success);
+        }
+        Err(e) => return Err(e),
+    };
 
-            let file_name = flamegraph_path
-                .file_name()
-                .and_then(|n| n.to_str())
-                .unwrap_or("flamegraph.svg");
-
-            let metadata = std::fs::metadata(flamegraph_path)
-                .context("Failed to read flamegraph file metadata")?;
-            let file_size = metadata.len() as i64;
-
-            // Check file size (10 MiB limit)
-            const MAX_FILE_SIZE: i64 = 10 * 1024 * 1024;
-            if file_size > MAX_FILE_SIZE {
-                eprintln!(
-                    "Warning: Flamegraph file too large ({}MB > 10MB limit): {}",
-                    file_size / 1024 / 1024,
-                    flamegraph_path.display()
-                );
-                continue;
-            }
+    progress!("Report submitted: {}", report.id);
+    print_warnings(&report);
+    print_alerts(&report);
+
+    if !flamegraphs.is_empty() {
+        progress!("\nUploading {} flamegraph(s)...", flamegraphs.len());
+        upload_flamegraphs(&client, &backoff, &project, &report.id, &flamegraphs).await?;
+    }
+
+    // Without --fail-on the report is still checked for the summary, as
+    // `--fail-on alert`, but never fails the run
+    let enforced = gate.is_some();
+    let gate = match gate {
+        Some(gate) => gate,
+        None => Gate::new(FailOn::Alert, &[])?,
+    };
+    let offenses = gate.offenses(&report);
+    let failed = enforced && gate.print_summary(&offenses);
+
+    summary.results = BenchmarkResult::checked(&input.metrics, &report, &offenses);
+    summary.submission = Submission::Checked;
+    summary.verdict = Some(Verdict::new(gate.fail_on(), enforced, &report, &offenses));
+    summary.report_id = Some(report.id);
+    emit(&summary, args.format, args.output.as_deref())?;
+
+    Ok(ExitCode::from(exit_code(
+        command_exit_code,
+        failed,
+        args.fail_exit_code,
+    )))
+}
+
+/// Exit code of a run whose report was checked: `--fail-exit-code` when the
+/// gate failed, even if the command failed too, then the command's own code.
+/// That is passed on unchanged, so it may equal 1 (an error) or the gate's
+/// code; pick a `--fail-exit-code` the command never uses to tell them apart.
+fn exit_code(command_exit_code: Option<u8>, gate_failed: bool, fail_exit_code: u8) -> u8 {
+    if gate_failed {
+        return fail_exit_code;
+    }
+    command_exit_code.unwrap_or(0)
+}
+
+pub fn print_warnings(report: &Report) {
+    for warning in &report.warnings {
+        eprintln!("Warning: {}", warning);
+    }
+}
+
+pub fn print_alerts(report: &Report) {
+    if report.alerts.is_empty() {
+        return;
+    }
+    progress!("\n{} alerts generated:", report.alerts.len());
+    for alert in &report.alerts {
+        let direction = if alert.percent_change > 0.0 { "+" } else { "" };
+        progress!(
+            "  - {} ({}): {}{:.1}% change (baseline: {:.2})",
+            alert.metric.benchmark.name,
+            alert.metric.measure.name,
+            direction,
+            alert.percent_change,
+            alert.baseline_value
+        );
+    }
+}
+
+/// Uploads flamegraph SVGs and links them to a submitted report; missing or
+/// oversized files are skipped with a warning.
+pub async fn upload_flamegraphs(
+    client: &ApiClient,
+    backoff: &Backoff,
+    project: &str,
+    report_id: &str,
+    paths: &[PathBuf],
+) -> Result<()> {
+    for flamegraph_path in paths {
+        // Validate file exists and is SVG
+        if !flamegraph_path.exists() {
+            eprintln!(
+                "Warning: Flamegraph file not found: {}",
+                flamegraph_path.display()
+            );
+            continue;
+        }
 
-            // Get signed upload URL
-            let upload_url = client
-                .get_flamegraph_upload_url(&args.project, file_name)
-                .await
-                .context("Failed to get flamegraph upload URL")?;
-
-            // Upload file to storage
-            client
-                .upload_flamegraph_file(&upload_url.signed_url, flamegraph_path)
-                .await
-                .context("Failed to upload flamegraph file")?;
-
-            // Confirm upload and link to report
-            let flamegraph = client
-                .confirm_flamegraph_upload(
-                    &report.id,
-                    &upload_url.storage_path,
-                    file_name,
-                    file_size,
-                    None, // No specific benchmark association
-                )
-                .await
-                .context("Failed to confirm flamegraph upload")?;
-
-            println!("  Uploaded: {} ({})", file_name, flamegraph.id);
+        let file_name = flamegraph_path
+            .file_name()
+            .and_then(|n| n.to_str())
+            .unwrap_or("flamegraph.svg");
+
+        let metadata = std::fs::metadata(flamegraph_path)
+            .context("Failed to read flamegraph file metadata")?;
+        let file_size = metadata.len() as i64;
+
+        // Check file size (10 MiB limit)
+        const MAX_FILE_SIZE: i64 = 10 * 1024 * 1024;
+        if file_size > MAX_FILE_SIZE {
+            eprintln!(
+                "Warning: Flamegraph file too large ({}MB > 10MB limit): {}",
+                file_size / 1024 / 1024,
+                flamegraph_path.display()
+            );
+            continue;
         }
+
+        let flamegraph = backoff
+            .run("Uploading flamegraph", || async {
+                // Get signed upload URL
+                let upload_url = client
+                    .get_flamegraph_upload_url(project, file_name)
+                    .await
+                    .context("Failed to get flamegraph upload URL")?;
+
+                // Upload file to storage
+                client
+                    .upload_flamegraph_file(&upload_url.signed_url, flamegraph_path)
+                    .await
+                    .context("Failed to upload flamegraph file")?;
+
+                // Confirm upload and link to report
+                client
+                    .confirm_flamegraph_upload(
+                        report_id,
+                        &upload_url.storage_path,
+                        file_name,
+                        file_size,
+                        None, // No specific benchmark association
+                    )
+                    .await
+                    .context("Failed to confirm flamegraph upload")
+            })
+            .await?;
+
+        progress!("  Uploaded: {} ({})", file_name, flamegraph.id);
+    }
+    Ok(())
+}
+
+/// Digest of the command and of the benchmarks and measures it found, which
+/// tells apart the reports one CI job submits for a testbed.
+fn submission_digest(command: &[String], metrics: &[MetricInput]) -> String {
+    let mut names: Vec<_> = metrics
+        .iter()
+        .map(|m| (m.benchmark.as_str(), m.measure.as_str()))
+        .collect();
+    names.sort_unstable();
+    let json = serde_json::to_vec(&(command, names)).expect("names are serializable");
+    hex::encode(&Sha256::digest(json)[..8])
+}
+
+/// Prints or writes the `--format` output, and appends the markdown summary
+/// to `$GITHUB_STEP_SUMMARY` when GitHub Actions provides one.
+fn emit(summary: &RunSummary, format: Format, output: Option<&Path>) -> Result<()> {
+    if let Some(path) = std::env::var_os("GITHUB_STEP_SUMMARY").filter(|p| !p.is_empty()) {
+        let mut file = std::fs::OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&path)
+            .context("Failed to open $GITHUB_STEP_SUMMARY")?;
+        file.write_all(summary.to_markdown().as_bytes())
+            .context("Failed to write $GITHUB_STEP_SUMMARY")?;
     }
 
+    let Some(rendered) = summary.render(format) else {
+        return Ok(());
+    };
+    match output {
+        Some(path) => std::fs::write(path, rendered)
+            .with_context(|| format!("Failed to write {}", path.display()))?,
+        None => print!("{}", rendered),
+    }
     Ok(())
 }
 
+fn value_name(value: impl ValueEnum) -> String {
+    value
+        .to_possible_value()
+        .map(|v| v.get_name().to_string())
+        .unwrap_or_default()
+}
+
+/// The adapter named by a `--file` argument of the form `ADAPTER=FILE`, and
+/// the file. Anything before a `=` that is not an adapter is part of the path.
+fn results_file<'a>(spec: &'a str, registry: &Registry) -> (Option<&'a str>, &'a Path) {
+    match spec.split_once('=') {
+        Some((adapter, path))
+            if adapter == "auto" || adapter == "custom" || registry.find(adapter).is_some() =>
+        {
+            (Some(adapter), Path::new(path))
+        }
+        _ => (None, Path::new(spec)),
+    }
+}
+
+fn is_stdin(path: &Path) -> bool {
+    path.as_os_str() == "-"
+}
+
+fn read_results_file(path: &Path) -> Result<String> {
+    if is_stdin(path) {
+        let mut buf = String::new();
+        std::io::stdin()
+            .read_to_string(&mut buf)
+            .context("Failed to read benchmark results from stdin")?;
+        Ok(buf)
+    } else {
+        std::fs::read_to_string(path)
+            .with_context(|| format!("Failed to read benchmark results from {}", path.display()))
+    }
+}
+
+/// Parses the command output with the named adapter, or with the first one
+/// that recognises it when `adapter_name` is `auto`.
+fn parse_output(
+    registry: &Registry,
+    adapter_name: &str,
+    stdout: &str,
+    stderr: &str,
+) -> Result<Vec<MetricInput>> {
+    let combined_output = format!("{}\n{}", stdout, stderr);
+
+    let adapter = if adapter_name == "auto" {
+        match registry
+            .detect(stdout)
+            .or_else(|| registry.detect(&combined_output))
+        {
+            Some(adapter) => adapter,
+            None => return Ok(Vec::new()),
+        }
+    } else if adapter_name == "custom" && registry.find("custom").is_none() {
+        anyhow::bail!("The custom adapter needs a [custom] section in driftwatch.toml");
+    } else {
+        registry.find(adapter_name).with_context(|| {
+            format!(
+                "Unknown adapter '{}' (available: auto, {})",
+                adapter_name,
+                registry.names().join(", ")
+            )
+        })?
+    };
+
+    let input = if adapter.stdout_only() {
+        stdout
+    } else {
+        &combined_output
+    };
+
+    Ok(adapter
+        .parse(input)?
+        .into_iter()
+        .flat_map(|result| {
+            let benchmark = result.name;
+            result.measures.into_iter().map(move |m| MetricInput {
+                benchmark: benchmark.clone(),
+                higher_is_better: Some(m.higher_is_better()),
+                measure: m.measure,
+                units: m.units,
+                value: m.value,
+                lower_value: m.lower,
+                upper_value: m.upper,
+            })
+        })
+        .collect())
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
 
     #[test]
-    fn test_parse_pr_from_github_ref_valid() {
-        assert_eq!(parse_pr_from_github_ref("refs/pull/123/merge"), Some(123));
-        assert_eq!(parse_pr_from_github_ref("refs/pull/1/merge"), Some(1));
-        assert_eq!(
-            parse_pr_from_github_ref("refs/pull/99999/merge"),
-            Some(99999)
-        );
-        assert_eq!(parse_pr_from_github_ref("refs/pull/42/head"), Some(42));
+    fn test_parse_output_auto_detects_adapter() {
+        let registry = Registry::new(&AdapterOptions::default()).unwrap();
+        let criterion = "fib/10  time:   [1.0 ns 2.0 ns 3.0 ns]\n";
+        let metrics = parse_output(&registry, "auto", criterion, "").unwrap();
+        assert_eq!(metrics.len(), 1);
+        assert_eq!(metrics[0].measure, "latency");
+        assert_eq!(metrics[0].units.as_deref(), Some("ns"));
+
+        // BMF on stdout is still found when the build logs to stderr
+        let bmf = r#"{"fib/10": {"instructions": {"value": 42.0}}}"#;
+        let metrics = parse_output(&registry, "auto", bmf, "Compiling fib v0.1.0").unwrap();
+        assert_eq!(metrics.len(), 1);
+        assert_eq!(metrics[0].measure, "instructions");
+
+        assert!(parse_output(&registry, "auto", "hello", "")
+            .unwrap()
+            .is_empty());
     }
 
     #[test]
-    fn test_parse_pr_from_github_ref_invalid() {
-        assert_eq!(parse_pr_from_github_ref("refs/heads/main"), None);
-        assert_eq!(parse_pr_from_github_ref("refs/tags/v1.0.0"), None);
-        assert_eq!(parse_pr_from_github_ref(""), None);
-        assert_eq!(parse_pr_from_github_ref("refs/pull/"), None);
-        assert_eq!(parse_pr_from_github_ref("refs/pull/abc/merge"), None);
+    fn test_parse_output_explicit_adapter() {
+        let registry = Registry::new(&AdapterOptions::default()).unwrap();
+        let bmf = r#"{"fib/10": {"latency": {"value": 42.0}}}"#;
+        assert_eq!(parse_output(&registry, "bmf", bmf, "").unwrap().len(), 1);
+        assert!(parse_output(&registry, "criterion", bmf, "")
+            .unwrap()
+            .is_empty());
+        assert!(parse_output(&registry, "nope", bmf, "").is_err());
     }
 
     #[test]
-    fn test_parse_pr_from_github_ref_edge_cases() {
-        // Not starting with refs/pull/
-        assert_eq!(parse_pr_from_github_ref("pull/123/merge"), None);
-        assert_eq!(parse_pr_from_github_ref(" refs/pull/123/merge"), None);
-
-        // Large PR numbers
+    fn test_submission_digest() {
+        let metric = |benchmark: &str| MetricInput {
+            benchmark: benchmark.to_string(),
+            measure: "latency".to_string(),
+            units: None,
+            higher_is_better: None,
+            value: 1.0,
+            lower_value: None,
+            upper_value: None,
+        };
+        let command = vec!["cargo".to_string(), "bench".to_string()];
+        let digest = submission_digest(&command, &[metric("a"), metric("b")]);
+        assert_eq!(digest.len(), 16);
+        // Only the names count, in any order
         assert_eq!(
-            parse_pr_from_github_ref("refs/pull/2147483647/merge"),
-            Some(2147483647)
+            digest,
+            submission_digest(&command, &[metric("b"), metric("a")])
+        );
+        assert_ne!(digest, submission_digest(&command, &[metric("a")]));
+        assert_ne!(
+            digest,
+            submission_digest(&command[..1], &[metric("a"), metric("b")])
         );
+    }
 
-        // Negative numbers (should be rejected)
-        assert_eq!(parse_pr_from_github_ref("refs/pull/-1/merge"), None);
+    #[test]
+    fn test_exit_code_gate_takes_precedence() {
+        assert_eq!(exit_code(None, false, 3), 0);
+        assert_eq!(exit_code(None, true, 3), 3);
+        assert_eq!(exit_code(Some(2), false, 3), 2);
+        assert_eq!(exit_code(Some(2), true, 3), 3);
+        assert_eq!(exit_code(Some(124), true, 10), 10);
+    }
 
-        // Zero (should be rejected - PR numbers start at 1)
-        assert_eq!(parse_pr_from_github_ref("refs/pull/0/merge"), None);
+    #[test]
+    fn test_results_file() {
+        let registry = Registry::new(&AdapterOptions::default()).unwrap();
+        assert_eq!(
+            results_file("bmf=out/results.json", &registry),
+            (Some("bmf"), Path::new("out/results.json"))
+        );
+        assert_eq!(
+            results_file("go=-", &registry),
+            (Some("go"), Path::new("-"))
+        );
+        assert_eq!(
+            results_file("results.json", &registry),
+            (None, Path::new("results.json"))
+        );
+        // Not an adapter, so part of the file name
+        assert_eq!(
+            results_file("run=1.txt", &registry),
+            (None, Path::new("run=1.txt"))
+        );
     }
 }
@@ -0,0 +1,592 @@
+use anyhow::{Context, Result};
+use clap::{Args, Subcommand};
+use serde::Deserialize;
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
+
+use crate::api::{ApiClient, Config, CreateThresholdInput, Threshold, UpdateThresholdInput};
+use crate::project_config::ProjectConfig;
+
+/// Baseline values a threshold needs before it is checked, as the server
+/// defaults it.
+const DEFAULT_MIN_SAMPLE_SIZE: i32 = 2;
+
+#[derive(Subcommand)]
+pub enum ThresholdCommands {
+    /// List a project's thresholds
+    List {
+        /// Project slug (defaults to `project` in driftwatch.toml)
+        #[arg(long, short)]
+        project: Option<String>,
+    },
+    /// Alert when a measure moves further from its baseline than allowed
+    Create(CreateArgs),
+    /// Change the boundaries of a threshold
+    Update(UpdateArgs),
+    /// Delete a threshold
+    Delete {
+        /// Threshold ID, as shown by `threshold list`
+        id: String,
+    },
+    /// Create, update and delete thresholds to match a file
+    Apply(ApplyArgs),
+}
+
+#[derive(Args)]
+pub struct CreateArgs {
+    /// Project slug (defaults to `project` in driftwatch.toml)
+    #[arg(long, short)]
+    pub project: Option<String>,
+
+    /// Measure name, e.g. `latency`
+    #[arg(long, short)]
+    pub measure: String,
+
+    /// Only check reports on this branch (every branch otherwise)
+    #[arg(long, short)]
+    pub branch: Option<String>,
+
+    /// Only check reports from this testbed (every testbed otherwise)
+    #[arg(long, short)]
+    pub testbed: Option<String>,
+
+    #[command(flatten)]
+    pub limits: Limits,
+}
+
+#[derive(Args)]
+pub struct UpdateArgs {
+    /// Threshold ID, as shown by `threshold list`
+    pub id: String,
+
+    #[command(flatten)]
+    pub limits: Limits,
+
+    /// Stop checking increases
+    #[arg(long, conflicts_with = "upper_boundary")]
+    pub no_upper_boundary: bool,
+
+    /// Stop checking decreases
+    #[arg(long, conflicts_with = "lower_boundary")]
+    pub no_lower_boundary: bool,
+}
+
+#[derive(Args)]
+pub struct Limits {
+    /// Allowed increase over the baseline, in percent
+    #[arg(long, value_name = "PERCENT", value_parser = parse_boundary)]
+    pub upper_boundary: Option<f64>,
+
+    /// Allowed decrease below the baseline, in percent
+    #[arg(long, value_name = "PERCENT", value_parser = parse_boundary)]
+    pub lower_boundary: Option<f64>,
+
+    /// Baseline values needed before the threshold is checked [default: 2]
+    #[arg(
+        long,
+        value_name = "N",
+        value_parser = clap::value_parser!(i32).range(1..)
+    )]
+    pub min_sample_size: Option<i32>,
+}
+
+#[derive(Args)]
+pub struct ApplyArgs {
+    /// TOML file of `[[threshold]]` tables, each with a `measure`, optional
+    /// `branch` and `testbed`, and the boundaries as in `threshold create`
+    #[arg(long, short, value_name = "FILE")]
+    pub file: PathBuf,
+
+    /// Project slug (defaults to `project` in the file, then in driftwatch.toml)
+    #[arg(long, short)]
+    pub project: Option<String>,
+
+    /// Show the changes without making them
+    #[arg(long)]
+    pub dry_run: bool,
+}
+
+pub async fn handle(
+    command: ThresholdCommands,
+    api_url: Option<&str>,
+    profile: Option<&str>,
+) -> Result<()> {
+    let config = Config::load(profile)?;
+    let client = ApiClient::new(api_url.unwrap_or(&config.api_url), &config.token);
+
+    match command {
+        ThresholdCommands::List { project } => list(&client, &project_slug(project)?).await,
+        ThresholdCommands::Create(args) => create(&client, args).await,
+        ThresholdCommands::Update(args) => update(&client, args).await,
+        ThresholdCommands::Delete { id } => {
+            client.delete_threshold(&id).await?;
+            println!("Deleted threshold {}", id);
+            Ok(())
+        }
+        ThresholdCommands::Apply(args) => apply(&client, args).await,
+    }
+}
+
+fn project_slug(project: Option<String>) -> Result<String> {
+    match project {
+        Some(project) => Ok(project),
+        None => ProjectConfig::discover()?
+            .project
+            .context("No project given. Pass --project or set `project` in driftwatch.toml"),
+    }
+}
+
+fn parse_boundary(s: &str) -> Result<f64, String> {
+    let percent: f64 = s
+        .trim_end_matches('%')
+        .parse()
+        .map_err(|_| format!("invalid percentage '{}'", s))?;
+    if !percent.is_finite() || percent < 0.0 {
+        return Err("must be a percentage of zero or more".to_string());
+    }
+    Ok(percent)
+}
+
+async fn list(client: &ApiClient, project: &str) -> Result<()> {
+    let thresholds = client.list_thresholds(project).await?;
+
+    if thresholds.is_empty() {
+        println!("No thresholds in {}.", project);
+        println!(
+            "Create one with: driftwatch threshold create --measure latency --upper-boundary 10"
+        );
+        return Ok(());
+    }
+
+    println!(
+        "{:<36}  {:<16} {:<12} {:<12} {:>8} {:>8} {:>7}",
+        "ID", "MEASURE", "BRANCH", "TESTBED", "UPPER", "LOWER", "SAMPLES"
+    );
+    println!("{}", "-".repeat(105));
+
+    for threshold in thresholds {
+        println!(
+            "{:<36}  {:<16} {:<12} {:<12} {:>8} {:>8} {:>7}",
+            threshold.id,
+            threshold.measure.name,
+            threshold.branch.as_ref().map_or("*", |b| b.name.as_str()),
+            threshold.testbed.as_ref().map_or("*", |t| t.name.as_str()),
+            boundary('+', threshold.upper_boundary),
+            boundary('-', threshold.lower_boundary),
+            threshold.min_sample_size
+        );
+    }
+
+    Ok(())
+}
+
+async fn create(client: &ApiClient, args: CreateArgs) -> Result<()> {
+    if args.limits.upper_boundary.is_none() && args.limits.lower_boundary.is_none() {
+        anyhow::bail!("Give --upper-boundary, --lower-boundary or both");
+    }
+    let input = CreateThresholdInput {
+        project_slug: project_slug(args.project)?,
+        measure: args.measure,
+        branch: args.branch,
+        testbed: args.testbed,
+        upper_boundary: args.limits.upper_boundary,
+        lower_boundary: args.limits.lower_boundary,
+        min_sample_size: args.limits.min_sample_size,
+    };
+    let threshold = client.create_threshold(&input).await?;
+    println!("Created threshold {}", threshold.id);
+    println!("  {}: {}", scope_of(&threshold), limits_of(&threshold));
+    Ok(())
+}
+
+async fn update(client: &ApiClient, args: UpdateArgs) -> Result<()> {
+    let clear = |no: bool, value: Option<f64>| if no { Some(None) } else { value.map(Some) };
+    let input = UpdateThresholdInput {
+        upper_boundary: clear(args.no_upper_boundary, args.limits.upper_boundary),
+        lower_boundary: clear(args.no_lower_boundary, args.limits.lower_boundary),
+        min_sample_size: args.limits.min_sample_size,
+    };
+    if input.upper_boundary.is_none()
+        && input.lower_boundary.is_none()
+        && input.min_sample_size.is_none()
+    {
+        anyhow::bail!("Nothing to update; give a boundary or --min-sample-size");
+    }
+    let threshold = client.update_threshold(&args.id, &input).await?;
+    println!("Updated threshold {}", threshold.id);
+    println!("  {}: {}", scope_of(&threshold), limits_of(&threshold));
+    Ok(())
+}
+
+/// `thresholds.toml`: the complete set of a project's thresholds.
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct ThresholdsFile {
+    /// Project slug
+    project: Option<String>,
+    #[serde(default, rename = "threshold")]
+    thresholds: Vec<ThresholdSpec>,
+}
+
+/// One `[[threshold]]` of a thresholds file.
+#[derive(Debug, PartialEq, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct ThresholdSpec {
+    measure: String,
+    branch: Option<String>,
+    testbed: Option<String>,
+    upper_boundary: Option<f64>,
+    lower_boundary: Option<f64>,
+    #[serde(default = "default_min_sample_size")]
+    min_sample_size: i32,
+}
+
+fn default_min_sample_size() -> i32 {
+    DEFAULT_MIN_SAMPLE_SIZE
+}
+
+impl ThresholdSpec {
+    fn scope(&self) -> String {
+        scope(
+            &self.measure,
+            self.branch.as_deref(),
+            self.testbed.as_deref(),
+        )
+    }
+
+    fn limits(&self) -> String {
+        limits(
+            self.upper_boundary,
+            self.lower_boundary,
+            self.min_sample_size,
+        )
+    }
+
+    /// Whether `threshold` checks the same measure, branch and testbed.
+    fn covers(&self, threshold: &Threshold) -> bool {
+        self.measure == threshold.measure.name
+            && self.branch.as_deref() == threshold.branch.as_ref().map(|b| b.name.as_str())
+            && self.testbed.as_deref() == threshold.testbed.as_ref().map(|t| t.name.as_str())
+    }
+
+    fn matches(&self, threshold: &Threshold) -> bool {
+        self.upper_boundary == threshold.upper_boundary
+            && self.lower_boundary == threshold.lower_boundary
+            && self.min_sample_size == threshold.min_sample_size
+    }
+}
+
+fn load_thresholds_file(path: &Path) -> Result<ThresholdsFile> {
+    let contents = std::fs::read_to_string(path)
+        .with_context(|| format!("Failed to read {}", path.display()))?;
+    let file: ThresholdsFile =
+        toml::from_str(&contents).with_context(|| format!("Invalid {}", path.display()))?;
+
+    let mut scopes = HashSet::new();
+    for spec in &file.thresholds {
+        let invalid = |problem: &str| {
+            anyhow::anyhow!("Invalid {}: {} {}", path.display(), spec.scope(), problem)
+        };
+        if spec.upper_boundary.is_none() && spec.lower_boundary.is_none() {
+            return Err(invalid("needs an upper_boundary or a lower_boundary"));
+        }
+        let negative = |b: Option<f64>| b.is_some_and(|b| !b.is_finite() || b < 0.0);
+        if negative(spec.upper_boundary) || negative(spec.lower_boundary) {
+            return Err(invalid(
+                "has a boundary that is not a percentage of zero or more",
+            ));
+        }
+        if spec.min_sample_size < 1 {
+            return Err(invalid("needs a min_sample_size of at least 1"));
+        }
+        if !scopes.insert((&spec.measure, &spec.branch, &spec.testbed)) {
+            return Err(invalid("is defined more than once"));
+        }
+    }
+    Ok(file)
+}
+
+#[derive(Debug)]
+enum Change<'a> {
+    Create(&'a ThresholdSpec),
+    Update(&'a Threshold, &'a ThresholdSpec),
+    Delete(&'a Threshold),
+}
+
+/// What makes `existing` match `desired`. A threshold is identified by its
+/// measure, branch and testbed; those in neither are deleted.
+fn plan<'a>(desired: &'a [ThresholdSpec], existing: &'a [Threshold]) -> Vec<Change<'a>> {
+    let mut unmatched: Vec<&Threshold> = existing.iter().collect();
+    let mut changes = Vec::new();
+    for spec in desired {
+        match unmatched.iter().position(|t| spec.covers(t)) {
+            Some(i) => {
+                let threshold = unmatched.remove(i);
+                if !spec.matches(threshold) {
+                    changes.push(Change::Update(threshold, spec));
+                }
+            }
+            None => changes.push(Change::Create(spec)),
+        }
+    }
+    changes.extend(unmatched.into_iter().map(Change::Delete));
+    changes
+}
+
+async fn apply(client: &ApiClient, args: ApplyArgs) -> Result<()> {
+    let file = load_thresholds_file(&args.file)?;
+    let project = project_slug(args.project.or(file.project))?;
+    let existing = client.list_thresholds(&project).await?;
+    let changes = plan(&file.thresholds, &existing);
+
+    if changes.is_empty() {
+        println!(
+            "Thresholds of {} match {} ({} threshold(s)).",
+            project,
+            args.file.display(),
+            file.thresholds.len()
+        );
+        return Ok(());
+    }
+
+    for change in &changes {
+        match change {
+            Change::Create(spec) => println!("+ {}: {}", spec.scope(), spec.limits()),
+            Change::Update(threshold, spec) => println!(
+                "~ {}: {} -> {}",
+                spec.scope(),
+                limits_of(threshold),
+                spec.limits()
+            ),
+            Change::Delete(threshold) => {
+                println!("- {}: {}", scope_of(threshold), limits_of(threshold))
+            }
+        }
+    }
+    println!();
+
+    if args.dry_run {
+        println!("Dry run - not applying {} change(s).", changes.len());
+        return Ok(());
+    }
+
+    // Deletes last, so a failure part-way leaves no measure unchecked
+    let (deletes, others): (Vec<_>, Vec<_>) =
+        changes.iter().partition(|c| matches!(c, Change::Delete(_)));
+    for change in others.into_iter().chain(deletes) {
+        match change {
+            Change::Create(spec) => {
+                let input = CreateThresholdInput {
+                    project_slug: project.clone(),
+                    measure: spec.measure.clone(),
+                    branch: spec.branch.clone(),
+                    testbed: spec.testbed.clone(),
+                    upper_boundary: spec.upper_boundary,
+                    lower_boundary: spec.lower_boundary,
+                    min_sample_size: Some(spec.min_sample_size),
+                };
+                client
+                    .create_threshold(&input)
+                    .await
+                    .with_context(|| format!("Failed to create {}", spec.scope()))?;
+            }
+            Change::Update(threshold, spec) => {
+                let input = UpdateThresholdInput {
+                    upper_boundary: Some(spec.upper_boundary),
+                    lower_boundary: Some(spec.lower_boundary),
+                    min_sample_size: Some(spec.min_sample_size),
+                };
+                client
+                    .update_threshold(&threshold.id, &input)
+                    .await
+                    .with_context(|| format!("Failed to update {}", spec.scope()))?;
+            }
+            Change::Delete(threshold) => {
+                client
+                    .delete_threshold(&threshold.id)
+                    .await
+                    .with_context(|| format!("Failed to delete {}", scope_of(threshold)))?;
+            }
+        }
+    }
+
+    let count = |f: fn(&Change) -> bool| changes.iter().filter(|c| f(c)).count();
+    println!(
+        "Applied: {} created, {} updated, {} deleted.",
+        count(|c| matches!(c, Change::Create(_))),
+        count(|c| matches!(c, Change::Update(..))),
+        count(|c| matches!(c, Change::Delete(_)))
+    );
+    Ok(())
+}
+
+/// e.g. `latency on main / ci-linux`
+fn scope(measure: &str, branch: Option<&str>, testbed: Option<&str>) -> String {
+    format!(
+        "{} on {} / {}",
+        measure,
+        branch.unwrap_or("any branch"),
+        testbed.unwrap_or("any testbed")
+    )
+}
+
+fn scope_of(threshold: &Threshold) -> String {
+    scope(
+        &threshold.measure.name,
+        threshold.branch.as_ref().map(|b| b.name.as_str()),
+        threshold.testbed.as_ref().map(|t| t.name.as_str()),
+    )
+}
+
+/// e.g. `+10% / -5%, 2 samples`
+fn limits(upper: Option<f64>, lower: Option<f64>, min_sample_size: i32) -> String {
+    format!(
+        "{} / {}, {} sample{}",
+        boundary('+', upper),
+        boundary('-', lower),
+        min_sample_size,
+        if min_sample_size == 1 { "" } else { "s" }
+    )
+}
+
+fn limits_of(threshold: &Threshold) -> String {
+    limits(
+        threshold.upper_boundary,
+        threshold.lower_boundary,
+        threshold.min_sample_size,
+    )
+}
+
+fn boundary(sign: char, percent: Option<f64>) -> String {
+    match percent {
+        Some(percent) => format!("{}{}%", sign, percent),
+        None => "-".to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::api::{Branch, Measure};
+
+    fn threshold(id: &str, measure: &str, branch: Option<&str>, upper: f64) -> Threshold {
+        Threshold {
+            id: id.to_string(),
+            measure: Measure {
+                id: format!("{}-measure", id),
+                name: measure.to_string(),
+                units: None,
+                higher_is_better: false,
+            },
+            branch: branch.map(|name| Branch {
+                id: format!("{}-branch", id),
+                name: name.to_string(),
+            }),
+            testbed: None,
+            upper_boundary: Some(upper),
+            lower_boundary: None,
+            min_sample_size: DEFAULT_MIN_SAMPLE_SIZE,
+        }
+    }
+
+    fn parse(toml: &str) -> Result<ThresholdsFile> {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("thresholds.toml");
+        std::fs::write(&path, toml).unwrap();
+        load_thresholds_file(&path)
+    }
+
+    #[test]
+    fn test_load_thresholds_file() {
+        let file = parse(
+            r#"
+project = "demo"
+
+[[threshold]]
+measure = "latency"
+branch = "main"
+upper_boundary = 10.0
+
+[[threshold]]
+measure = "instructions"
+lower_boundary = 2
+min_sample_size = 5
+"#,
+        )
+        .unwrap();
+        assert_eq!(file.project.as_deref(), Some("demo"));
+        assert_eq!(
+            file.thresholds[1],
+            ThresholdSpec {
+                measure: "instructions".to_string(),
+                branch: None,
+                testbed: None,
+                upper_boundary: None,
+                lower_boundary: Some(2.0),
+                min_sample_size: 5,
+            }
+        );
+        assert_eq!(file.thresholds[0].min_sample_size, DEFAULT_MIN_SAMPLE_SIZE);
+
+        let no_boundary = "[[threshold]]\nmeasure = \"latency\"\n";
+        assert!(parse(no_boundary).is_err());
+        let duplicate = "[[threshold]]\nmeasure = \"latency\"\nupper_boundary = 5.0\n\
+                         [[threshold]]\nmeasure = \"latency\"\nupper_boundary = 10.0\n";
+        assert!(parse(duplicate).is_err());
+        assert!(parse("[[threshold]]\nmeasure = \"latency\"\nupper = 5.0\n").is_err());
+    }
+
+    #[test]
+    fn test_plan() {
+        let desired = parse(
+            r#"
+[[threshold]]
+measure = "latency"
+branch = "main"
+upper_boundary = 10.0
+
+[[threshold]]
+measure = "latency"
+upper_boundary = 20.0
+
+[[threshold]]
+measure = "instructions"
+upper_boundary = 1.0
+"#,
+        )
+        .unwrap()
+        .thresholds;
+        let existing = [
+            threshold("a", "latency", Some("main"), 10.0),
+            threshold("b", "latency", None, 15.0),
+            threshold("c", "peak-rss", None, 5.0),
+        ];
+
+        let changes: Vec<String> = plan(&desired, &existing)
+            .iter()
+            .map(|change| match change {
+                Change::Create(spec) => format!("create {}", spec.scope()),
+                Change::Update(t, _) => format!("update {}", t.id),
+                Change::Delete(t) => format!("delete {}", t.id),
+            })
+            .collect();
+        assert_eq!(
+            changes,
+            [
+                "update b",
+                "create instructions on any branch / any testbed",
+                "delete c"
+            ]
+        );
+
+        assert!(plan(&desired[..1], &existing[..1]).is_empty());
+    }
+
+    #[test]
+    fn test_limits() {
+        assert_eq!(limits(Some(10.0), None, 2), "+10% / -, 2 samples");
+        assert_eq!(limits(None, Some(2.5), 1), "- / -2.5%, 1 sample");
+        assert_eq!(parse_boundary("10%"), Ok(10.0));
+        assert!(parse_boundary("-5").is_err());
+    }
+}
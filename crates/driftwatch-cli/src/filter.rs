@@ -0,0 +1,176 @@
+use anyhow::{Context, Result};
+use regex::Regex;
+use std::collections::HashMap;
+
+use crate::api::MetricInput;
+
+/// Separates the pattern from the replacement in a rename rule.
+const RENAME_SEPARATOR: &str = "=>";
+
+/// Decides which benchmarks are submitted, and under which name.
+#[derive(Debug, Default)]
+pub struct BenchmarkFilter {
+    include: Vec<Regex>,
+    exclude: Vec<Regex>,
+    renames: Vec<(Regex, String)>,
+}
+
+impl BenchmarkFilter {
+    pub fn new(include: &[String], exclude: &[String], renames: &[String]) -> Result<Self> {
+        Ok(Self {
+            include: compile(include)?,
+            exclude: compile(exclude)?,
+            renames: renames
+                .iter()
+                .map(|rule| parse_rename(rule))
+                .collect::<Result<_>>()?,
+        })
+    }
+
+    /// A benchmark is kept if it matches any include pattern (or there are
+    /// none) and no exclude pattern.
+    pub fn keeps(&self, name: &str) -> bool {
+        (self.include.is_empty() || self.include.iter().any(|r| r.is_match(name)))
+            && !self.exclude.iter().any(|r| r.is_match(name))
+    }
+
+    /// Name a benchmark is submitted under: the first matching rename rule
+    /// replaces the first match of its pattern, `$1` and `${name}` expanding
+    /// to its groups.
+    pub fn rename(&self, name: &str) -> String {
+        self.renames
+            .iter()
+            .find(|(pattern, _)| pattern.is_match(name))
+            .map(|(pattern, replacement)| pattern.replace(name, replacement).into_owned())
+            .unwrap_or_else(|| name.to_string())
+    }
+
+    /// The metrics of kept benchmarks, renamed. Two benchmarks renamed to
+    /// the same name and measure are an error rather than a silent overwrite.
+    pub fn apply(&self, metrics: Vec<MetricInput>) -> Result<Vec<MetricInput>> {
+        let mut renamed_from: HashMap<(String, String), String> = HashMap::new();
+        let mut kept = Vec::with_capacity(metrics.len());
+        for mut metric in metrics {
+            if !self.keeps(&metric.benchmark) {
+                continue;
+            }
+            let name = self.rename(&metric.benchmark);
+            let key = (name.clone(), metric.measure.clone());
+            if let Some(other) = renamed_from.insert(key, metric.benchmark.clone()) {
+                if other != metric.benchmark {
+                    anyhow::bail!(
+                        "Rename rules map both '{}' and '{}' to '{}'",
+                        other,
+                        metric.benchmark,
+                        name
+                    );
+                }
+            }
+            metric.benchmark = name;
+            kept.push(metric);
+        }
+        Ok(kept)
+    }
+}
+
+fn compile(patterns: &[String]) -> Result<Vec<Regex>> {
+    patterns
+        .iter()
+        .map(|p| Regex::new(p).with_context(|| format!("Invalid benchmark filter '{}'", p)))
+        .collect()
+}
+
+/// Parses `pattern=>replacement`.
+fn parse_rename(rule: &str) -> Result<(Regex, String)> {
+    let (pattern, replacement) = rule.split_once(RENAME_SEPARATOR).with_context(|| {
+        format!(
+            "Invalid rename rule '{}', expected 'pattern{}replacement'",
+            rule, RENAME_SEPARATOR
+        )
+    })?;
+    let pattern = Regex::new(pattern)
+        .with_context(|| format!("Invalid pattern in rename rule '{}'", rule))?;
+    Ok((pattern, replacement.to_string()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn metric(benchmark: &str) -> MetricInput {
+        MetricInput {
+            benchmark: benchmark.to_string(),
+            measure: "latency".to_string(),
+            units: None,
+            higher_is_better: None,
+            value: 1.0,
+            lower_value: None,
+            upper_value: None,
+        }
+    }
+
+    #[test]
+    fn test_benchmark_filter() {
+        let all = BenchmarkFilter::default();
+        assert!(all.keeps("anything"));
+
+        let filter = BenchmarkFilter::new(
+            &["^parse/".to_string(), "^render".to_string()],
+            &["/huge$".to_string()],
+            &[],
+        )
+        .unwrap();
+        assert!(filter.keeps("parse/small"));
+        assert!(filter.keeps("render"));
+        assert!(!filter.keeps("parse/huge"));
+        assert!(!filter.keeps("load/small"));
+
+        assert!(BenchmarkFilter::new(&["(".to_string()], &[], &[]).is_err());
+    }
+
+    #[test]
+    fn test_rename() {
+        let filter = BenchmarkFilter::new(
+            &[],
+            &[],
+            &[
+                "^parser/(.*)=>parse/$1".to_string(),
+                "^parse/=>unused/".to_string(),
+                "_v2$=>".to_string(),
+            ],
+        )
+        .unwrap();
+        assert_eq!(filter.rename("parser/small"), "parse/small");
+        // Only the first matching rule applies
+        assert_eq!(filter.rename("parse/small"), "unused/small");
+        assert_eq!(filter.rename("render_v2"), "render");
+        assert_eq!(filter.rename("load"), "load");
+
+        assert!(BenchmarkFilter::new(&[], &[], &["parser/".to_string()]).is_err());
+        assert!(BenchmarkFilter::new(&[], &[], &["(=>x".to_string()]).is_err());
+    }
+
+    #[test]
+    fn test_apply_filters_before_renaming() {
+        let filter = BenchmarkFilter::new(
+            &[],
+            &["^experimental/".to_string()],
+            &["^old/=>new/".to_string()],
+        )
+        .unwrap();
+        let metrics = filter
+            .apply(vec![
+                metric("old/parse"),
+                metric("experimental/parse"),
+                metric("render"),
+            ])
+            .unwrap();
+        let names: Vec<_> = metrics.iter().map(|m| m.benchmark.as_str()).collect();
+        assert_eq!(names, ["new/parse", "render"]);
+
+        // Both would be submitted as new/parse
+        assert!(filter
+            .apply(vec![metric("old/parse"), metric("new/parse")])
+            .is_err());
+    }
+}
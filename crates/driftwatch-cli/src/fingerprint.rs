@@ -0,0 +1,227 @@
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::Path;
+use std::process::Command;
+
+/// Hardware and toolchain of the machine the benchmarks ran on, sent with a
+/// report so the server can tell when a testbed's machine changes.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct Fingerprint {
+    #[serde(rename = "cpuModel")]
+    pub cpu_model: Option<String>,
+    /// Logical CPUs
+    #[serde(rename = "cpuCores")]
+    pub cpu_cores: Option<i32>,
+    #[serde(rename = "memoryMb")]
+    pub memory_mb: Option<i32>,
+    pub kernel: Option<String>,
+    pub rustc: Option<String>,
+    #[serde(rename = "cpuGovernor")]
+    pub cpu_governor: Option<String>,
+    /// `bare-metal`, `container`, `vm` or `container-in-vm`
+    pub virtualization: Option<String>,
+}
+
+impl Fingerprint {
+    /// One line for the run header, e.g.
+    /// `AMD EPYC 7763, 4 cores, 15.6 GiB, performance, vm`.
+    pub fn describe(&self) -> String {
+        let mut parts = Vec::new();
+        if let Some(ref model) = self.cpu_model {
+            parts.push(model.clone());
+        }
+        if let Some(cores) = self.cpu_cores {
+            parts.push(format!(
+                "{} core{}",
+                cores,
+                if cores == 1 { "" } else { "s" }
+            ));
+        }
+        if let Some(mb) = self.memory_mb {
+            parts.push(format!("{:.1} GiB", mb as f64 / 1024.0));
+        }
+        if let Some(ref governor) = self.cpu_governor {
+            parts.push(governor.clone());
+        }
+        if let Some(ref virtualization) = self.virtualization {
+            parts.push(virtualization.clone());
+        }
+        parts.join(", ")
+    }
+}
+
+/// Fingerprint of this machine. Only Linux is supported; elsewhere `None`.
+pub fn collect() -> Option<Fingerprint> {
+    if !cfg!(target_os = "linux") {
+        return None;
+    }
+
+    let cpuinfo = read("/proc/cpuinfo").unwrap_or_default();
+    let cpu = parse_cpuinfo(&cpuinfo);
+    let container = Path::new("/.dockerenv").exists()
+        || Path::new("/run/.containerenv").exists()
+        || std::env::var_os("container").is_some()
+        || read("/proc/1/cgroup").is_some_and(|cgroup| cgroup_is_container(&cgroup));
+
+    Some(Fingerprint {
+        cpu_model: cpu.model,
+        cpu_cores: cpu.cores,
+        memory_mb: read("/proc/meminfo").and_then(|m| parse_meminfo(&m)),
+        kernel: read("/proc/sys/kernel/osrelease"),
+        rustc: rustc_version(),
+        cpu_governor: read("/sys/devices/system/cpu/cpu0/cpufreq/scaling_governor"),
+        virtualization: Some(virtualization(container, cpu.hypervisor).to_string()),
+    })
+}
+
+/// Trimmed contents of `path`, `None` if unreadable or empty.
+fn read(path: &str) -> Option<String> {
+    fs::read_to_string(path)
+        .ok()
+        .map(|s| s.trim().to_string())
+        .filter(|s| !s.is_empty())
+}
+
+/// `rustc --version` of the toolchain cargo would use.
+fn rustc_version() -> Option<String> {
+    let rustc = std::env::var("RUSTC").unwrap_or_else(|_| "rustc".to_string());
+    Command::new(rustc)
+        .arg("--version")
+        .output()
+        .ok()
+        .filter(|o| o.status.success())
+        .and_then(|o| String::from_utf8(o.stdout).ok())
+        .map(|s| s.trim().to_string())
+}
+
+#[derive(Debug, Default, PartialEq)]
+struct CpuInfo {
+    model: Option<String>,
+    cores: Option<i32>,
+    /// Set by the kernel when running under a hypervisor (x86 only)
+    hypervisor: bool,
+}
+
+fn parse_cpuinfo(cpuinfo: &str) -> CpuInfo {
+    let mut info = CpuInfo::default();
+    let mut cores = 0;
+    for line in cpuinfo.lines() {
+        let Some((key, value)) = line.split_once(':') else {
+            continue;
+        };
+        let value = value.trim();
+        match key.trim() {
+            "processor" => cores += 1,
+            "model name" if info.model.is_none() => info.model = Some(value.to_string()),
+            "flags" => {
+                info.hypervisor |= value.split_whitespace().any(|flag| flag == "hypervisor");
+            }
+            _ => {}
+        }
+    }
+    info.cores = (cores > 0).then_some(cores);
+    info
+}
+
+/// Total memory in MiB from `/proc/meminfo`.
+fn parse_meminfo(meminfo: &str) -> Option<i32> {
+    let kb: i64 = meminfo
+        .lines()
+        .find_map(|line| line.strip_prefix("MemTotal:"))?
+        .trim()
+        .trim_end_matches("kB")
+        .trim()
+        .parse()
+        .ok()?;
+    i32::try_from(kb / 1024).ok()
+}
+
+/// Whether the cgroups of PID 1 belong to a container runtime.
+fn cgroup_is_container(cgroup: &str) -> bool {
+    ["docker", "kubepods", "containerd", "lxc", "libpod"]
+        .iter()
+        .any(|runtime| cgroup.contains(runtime))
+}
+
+fn virtualization(container: bool, hypervisor: bool) -> &'static str {
+    match (container, hypervisor) {
+        (true, true) => "container-in-vm",
+        (true, false) => "container",
+        (false, true) => "vm",
+        (false, false) => "bare-metal",
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const CPUINFO: &str = "\
+processor\t: 0
+vendor_id\t: AuthenticAMD
+model name\t: AMD EPYC 7763 64-Core Processor
+flags\t\t: fpu vme de pse hypervisor lahf_lm
+
+processor\t: 1
+vendor_id\t: AuthenticAMD
+model name\t: AMD EPYC 7763 64-Core Processor
+flags\t\t: fpu vme de pse hypervisor lahf_lm
+";
+
+    #[test]
+    fn test_parse_cpuinfo() {
+        assert_eq!(
+            parse_cpuinfo(CPUINFO),
+            CpuInfo {
+                model: Some("AMD EPYC 7763 64-Core Processor".to_string()),
+                cores: Some(2),
+                hypervisor: true,
+            }
+        );
+
+        // ARM kernels have no model name or flags lines
+        let arm = "processor\t: 0\nBogoMIPS\t: 48.00\nFeatures\t: fp asimd\n";
+        assert_eq!(
+            parse_cpuinfo(arm),
+            CpuInfo {
+                model: None,
+                cores: Some(1),
+                hypervisor: false,
+            }
+        );
+        assert_eq!(parse_cpuinfo(""), CpuInfo::default());
+    }
+
+    #[test]
+    fn test_parse_meminfo() {
+        let meminfo = "MemTotal:       16364312 kB\nMemFree:         1234567 kB\n";
+        assert_eq!(parse_meminfo(meminfo), Some(15980));
+        assert_eq!(parse_meminfo("MemFree: 12 kB\n"), None);
+    }
+
+    #[test]
+    fn test_container_detection() {
+        assert!(cgroup_is_container(
+            "0::/kubepods/besteffort/pod1234/abcdef\n"
+        ));
+        assert!(!cgroup_is_container("0::/init.scope\n"));
+        assert_eq!(virtualization(true, true), "container-in-vm");
+        assert_eq!(virtualization(false, false), "bare-metal");
+    }
+
+    #[test]
+    fn test_describe() {
+        let fingerprint = Fingerprint {
+            cpu_model: Some("AMD EPYC 7763 64-Core Processor".to_string()),
+            cpu_cores: Some(4),
+            memory_mb: Some(15980),
+            cpu_governor: Some("performance".to_string()),
+            virtualization: Some("vm".to_string()),
+            ..Default::default()
+        };
+        assert_eq!(
+            fingerprint.describe(),
+            "AMD EPYC 7763 64-Core Processor, 4 cores, 15.6 GiB, performance, vm"
+        );
+    }
+}
@@ -0,0 +1,294 @@
+use anyhow::{Context, Result};
+use clap::ValueEnum;
+use regex::Regex;
+use serde::Serialize;
+
+use crate::api::{Metric, Report};
+use crate::output::progress;
+
+/// Exit code of `driftwatch run` when the report fails `--fail-on`; errors
+/// exit with 1.
+pub const DEFAULT_FAIL_EXIT_CODE: u8 = 3;
+
+/// What makes a submitted report fail the run.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum, Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum FailOn {
+    /// Any alert raised by a threshold
+    Alert,
+    /// Alerts where the value got worse
+    Regression,
+    /// Any alert, or any value that moved outside its bounds from the baseline
+    AnyChange,
+}
+
+/// One benchmark and measure that failed the check.
+#[derive(Debug, PartialEq)]
+pub struct Offense {
+    pub benchmark: String,
+    pub measure: String,
+    pub units: Option<String>,
+    pub baseline: f64,
+    pub current: f64,
+    /// Whether an `--allow-failure-for` pattern matches the benchmark
+    pub allowed: bool,
+}
+
+impl Offense {
+    fn new(metric: &Metric, baseline: f64, current: f64) -> Self {
+        Self {
+            benchmark: metric.benchmark.name.clone(),
+            measure: metric.measure.name.clone(),
+            units: metric.measure.units.clone(),
+            baseline,
+            current,
+            allowed: false,
+        }
+    }
+
+    pub fn percent_change(&self) -> Option<f64> {
+        (self.baseline != 0.0).then(|| (self.current - self.baseline) / self.baseline.abs() * 100.0)
+    }
+}
+
+/// Decides whether a submitted report fails the run.
+pub struct Gate {
+    fail_on: FailOn,
+    allow: Vec<Regex>,
+}
+
+impl Gate {
+    pub fn new(fail_on: FailOn, allow_failure_for: &[String]) -> Result<Self> {
+        let allow = allow_failure_for
+            .iter()
+            .map(|p| Regex::new(p).with_context(|| format!("Invalid --allow-failure-for '{}'", p)))
+            .collect::<Result<_>>()?;
+        Ok(Self { fail_on, allow })
+    }
+
+    pub fn fail_on(&self) -> FailOn {
+        self.fail_on
+    }
+
+    /// Every benchmark and measure of `report` that fails the check, once
+    /// each, in the order the server returned them.
+    pub fn offenses(&self, report: &Report) -> Vec<Offense> {
+        let mut offenses: Vec<Offense> = Vec::new();
+        let mut push = |offense: Offense| {
+            let seen = offenses
+                .iter()
+                .any(|o| o.benchmark == offense.benchmark && o.measure == offense.measure);
+            if !seen {
+                offenses.push(offense);
+            }
+        };
+
+        for alert in &report.alerts {
+            if self.fail_on == FailOn::Regression
+                && !is_regression(&alert.metric, alert.percent_change)
+            {
+                continue;
+            }
+            push(Offense::new(
+                &alert.metric,
+                alert.baseline_value,
+                alert.current_value,
+            ));
+        }
+
+        if self.fail_on == FailOn::AnyChange {
+            for metric in &report.metrics {
+                let Some(baseline) = metric.baseline_value else {
+                    continue;
+                };
+                let lower = metric.lower.unwrap_or(metric.value);
+                let upper = metric.upper.unwrap_or(metric.value);
+                if baseline < lower || baseline > upper {
+                    push(Offense::new(metric, baseline, metric.value));
+                }
+            }
+        }
+
+        for offense in &mut offenses {
+            offense.allowed = self.allow.iter().any(|r| r.is_match(&offense.benchmark));
+        }
+        offenses
+    }
+
+    /// Prints the offending benchmarks; returns whether any of them fails
+    /// the run.
+    pub fn print_summary(&self, offenses: &[Offense]) -> bool {
+        let name = self
+            .fail_on
+            .to_possible_value()
+            .map(|v| v.get_name().to_string())
+            .unwrap_or_default();
+        let (allowed, failing): (Vec<_>, Vec<_>) = offenses.iter().partition(|o| o.allowed);
+
+        if !failing.is_empty() {
+            progress!(
+                "\nFAILED (--fail-on {}): {} benchmark result(s)",
+                name,
+                failing.len()
+            );
+            for offense in &failing {
+                print_offense(offense);
+            }
+        }
+        if !allowed.is_empty() {
+            progress!("\nAllowed to fail (--allow-failure-for):");
+            for offense in &allowed {
+                print_offense(offense);
+            }
+        }
+        if offenses.is_empty() {
+            progress!("\nPassed (--fail-on {})", name);
+        }
+
+        !failing.is_empty()
+    }
+}
+
+/// Measures where higher is better regress when they fall, everything else
+/// when it rises.
+fn is_regression(metric: &Metric, percent_change: f64) -> bool {
+    if metric.measure.higher_is_better {
+        percent_change < 0.0
+    } else {
+        percent_change > 0.0
+    }
+}
+
+fn print_offense(offense: &Offense) {
+    let units = offense
+        .units
+        .as_deref()
+        .map(|u| format!(" {}", u))
+        .unwrap_or_default();
+    let change = offense
+        .percent_change()
+        .map(|p| format!(" ({:+.1}%)", p))
+        .unwrap_or_default();
+    progress!(
+        "  {} ({}) : {:.2}{} -> {:.2}{}{}",
+        offense.benchmark,
+        offense.measure,
+        offense.baseline,
+        units,
+        offense.current,
+        units,
+        change
+    );
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::api::{Alert, Benchmark, Measure};
+
+    fn metric(benchmark: &str, measure: &str, units: &str, value: f64) -> Metric {
+        Metric {
+            value,
+            lower: None,
+            upper: None,
+            baseline_value: None,
+            benchmark: Benchmark {
+                id: String::new(),
+                name: benchmark.to_string(),
+            },
+            measure: Measure {
+                id: String::new(),
+                name: measure.to_string(),
+                units: Some(units.to_string()),
+                higher_is_better: false,
+            },
+        }
+    }
+
+    fn higher_is_better(mut metric: Metric) -> Metric {
+        metric.measure.higher_is_better = true;
+        metric
+    }
+
+    fn alert(metric: Metric, baseline: f64) -> Alert {
+        Alert {
+            id: String::new(),
+            baseline_value: baseline,
+            current_value: metric.value,
+            percent_change: (metric.value - baseline) / baseline * 100.0,
+            metric,
+        }
+    }
+
+    fn report() -> Report {
+        Report {
+            id: String::new(),
+            git_hash: None,
+            finalized: true,
+            alerts: vec![
+                // Slower: a regression
+                alert(metric("parse", "latency", "ns", 120.0), 100.0),
+                // Faster: only an alert
+                alert(metric("load", "latency", "ns", 50.0), 100.0),
+                // Less throughput: a regression
+                alert(
+                    higher_is_better(metric("copy", "throughput", "bytes/s", 80.0)),
+                    100.0,
+                ),
+            ],
+            metrics: vec![
+                Metric {
+                    lower: Some(95.0),
+                    upper: Some(105.0),
+                    baseline_value: Some(101.0),
+                    ..metric("stable", "latency", "ns", 100.0)
+                },
+                Metric {
+                    baseline_value: Some(1000.0),
+                    ..metric("count", "instructions", "count", 1001.0)
+                },
+                metric("new", "latency", "ns", 1.0),
+            ],
+            warnings: Vec::new(),
+        }
+    }
+
+    fn names(offenses: &[Offense]) -> Vec<&str> {
+        offenses.iter().map(|o| o.benchmark.as_str()).collect()
+    }
+
+    #[test]
+    fn test_fail_on_alert() {
+        let gate = Gate::new(FailOn::Alert, &[]).unwrap();
+        let offenses = gate.offenses(&report());
+        assert_eq!(names(&offenses), ["parse", "load", "copy"]);
+        assert_eq!(offenses[0].percent_change(), Some(20.0));
+        assert!(offenses.iter().all(|o| !o.allowed));
+    }
+
+    #[test]
+    fn test_fail_on_regression() {
+        let gate = Gate::new(FailOn::Regression, &[]).unwrap();
+        assert_eq!(names(&gate.offenses(&report())), ["parse", "copy"]);
+    }
+
+    #[test]
+    fn test_fail_on_any_change() {
+        let gate = Gate::new(FailOn::AnyChange, &[]).unwrap();
+        // `stable` is within its bounds; `count` has none and moved
+        assert_eq!(
+            names(&gate.offenses(&report())),
+            ["parse", "load", "copy", "count"]
+        );
+    }
+
+    #[test]
+    fn test_allow_failure_for() {
+        let gate = Gate::new(FailOn::Regression, &["^par".to_string()]).unwrap();
+        let offenses = gate.offenses(&report());
+        assert!(offenses[0].allowed);
+        assert!(!offenses[1].allowed);
+
+        assert!(Gate::new(FailOn::Alert, &["(".to_string()]).is_err());
+    }
+}
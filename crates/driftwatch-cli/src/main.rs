@@ -1,12 +1,24 @@
 use anyhow::Result;
-use clap::{Args, Parser, Subcommand};
+use clap::{error::ErrorKind, Args, CommandFactory, Parser, Subcommand};
+use std::process::ExitCode;
 use tracing_subscriber::{layer::SubscriberExt, util::SubscriberInitExt};
 
 mod adapters;
+mod aggregate;
 mod api;
+mod ci;
 mod commands;
+mod filter;
+mod fingerprint;
+mod gate;
+mod output;
+mod process;
+mod project_config;
+mod resources;
+mod retry;
+mod spool;
 
-use commands::{auth, config, project, run};
+use commands::{auth, config, flush, project, report, run, threshold};
 
 #[derive(Parser)]
 #[command(name = "driftwatch")]
@@ -16,13 +28,13 @@ struct Cli {
     #[command(subcommand)]
     command: Commands,
 
-    #[arg(
-        long,
-        env = "DRIFTWATCH_API_URL",
-        default_value = "http://localhost:4000",
-        global = true
-    )]
-    api_url: String,
+    /// API server URL (defaults to the profile's)
+    #[arg(long, env = "DRIFTWATCH_API_URL", global = true)]
+    api_url: Option<String>,
+
+    /// Named server profile from the config file
+    #[arg(long, env = "DRIFTWATCH_PROFILE", global = true)]
+    profile: Option<String>,
 }
 
 #[derive(Subcommand)]
@@ -40,7 +52,17 @@ enum Commands {
         #[command(subcommand)]
         command: project::ProjectCommands,
     },
-    Run(run::RunArgs),
+    Report {
+        #[command(subcommand)]
+        command: report::ReportCommands,
+    },
+    Threshold {
+        #[command(subcommand)]
+        command: threshold::ThresholdCommands,
+    },
+    Run(Box<run::RunArgs>),
+    /// Resubmit reports that `run` could not submit
+    Flush(flush::FlushArgs),
 }
 
 #[derive(Args)]
@@ -53,10 +75,17 @@ struct ServeArgs {
 }
 
 #[tokio::main]
-async fn main() -> Result<()> {
+async fn main() -> Result<ExitCode> {
     let cli = Cli::parse();
+    if let Commands::Run(ref args) = cli.command {
+        if let Some(conflict) = args.conflict() {
+            Cli::command()
+                .error(ErrorKind::ArgumentConflict, conflict)
+                .exit();
+        }
+    }
 
-    match cli.command {
+    let result = match cli.command {
         Commands::Serve(args) => {
             tracing_subscriber::registry()
                 .with(
@@ -70,21 +99,36 @@ async fn main() -> Result<()> {
         }
         Commands::Auth { command } => {
             init_cli_tracing();
-            auth::handle(command).await
+            auth::handle(command, cli.profile.as_deref()).await
         }
         Commands::Config { command } => {
             init_cli_tracing();
-            config::handle(command).await
+            config::handle(command, cli.profile.as_deref()).await
         }
         Commands::Project { command } => {
             init_cli_tracing();
-            project::handle(command, &cli.api_url).await
+            project::handle(command, cli.api_url.as_deref(), cli.profile.as_deref()).await
+        }
+        Commands::Threshold { command } => {
+            init_cli_tracing();
+            threshold::handle(command, cli.api_url.as_deref(), cli.profile.as_deref()).await
+        }
+        Commands::Flush(args) => {
+            init_cli_tracing();
+            flush::handle(args, cli.api_url.as_deref(), cli.profile.as_deref()).await
+        }
+        // Commands with an exit code of their own, see `--fail-on`
+        Commands::Report { command } => {
+            init_cli_tracing();
+            return report::handle(command, cli.api_url.as_deref(), cli.profile.as_deref()).await;
         }
         Commands::Run(args) => {
             init_cli_tracing();
-            run::handle(args, &cli.api_url).await
+            return run::handle(*args, cli.api_url.as_deref(), cli.profile.as_deref()).await;
         }
-    }
+    };
+
+    result.map(|()| ExitCode::SUCCESS)
 }
 
 fn init_cli_tracing() {
@@ -0,0 +1,552 @@
+use clap::ValueEnum;
+use serde::Serialize;
+use std::fmt::Write as _;
+use std::sync::atomic::{AtomicBool, Ordering};
+
+use crate::api::{MetricInput, Report};
+use crate::gate::{FailOn, Offense};
+
+/// Version of the `--format json` document; bumped on any incompatible change.
+pub const SCHEMA_VERSION: u32 = 1;
+
+static PROGRESS_TO_STDERR: AtomicBool = AtomicBool::new(false);
+
+/// Moves the human-readable progress of `run` to stderr, leaving stdout to
+/// the `--format` output.
+pub fn progress_to_stderr() {
+    PROGRESS_TO_STDERR.store(true, Ordering::Relaxed);
+}
+
+pub fn progress_on_stderr() -> bool {
+    PROGRESS_TO_STDERR.load(Ordering::Relaxed)
+}
+
+/// `println!` for progress messages, see [`progress_to_stderr`].
+macro_rules! progress {
+    () => {
+        if $crate::output::progress_on_stderr() {
+            eprintln!()
+        } else {
+            println!()
+        }
+    };
+    ($($arg:tt)*) => {
+        if $crate::output::progress_on_stderr() {
+            eprintln!($($arg)*)
+        } else {
+            println!($($arg)*)
+        }
+    };
+}
+pub(crate) use progress;
+
+/// What `driftwatch run` prints once the results are in.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum Format {
+    /// Human-readable progress and results
+    Text,
+    /// A schema-versioned JSON document
+    Json,
+    /// A markdown table, as written to `$GITHUB_STEP_SUMMARY`
+    Markdown,
+    /// JUnit XML with one test case per benchmark
+    Junit,
+}
+
+/// The results of a run and the server's verdict on them.
+#[derive(Debug, Serialize)]
+pub struct RunSummary {
+    pub schema_version: u32,
+    pub project: String,
+    pub branch: String,
+    pub testbed: String,
+    pub git_hash: Option<String>,
+    pub pr_number: Option<i32>,
+    pub ci_provider: Option<String>,
+    pub submission: Submission,
+    /// The report the results were submitted or appended to
+    pub report_id: Option<String>,
+    pub results: Vec<BenchmarkResult>,
+    /// Only for a `checked` submission
+    pub verdict: Option<Verdict>,
+}
+
+/// What became of the results of a run.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Submission {
+    /// Nothing was submitted: the command failed or no results were found
+    Skipped,
+    DryRun,
+    /// Added to a report opened with `report open`, which is checked when it
+    /// is finalized
+    Appended,
+    /// Kept for `driftwatch flush` because the server could not be reached
+    Spooled,
+    /// Submitted, and checked by the gate
+    Checked,
+}
+
+#[derive(Debug, Serialize)]
+pub struct BenchmarkResult {
+    pub benchmark: String,
+    pub measure: String,
+    pub units: Option<String>,
+    pub value: f64,
+    pub lower: Option<f64>,
+    pub upper: Option<f64>,
+    pub baseline: Option<f64>,
+    pub percent_change: Option<f64>,
+    pub alert: bool,
+    pub status: Status,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Status {
+    Passed,
+    Failed,
+    /// Failed, but matched by `--allow-failure-for`
+    AllowedFailure,
+    /// Not submitted, so not checked
+    Unchecked,
+}
+
+#[derive(Debug, Serialize)]
+pub struct Verdict {
+    /// The check applied; `alert` when `--fail-on` was not given
+    pub fail_on: FailOn,
+    /// Whether `--fail-on` was given, i.e. whether a failure fails the run
+    pub enforced: bool,
+    pub passed: bool,
+    pub failed: usize,
+    pub allowed_failures: usize,
+    pub alerts: usize,
+}
+
+impl BenchmarkResult {
+    /// A result that was not submitted.
+    pub fn unchecked(metric: &MetricInput) -> Self {
+        Self {
+            benchmark: metric.benchmark.clone(),
+            measure: metric.measure.clone(),
+            units: metric.units.clone(),
+            value: metric.value,
+            lower: metric.lower_value,
+            upper: metric.upper_value,
+            baseline: None,
+            percent_change: None,
+            alert: false,
+            status: Status::Unchecked,
+        }
+    }
+
+    /// The submitted `metrics` with their baselines, alerts and statuses from
+    /// the server's `report` and the gate's `offenses`.
+    pub fn checked(metrics: &[MetricInput], report: &Report, offenses: &[Offense]) -> Vec<Self> {
+        metrics
+            .iter()
+            .map(|metric| {
+                let is = |benchmark: &str, measure: &str| {
+                    benchmark == metric.benchmark && measure == metric.measure
+                };
+                let baseline = report
+                    .metrics
+                    .iter()
+                    .find(|m| is(&m.benchmark.name, &m.measure.name))
+                    .and_then(|m| m.baseline_value);
+                let alert = report
+                    .alerts
+                    .iter()
+                    .any(|a| is(&a.metric.benchmark.name, &a.metric.measure.name));
+                let status = match offenses.iter().find(|o| is(&o.benchmark, &o.measure)) {
+                    Some(o) if o.allowed => Status::AllowedFailure,
+                    Some(_) => Status::Failed,
+                    None => Status::Passed,
+                };
+                Self {
+                    baseline,
+                    percent_change: baseline
+                        .filter(|b| *b != 0.0)
+                        .map(|b| (metric.value - b) / b.abs() * 100.0),
+                    alert,
+                    status,
+                    ..Self::unchecked(metric)
+                }
+            })
+            .collect()
+    }
+}
+
+impl Verdict {
+    pub fn new(fail_on: FailOn, enforced: bool, report: &Report, offenses: &[Offense]) -> Self {
+        let allowed_failures = offenses.iter().filter(|o| o.allowed).count();
+        let failed = offenses.len() - allowed_failures;
+        Self {
+            fail_on,
+            enforced,
+            passed: failed == 0,
+            failed,
+            allowed_failures,
+            alerts: report.alerts.len(),
+        }
+    }
+}
+
+impl RunSummary {
+    /// Renders the summary in a machine-readable `format`; `None` for text,
+    /// which is printed as the run goes.
+    pub fn render(&self, format: Format) -> Option<String> {
+        match format {
+            Format::Text => None,
+            Format::Json => Some(self.to_json()),
+            Format::Markdown => Some(self.to_markdown()),
+            Format::Junit => Some(self.to_junit()),
+        }
+    }
+
+    pub fn to_json(&self) -> String {
+        let mut json = serde_json::to_string_pretty(self).expect("summary is serializable");
+        json.push('\n');
+        json
+    }
+
+    pub fn to_markdown(&self) -> String {
+        let mut md = format!("### Driftwatch: {}\n\n", self.project);
+        let mut context = vec![
+            format!("Branch `{}`", self.branch),
+            format!("Testbed `{}`", self.testbed),
+        ];
+        if let Some(ref hash) = self.git_hash {
+            context.push(format!("Commit `{}`", short_hash(hash)));
+        }
+        if let Some(pr) = self.pr_number {
+            context.push(format!("PR #{}", pr));
+        }
+        if let Some(ref id) = self.report_id {
+            context.push(format!("Report `{}`", id));
+        }
+        let _ = writeln!(md, "{}\n", context.join(" · "));
+
+        if self.results.is_empty() {
+            md.push_str("No benchmark results.\n");
+        } else {
+            md.push_str("| Benchmark | Measure | Value | Baseline | Change | Status |\n");
+            md.push_str("| --- | --- | ---: | ---: | ---: | --- |\n");
+            for r in &self.results {
+                let units = r.units.as_deref();
+                let _ = writeln!(
+                    md,
+                    "| {} | {} | {} | {} | {} | {} |",
+                    escape_cell(&r.benchmark),
+                    escape_cell(&r.measure),
+                    with_units(r.value, units),
+                    r.baseline.map(|b| with_units(b, units)).unwrap_or_default(),
+                    r.percent_change
+                        .map(|p| format!("{:+.1}%", p))
+                        .unwrap_or_default(),
+                    status_label(r),
+                );
+            }
+        }
+
+        md.push('\n');
+        match (self.submission, &self.verdict) {
+            (Submission::Checked, Some(v)) => {
+                let fail_on = fail_on_name(v.fail_on);
+                if v.passed {
+                    let _ = writeln!(md, "**Passed** (`--fail-on {}`)", fail_on);
+                } else {
+                    let _ = writeln!(
+                        md,
+                        "**Failed** (`--fail-on {}`): {} benchmark result(s)",
+                        fail_on, v.failed
+                    );
+                }
+            }
+            (Submission::DryRun, _) => md.push_str("_Dry run: results were not submitted._\n"),
+            (Submission::Appended, _) => md.push_str(
+                "_Appended to an open report: results are checked when it is finalized._\n",
+            ),
+            (Submission::Spooled, _) => md.push_str(
+                "_Not checked: the server could not be reached, so the report was spooled \
+                 for `driftwatch flush`._\n",
+            ),
+            _ => md.push_str("_No results were submitted._\n"),
+        }
+        md
+    }
+
+    pub fn to_junit(&self) -> String {
+        // One test case per benchmark, in first-seen order
+        let mut cases: Vec<(&str, Vec<&BenchmarkResult>)> = Vec::new();
+        for r in &self.results {
+            match cases.iter_mut().find(|(name, _)| *name == r.benchmark) {
+                Some((_, results)) => results.push(r),
+                None => cases.push((&r.benchmark, vec![r])),
+            }
+        }
+        let failures = cases
+            .iter()
+            .filter(|(_, results)| results.iter().any(|r| r.status == Status::Failed))
+            .count();
+        let unchecked = self.submission.unchecked_reason();
+        let skipped = if unchecked.is_some() { cases.len() } else { 0 };
+
+        let suite = xml_escape(&format!("driftwatch: {}", self.project));
+        let mut xml = String::from("<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n");
+        let _ = writeln!(
+            xml,
+            "<testsuites name=\"{}\" tests=\"{}\" failures=\"{}\" skipped=\"{}\">",
+            suite,
+            cases.len(),
+            failures,
+            skipped
+        );
+        let _ = writeln!(
+            xml,
+            "  <testsuite name=\"{}\" tests=\"{}\" failures=\"{}\" skipped=\"{}\">",
+            suite,
+            cases.len(),
+            failures,
+            skipped
+        );
+        for (name, results) in &cases {
+            let _ = writeln!(
+                xml,
+                "    <testcase name=\"{}\" classname=\"{}\">",
+                xml_escape(name),
+                xml_escape(&self.project)
+            );
+            let failed: Vec<_> = results
+                .iter()
+                .filter(|r| r.status == Status::Failed)
+                .map(|r| describe(r))
+                .collect();
+            if !failed.is_empty() {
+                let _ = writeln!(
+                    xml,
+                    "      <failure message=\"{}\">{}</failure>",
+                    xml_escape(&failed.join("; ")),
+                    xml_escape(&failed.join("\n"))
+                );
+            } else if let Some(reason) = unchecked {
+                let _ = writeln!(xml, "      <skipped message=\"{}\"/>", reason);
+            }
+            let out: Vec<_> = results
+                .iter()
+                .map(|r| format!("{}{}", describe(r), status_note(r)))
+                .collect();
+            let _ = writeln!(
+                xml,
+                "      <system-out>{}</system-out>",
+                xml_escape(&out.join("\n"))
+            );
+            xml.push_str("    </testcase>\n");
+        }
+        xml.push_str("  </testsuite>\n</testsuites>\n");
+        xml
+    }
+}
+
+impl Submission {
+    /// Why the results were not checked, for JUnit; `None` if they were.
+    fn unchecked_reason(self) -> Option<&'static str> {
+        match self {
+            Submission::Skipped => Some("not submitted"),
+            Submission::DryRun => Some("dry run"),
+            Submission::Appended => {
+                Some("appended to an open report, checked when it is finalized")
+            }
+            Submission::Spooled => Some("spooled, not checked"),
+            Submission::Checked => None,
+        }
+    }
+}
+
+fn fail_on_name(fail_on: FailOn) -> String {
+    fail_on
+        .to_possible_value()
+        .map(|v| v.get_name().to_string())
+        .unwrap_or_default()
+}
+
+fn short_hash(hash: &str) -> &str {
+    hash.get(..7).unwrap_or(hash)
+}
+
+fn with_units(value: f64, units: Option<&str>) -> String {
+    match units {
+        Some(u) => format!("{:.2} {}", value, u),
+        None => format!("{:.2}", value),
+    }
+}
+
+fn status_label(result: &BenchmarkResult) -> &'static str {
+    match result.status {
+        Status::Passed if result.alert => "⚠️ alert",
+        Status::Passed => "✅",
+        Status::Failed => "❌ failed",
+        Status::AllowedFailure => "⚠️ allowed to fail",
+        Status::Unchecked => "",
+    }
+}
+
+fn status_note(result: &BenchmarkResult) -> &'static str {
+    match result.status {
+        Status::AllowedFailure => " (allowed to fail)",
+        Status::Passed if result.alert => " (alert)",
+        _ => "",
+    }
+}
+
+/// `measure: value [baseline -> value (+x%)]` for JUnit messages.
+fn describe(result: &BenchmarkResult) -> String {
+    let units = result.units.as_deref();
+    let mut line = format!("{}: {}", result.measure, with_units(result.value, units));
+    if let Some(baseline) = result.baseline {
+        let _ = write!(line, " (baseline {}", with_units(baseline, units));
+        if let Some(p) = result.percent_change {
+            let _ = write!(line, ", {:+.1}%", p);
+        }
+        line.push(')');
+    }
+    line
+}
+
+fn escape_cell(text: &str) -> String {
+    text.replace('|', "\\|")
+}
+
+fn xml_escape(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&apos;"),
+            // Not representable in XML 1.0
+            c if c.is_control() && !matches!(c, '\n' | '\t' | '\r') => {}
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn result(benchmark: &str, measure: &str, status: Status) -> BenchmarkResult {
+        BenchmarkResult {
+            benchmark: benchmark.to_string(),
+            measure: measure.to_string(),
+            units: Some("ns".to_string()),
+            value: 120.0,
+            lower: Some(110.0),
+            upper: Some(130.0),
+            baseline: Some(100.0),
+            percent_change: Some(20.0),
+            alert: status != Status::Passed,
+            status,
+        }
+    }
+
+    fn summary() -> RunSummary {
+        RunSummary {
+            schema_version: SCHEMA_VERSION,
+            project: "demo".to_string(),
+            branch: "main".to_string(),
+            testbed: "linux".to_string(),
+            git_hash: Some("0123456789abcdef".to_string()),
+            pr_number: Some(7),
+            ci_provider: None,
+            submission: Submission::Checked,
+            report_id: Some("r1".to_string()),
+            results: vec![
+                result("parse<&>", "latency", Status::Failed),
+                result("parse<&>", "instructions", Status::Passed),
+                result("a|b", "latency", Status::AllowedFailure),
+            ],
+            verdict: Some(Verdict {
+                fail_on: FailOn::Regression,
+                enforced: true,
+                passed: false,
+                failed: 1,
+                allowed_failures: 1,
+                alerts: 2,
+            }),
+        }
+    }
+
+    #[test]
+    fn test_json_is_versioned_and_stable() {
+        let json: serde_json::Value = serde_json::from_str(&summary().to_json()).unwrap();
+        assert_eq!(json["schema_version"], 1);
+        assert_eq!(json["submission"], "checked");
+        assert_eq!(json["report_id"], "r1");
+        assert_eq!(json["results"][0]["status"], "failed");
+        assert_eq!(json["results"][2]["status"], "allowed_failure");
+        assert_eq!(json["results"][0]["percent_change"], 20.0);
+        assert_eq!(json["verdict"]["fail_on"], "regression");
+        assert_eq!(json["verdict"]["passed"], false);
+    }
+
+    #[test]
+    fn test_markdown_table() {
+        let md = summary().to_markdown();
+        assert!(md.contains("Branch `main` · Testbed `linux` · Commit `0123456`"));
+        assert!(md.contains("| parse<&> | latency | 120.00 ns | 100.00 ns | +20.0% | ❌ failed |"));
+        assert!(md.contains("| a\\|b | latency |"));
+        assert!(md.contains("**Failed** (`--fail-on regression`): 1 benchmark result(s)"));
+
+        let unchecked = |submission| RunSummary {
+            submission,
+            verdict: None,
+            ..summary()
+        };
+        assert!(unchecked(Submission::DryRun)
+            .to_markdown()
+            .contains("_Dry run"));
+        assert!(unchecked(Submission::Appended)
+            .to_markdown()
+            .contains("_Appended to an open report"));
+        assert!(unchecked(Submission::Spooled)
+            .to_markdown()
+            .contains("spooled for `driftwatch flush`"));
+        assert!(!unchecked(Submission::Spooled)
+            .to_markdown()
+            .contains("Dry run"));
+    }
+
+    #[test]
+    fn test_junit_one_case_per_benchmark() {
+        let xml = summary().to_junit();
+        assert_eq!(xml.matches("<testcase ").count(), 2);
+        assert!(xml.contains("tests=\"2\" failures=\"1\" skipped=\"0\""));
+        assert!(xml.contains("<testcase name=\"parse&lt;&amp;&gt;\" classname=\"demo\">"));
+        assert!(
+            xml.contains("<failure message=\"latency: 120.00 ns (baseline 100.00 ns, +20.0%)\">")
+        );
+        // Allowed failures pass
+        assert_eq!(xml.matches("<failure").count(), 1);
+        assert!(xml.contains("(allowed to fail)"));
+
+        let spooled = RunSummary {
+            submission: Submission::Spooled,
+            verdict: None,
+            results: vec![result("parse", "latency", Status::Unchecked)],
+            ..summary()
+        };
+        let xml = spooled.to_junit();
+        assert!(xml.contains("skipped=\"1\""));
+        assert!(xml.contains("<skipped message=\"spooled, not checked\"/>"));
+    }
+
+    #[test]
+    fn test_xml_escape() {
+        assert_eq!(xml_escape("a<b>&\"c'\u{1}"), "a&lt;b&gt;&amp;&quot;c&apos;");
+    }
+}
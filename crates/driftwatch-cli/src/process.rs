@@ -0,0 +1,360 @@
+use anyhow::{Context, Result};
+use std::io::{self, Read, Write};
+use std::path::PathBuf;
+use std::process::{Child, Command, ExitStatus, Stdio};
+use std::sync::mpsc;
+use std::thread::{self, JoinHandle};
+use std::time::{Duration, Instant};
+
+use crate::output::{self, progress};
+use crate::resources::{self, ResourceUsage};
+
+/// Exit code after `--timeout`, as with coreutils' `timeout`.
+pub const TIMEOUT_EXIT_CODE: u8 = 124;
+
+#[derive(Debug, Clone, Default)]
+pub struct RunOptions {
+    /// Kill the command and everything it started after this long
+    pub timeout: Option<Duration>,
+    pub measure_resources: bool,
+    /// Directory the command runs in, the current one when unset
+    pub current_dir: Option<PathBuf>,
+}
+
+/// What a finished benchmark command printed and how it exited.
+pub struct CommandOutput {
+    pub stdout: String,
+    pub stderr: String,
+    pub status: ExitStatus,
+    /// Killed by `--timeout`
+    pub timed_out: Option<Duration>,
+    pub resources: Option<ResourceUsage>,
+}
+
+impl CommandOutput {
+    pub fn success(&self) -> bool {
+        self.status.success() && self.timed_out.is_none()
+    }
+
+    /// Exit code to pass on: the command's own, [`TIMEOUT_EXIT_CODE`] after a
+    /// timeout, or 128 + N when a signal N killed it, as shells report it.
+    pub fn exit_code(&self) -> u8 {
+        if self.timed_out.is_some() {
+            return TIMEOUT_EXIT_CODE;
+        }
+        if let Some(code) = self.status.code() {
+            return u8::try_from(code).ok().filter(|&c| c != 0).unwrap_or(1);
+        }
+        #[cfg(unix)]
+        {
+            use std::os::unix::process::ExitStatusExt;
+            if let Some(signal) = self.status.signal() {
+                return u8::try_from(128 + signal).unwrap_or(1);
+            }
+        }
+        1
+    }
+
+    /// Why the command failed, e.g. `exited with code 2`.
+    pub fn failure(&self) -> String {
+        if let Some(timeout) = self.timed_out {
+            return format!("timed out after {}", format_duration(timeout));
+        }
+        match self.status.code() {
+            Some(code) => format!("exited with code {}", code),
+            None => format!("was killed ({})", self.status),
+        }
+    }
+}
+
+/// Runs `command` through the shell, copying its output to the terminal as it
+/// is printed while also capturing it.
+pub fn run(command: &[String], options: &RunOptions) -> Result<CommandOutput> {
+    let cmd = command.join(" ");
+    let mut process = if cfg!(target_os = "windows") {
+        let mut process = Command::new("cmd");
+        process.args(["/C", &cmd]);
+        process
+    } else {
+        let mut process = Command::new("sh");
+        process.args(["-c", &cmd]);
+        process
+    };
+    process
+        .stdin(Stdio::null())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped());
+    if let Some(ref dir) = options.current_dir {
+        process.current_dir(dir);
+    }
+    // Its own process group, so a timeout also kills what the shell started
+    #[cfg(unix)]
+    std::os::unix::process::CommandExt::process_group(&mut process, 0);
+
+    let started = Instant::now();
+    let mut child = process
+        .spawn()
+        .context("Failed to execute benchmark command")?;
+    #[cfg(unix)]
+    let _forwarding = signals::forward_to(child.id());
+
+    // Progress may be on stderr to keep stdout for --format output
+    let stdout = tee(
+        child.stdout.take().expect("stdout is piped"),
+        output::progress_on_stderr(),
+    );
+    let stderr = tee(child.stderr.take().expect("stderr is piped"), true);
+    let watchdog = options.timeout.map(|t| Watchdog::start(&child, t));
+
+    let (status, resources) = if options.measure_resources {
+        let (status, usage) = resources::wait(&child, started)?;
+        (status, Some(usage))
+    } else {
+        let status = child
+            .wait()
+            .context("Failed to wait for benchmark command")?;
+        (status, None)
+    };
+    let timed_out = watchdog
+        .is_some_and(Watchdog::finish)
+        .then_some(options.timeout)
+        .flatten();
+
+    let captured = |reader: JoinHandle<io::Result<Vec<u8>>>| -> Result<String> {
+        let bytes = reader
+            .join()
+            .expect("output reader panicked")
+            .context("Failed to read benchmark command output")?;
+        Ok(String::from_utf8_lossy(&bytes).into_owned())
+    };
+    let (stdout, stderr) = (captured(stdout)?, captured(stderr)?);
+    // Keep what driftwatch prints next off the command's last line
+    if !stdout.is_empty() && !stdout.ends_with('\n') {
+        progress!();
+    }
+    Ok(CommandOutput {
+        stdout,
+        stderr,
+        status,
+        timed_out,
+        resources,
+    })
+}
+
+/// Copies `from` to stderr or stdout until it closes and returns all of it.
+fn tee(mut from: impl Read + Send + 'static, to_stderr: bool) -> JoinHandle<io::Result<Vec<u8>>> {
+    thread::spawn(move || {
+        let mut captured = Vec::new();
+        let mut buf = [0; 8192];
+        loop {
+            let n = match from.read(&mut buf) {
+                Ok(0) => return Ok(captured),
+                Ok(n) => n,
+                Err(e) if e.kind() == io::ErrorKind::Interrupted => continue,
+                Err(e) => return Err(e),
+            };
+            captured.extend_from_slice(&buf[..n]);
+            // A closed terminal must not stop the capture
+            let _ = if to_stderr {
+                let mut stderr = io::stderr().lock();
+                stderr.write_all(&buf[..n]).and_then(|()| stderr.flush())
+            } else {
+                let mut stdout = io::stdout().lock();
+                stdout.write_all(&buf[..n]).and_then(|()| stdout.flush())
+            };
+        }
+    })
+}
+
+/// Kills the command's process tree unless it exits within the timeout.
+struct Watchdog {
+    done: mpsc::Sender<()>,
+    thread: JoinHandle<bool>,
+}
+
+impl Watchdog {
+    fn start(child: &Child, timeout: Duration) -> Self {
+        let pid = child.id();
+        let (done, exited) = mpsc::channel();
+        let thread = thread::spawn(move || match exited.recv_timeout(timeout) {
+            Err(mpsc::RecvTimeoutError::Timeout) => {
+                kill_tree(pid);
+                true
+            }
+            _ => false,
+        });
+        Self { done, thread }
+    }
+
+    /// Stops the watchdog once the command has exited; whether it had
+    /// killed it.
+    fn finish(self) -> bool {
+        drop(self.done);
+        self.thread.join().unwrap_or(false)
+    }
+}
+
+#[cfg(unix)]
+fn kill_tree(pid: u32) {
+    // SAFETY: signals the process group the command leads
+    unsafe {
+        libc::kill(-(pid as libc::pid_t), libc::SIGKILL);
+    }
+}
+
+#[cfg(not(unix))]
+fn kill_tree(pid: u32) {
+    let _ = Command::new("taskkill")
+        .args(["/T", "/F", "/PID", &pid.to_string()])
+        .status();
+}
+
+/// The command runs in a process group of its own, which the terminal's
+/// Ctrl-C and a CI runner's termination don't reach; these pass them on
+/// while it runs.
+#[cfg(unix)]
+mod signals {
+    use std::sync::atomic::{AtomicI32, Ordering};
+
+    const FORWARDED: [libc::c_int; 2] = [libc::SIGINT, libc::SIGTERM];
+
+    static GROUP: AtomicI32 = AtomicI32::new(0);
+
+    extern "C" fn forward(signal: libc::c_int) {
+        let group = GROUP.load(Ordering::Relaxed);
+        if group > 0 {
+            // SAFETY: kill is async-signal-safe
+            unsafe {
+                libc::kill(-group, signal);
+            }
+        }
+    }
+
+    /// Restores the previous handlers when dropped.
+    pub struct Forwarding {
+        previous: [libc::sighandler_t; 2],
+    }
+
+    pub fn forward_to(group: u32) -> Forwarding {
+        GROUP.store(group as i32, Ordering::Relaxed);
+        let handler = forward as extern "C" fn(libc::c_int) as libc::sighandler_t;
+        // SAFETY: the handler only calls async-signal-safe functions
+        let previous = FORWARDED.map(|signal| unsafe { libc::signal(signal, handler) });
+        Forwarding { previous }
+    }
+
+    impl Drop for Forwarding {
+        fn drop(&mut self) {
+            for (signal, previous) in FORWARDED.into_iter().zip(self.previous) {
+                // SAFETY: reinstates the handler that was replaced
+                unsafe {
+                    libc::signal(signal, previous);
+                }
+            }
+            GROUP.store(0, Ordering::Relaxed);
+        }
+    }
+}
+
+/// Parses a duration such as `90`, `90s`, `40m` or `2h`; bare numbers are
+/// seconds.
+pub fn parse_duration(s: &str) -> Result<Duration, String> {
+    let s = s.trim();
+    let (number, unit) = match s.find(|c: char| !c.is_ascii_digit()) {
+        Some(i) => s.split_at(i),
+        None => (s, "s"),
+    };
+    let seconds = match unit {
+        "s" => 1,
+        "m" => 60,
+        "h" => 3600,
+        _ => return Err(format!("invalid unit '{}', expected s, m or h", unit)),
+    };
+    let number: u64 = number
+        .parse()
+        .map_err(|_| format!("invalid duration '{}', expected e.g. 90s, 40m or 2h", s))?;
+    if number == 0 {
+        return Err("must be greater than zero".to_string());
+    }
+    Ok(Duration::from_secs(number * seconds))
+}
+
+fn format_duration(duration: Duration) -> String {
+    let secs = duration.as_secs();
+    if secs > 0 && secs.is_multiple_of(3600) {
+        format!("{}h", secs / 3600)
+    } else if secs > 0 && secs.is_multiple_of(60) {
+        format!("{}m", secs / 60)
+    } else {
+        format!("{}s", secs)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sh(script: &str) -> Vec<String> {
+        vec![script.to_string()]
+    }
+
+    #[test]
+    fn test_parse_duration() {
+        assert_eq!(parse_duration("90"), Ok(Duration::from_secs(90)));
+        assert_eq!(parse_duration("90s"), Ok(Duration::from_secs(90)));
+        assert_eq!(parse_duration("40m"), Ok(Duration::from_secs(2400)));
+        assert_eq!(parse_duration("2h"), Ok(Duration::from_secs(7200)));
+        assert!(parse_duration("0").is_err());
+        assert!(parse_duration("5d").is_err());
+        assert!(parse_duration("m").is_err());
+        assert_eq!(format_duration(Duration::from_secs(2400)), "40m");
+        assert_eq!(format_duration(Duration::from_secs(90)), "90s");
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_run_captures_output_and_exit_code() {
+        let output = run(
+            &sh("echo out; echo err >&2; exit 3"),
+            &RunOptions::default(),
+        )
+        .unwrap();
+        assert_eq!(output.stdout, "out\n");
+        assert_eq!(output.stderr, "err\n");
+        assert!(!output.success());
+        assert_eq!(output.exit_code(), 3);
+        assert_eq!(output.failure(), "exited with code 3");
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_run_in_current_dir() {
+        let dir = tempfile::tempdir().unwrap();
+        let options = RunOptions {
+            current_dir: Some(dir.path().to_path_buf()),
+            ..Default::default()
+        };
+        let output = run(&sh("pwd -P"), &options).unwrap();
+        assert_eq!(
+            output.stdout.trim(),
+            dir.path().canonicalize().unwrap().to_str().unwrap()
+        );
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_run_timeout_kills_process_group() {
+        let options = RunOptions {
+            timeout: Some(Duration::from_secs(1)),
+            ..Default::default()
+        };
+        let started = Instant::now();
+        // The shell's child holds the pipes open; it has to die as well
+        let output = run(&sh("echo partial; sleep 30; echo never"), &options).unwrap();
+        assert!(started.elapsed() < Duration::from_secs(10));
+        assert_eq!(output.stdout, "partial\n");
+        assert_eq!(output.timed_out, Some(Duration::from_secs(1)));
+        assert_eq!(output.exit_code(), TIMEOUT_EXIT_CODE);
+        assert_eq!(output.failure(), "timed out after 1s");
+    }
+}
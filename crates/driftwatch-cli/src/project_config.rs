@@ -0,0 +1,180 @@
+use anyhow::{Context, Result};
+use serde::Deserialize;
+use std::collections::{BTreeMap, HashMap};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::adapters::custom::CustomAdapterConfig;
+
+pub const PROJECT_CONFIG_FILE: &str = "driftwatch.toml";
+
+/// Repository-local settings read from `driftwatch.toml`.
+///
+/// Everything here is a default for `driftwatch run`; flags given on the
+/// command line win.
+#[derive(Debug, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ProjectConfig {
+    /// Where the config was loaded from, if anywhere
+    #[serde(skip)]
+    pub path: Option<PathBuf>,
+    /// Project slug
+    pub project: Option<String>,
+    pub testbed: Option<String>,
+    pub adapter: Option<String>,
+    /// Flamegraph SVGs uploaded with every report, relative to this file
+    #[serde(default)]
+    pub flamegraphs: Vec<PathBuf>,
+    /// Named benchmark commands, run with `driftwatch run --bench <name>`
+    #[serde(default)]
+    pub commands: BTreeMap<String, String>,
+    /// Adapter measure name -> measure name submitted to the server
+    #[serde(default)]
+    pub measures: HashMap<String, String>,
+    /// Only submit benchmarks matching one of these regexes
+    #[serde(default)]
+    pub include: Vec<String>,
+    /// Never submit benchmarks matching one of these regexes
+    #[serde(default)]
+    pub exclude: Vec<String>,
+    /// `pattern=>replacement` rules for the names benchmarks are submitted as
+    #[serde(default)]
+    pub rename: Vec<String>,
+    /// Definition of the `custom` adapter
+    pub custom: Option<CustomAdapterConfig>,
+}
+
+impl ProjectConfig {
+    /// Loads the nearest `driftwatch.toml` in the current directory or one
+    /// of its parents, returning the default config when there is none.
+    pub fn discover() -> Result<Self> {
+        let cwd = std::env::current_dir().context("Could not determine current directory")?;
+        match find_config_file(&cwd) {
+            Some(path) => Self::load(&path),
+            None => Ok(Self::default()),
+        }
+    }
+
+    pub fn load(path: &Path) -> Result<Self> {
+        let config_str = fs::read_to_string(path)
+            .with_context(|| format!("Failed to read {}", path.display()))?;
+        let mut config: Self =
+            toml::from_str(&config_str).with_context(|| format!("Invalid {}", path.display()))?;
+        if let Some(dir) = path.parent() {
+            for flamegraph in &mut config.flamegraphs {
+                *flamegraph = dir.join(&*flamegraph);
+            }
+        }
+        config.path = Some(path.to_path_buf());
+        Ok(config)
+    }
+
+    /// Directory of the loaded `driftwatch.toml`, where its `[commands]` run.
+    pub fn dir(&self) -> Option<&Path> {
+        self.path.as_deref().and_then(Path::parent)
+    }
+
+    pub fn command(&self, name: &str) -> Result<&str> {
+        self.commands
+            .get(name)
+            .map(String::as_str)
+            .with_context(|| {
+                let known: Vec<&str> = self.commands.keys().map(String::as_str).collect();
+                if known.is_empty() {
+                    format!(
+                        "No benchmark command '{}': driftwatch.toml has no [commands]",
+                        name
+                    )
+                } else {
+                    format!(
+                        "No benchmark command '{}' in driftwatch.toml (available: {})",
+                        name,
+                        known.join(", ")
+                    )
+                }
+            })
+    }
+
+    /// Measure name to submit for a measure reported by an adapter.
+    pub fn measure_name<'a>(&'a self, measure: &'a str) -> &'a str {
+        self.measures
+            .get(measure)
+            .map(String::as_str)
+            .unwrap_or(measure)
+    }
+}
+
+fn find_config_file(start: &Path) -> Option<PathBuf> {
+    start
+        .ancestors()
+        .map(|dir| dir.join(PROJECT_CONFIG_FILE))
+        .find(|path| path.is_file())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_discover_walks_up() {
+        let dir = tempfile::tempdir().unwrap();
+        let nested = dir.path().join("a/b");
+        fs::create_dir_all(&nested).unwrap();
+        assert_eq!(find_config_file(&nested), None);
+
+        fs::write(dir.path().join(PROJECT_CONFIG_FILE), "project = \"demo\"\n").unwrap();
+        assert_eq!(
+            find_config_file(&nested),
+            Some(dir.path().join(PROJECT_CONFIG_FILE))
+        );
+    }
+
+    #[test]
+    fn test_load_project_config() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join(PROJECT_CONFIG_FILE);
+        fs::write(
+            &path,
+            r#"
+project = "demo"
+testbed = "ci-linux"
+adapter = "criterion"
+flamegraphs = ["target/flamegraph.svg"]
+include = ["^parse/"]
+rename = ["^parser/=>parse/"]
+
+[commands]
+micro = "cargo bench --bench micro"
+
+[measures]
+latency = "wall-time"
+"#,
+        )
+        .unwrap();
+
+        let config = ProjectConfig::load(&path).unwrap();
+        assert_eq!(config.path.as_deref(), Some(path.as_path()));
+        assert_eq!(config.project.as_deref(), Some("demo"));
+        assert_eq!(
+            config.flamegraphs,
+            [dir.path().join("target/flamegraph.svg")]
+        );
+        assert_eq!(
+            config.command("micro").unwrap(),
+            "cargo bench --bench micro"
+        );
+        assert!(config.command("macro").is_err());
+        assert_eq!(config.measure_name("latency"), "wall-time");
+        assert_eq!(config.measure_name("instructions"), "instructions");
+        assert_eq!(config.include, ["^parse/"]);
+        assert_eq!(config.rename, ["^parser/=>parse/"]);
+    }
+
+    #[test]
+    fn test_load_rejects_unknown_keys() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join(PROJECT_CONFIG_FILE);
+        fs::write(&path, "projcet = \"demo\"\n").unwrap();
+        assert!(ProjectConfig::load(&path).is_err());
+    }
+}
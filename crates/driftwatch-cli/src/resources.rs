@@ -0,0 +1,144 @@
+use anyhow::Result;
+use std::process::{Child, ExitStatus};
+use std::time::{Duration, Instant};
+
+use crate::api::MetricInput;
+
+/// Synthetic benchmark the resource usage of the whole command is submitted
+/// under.
+pub const BENCHMARK: &str = "driftwatch/run";
+
+/// What the benchmark command used, including the descendants it waited for.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ResourceUsage {
+    pub wall_time: Duration,
+    pub user_time: Duration,
+    pub system_time: Duration,
+    pub peak_rss_bytes: u64,
+    pub voluntary_context_switches: u64,
+    pub involuntary_context_switches: u64,
+}
+
+impl ResourceUsage {
+    /// One metric per resource, as measures of [`BENCHMARK`].
+    pub fn metrics(&self) -> Vec<MetricInput> {
+        let metric = |measure: &str, units: &str, value: f64| MetricInput {
+            benchmark: BENCHMARK.to_string(),
+            measure: measure.to_string(),
+            units: Some(units.to_string()),
+            higher_is_better: None,
+            value,
+            lower_value: None,
+            upper_value: None,
+        };
+        vec![
+            metric("wall-time", "s", self.wall_time.as_secs_f64()),
+            metric("user-time", "s", self.user_time.as_secs_f64()),
+            metric("system-time", "s", self.system_time.as_secs_f64()),
+            metric("peak-rss", "bytes", self.peak_rss_bytes as f64),
+            metric(
+                "voluntary-context-switches",
+                "count",
+                self.voluntary_context_switches as f64,
+            ),
+            metric(
+                "involuntary-context-switches",
+                "count",
+                self.involuntary_context_switches as f64,
+            ),
+        ]
+    }
+}
+
+/// Waits for `child` to exit, started at `started`, and returns its exit
+/// status and the resources it used, as reported by `wait4`.
+#[cfg(target_os = "linux")]
+pub fn wait(child: &Child, started: Instant) -> Result<(ExitStatus, ResourceUsage)> {
+    use anyhow::Context;
+    use std::os::unix::process::ExitStatusExt;
+
+    let pid = child.id() as libc::pid_t;
+    let mut status = 0;
+    // SAFETY: rusage is plain data, fully written by a successful wait4
+    let mut rusage: libc::rusage = unsafe { std::mem::zeroed() };
+    loop {
+        // SAFETY: both pointers are valid for the duration of the call
+        let waited = unsafe { libc::wait4(pid, &mut status, 0, &mut rusage) };
+        if waited == pid {
+            break;
+        }
+        let err = std::io::Error::last_os_error();
+        if err.kind() != std::io::ErrorKind::Interrupted {
+            return Err(err).context("Failed to wait for benchmark command");
+        }
+    }
+    let wall_time = started.elapsed();
+
+    let timeval = |tv: libc::timeval| {
+        Duration::from_secs(tv.tv_sec as u64) + Duration::from_micros(tv.tv_usec as u64)
+    };
+    let usage = ResourceUsage {
+        wall_time,
+        user_time: timeval(rusage.ru_utime),
+        system_time: timeval(rusage.ru_stime),
+        // Kilobytes on Linux
+        peak_rss_bytes: rusage.ru_maxrss as u64 * 1024,
+        voluntary_context_switches: rusage.ru_nvcsw as u64,
+        involuntary_context_switches: rusage.ru_nivcsw as u64,
+    };
+    Ok((ExitStatus::from_raw(status), usage))
+}
+
+#[cfg(not(target_os = "linux"))]
+pub fn wait(_child: &Child, _started: Instant) -> Result<(ExitStatus, ResourceUsage)> {
+    anyhow::bail!("--measure-resources is only supported on Linux")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_metrics() {
+        let usage = ResourceUsage {
+            wall_time: Duration::from_millis(2500),
+            user_time: Duration::from_secs(4),
+            system_time: Duration::from_millis(250),
+            peak_rss_bytes: 64 << 20,
+            voluntary_context_switches: 12,
+            involuntary_context_switches: 3,
+        };
+        let metrics = usage.metrics();
+        assert!(metrics.iter().all(|m| m.benchmark == BENCHMARK));
+        let measures: Vec<_> = metrics
+            .iter()
+            .map(|m| (m.measure.as_str(), m.units.as_deref().unwrap(), m.value))
+            .collect();
+        assert_eq!(
+            measures,
+            [
+                ("wall-time", "s", 2.5),
+                ("user-time", "s", 4.0),
+                ("system-time", "s", 0.25),
+                ("peak-rss", "bytes", 67108864.0),
+                ("voluntary-context-switches", "count", 12.0),
+                ("involuntary-context-switches", "count", 3.0),
+            ]
+        );
+    }
+
+    #[cfg(target_os = "linux")]
+    #[test]
+    #[allow(clippy::zombie_processes)] // reaped by wait4
+    fn test_wait_measures_the_child() {
+        let started = Instant::now();
+        let child = std::process::Command::new("sh")
+            .args(["-c", "exit 3"])
+            .spawn()
+            .unwrap();
+        let (status, usage) = wait(&child, started).unwrap();
+        assert_eq!(status.code(), Some(3));
+        assert!(usage.peak_rss_bytes > 0);
+        assert!(usage.wall_time > Duration::ZERO);
+    }
+}
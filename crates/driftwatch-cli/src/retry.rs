@@ -0,0 +1,165 @@
+use anyhow::Result;
+use rand::Rng;
+use std::future::Future;
+use std::time::Duration;
+
+use crate::api::HttpStatusError;
+use crate::output::progress;
+
+/// Retries of a failed submission before giving up.
+pub const DEFAULT_RETRIES: u32 = 4;
+
+/// Exponential backoff between attempts: `base`, then twice as long each time
+/// up to `max`, each delay randomly shortened by up to half.
+#[derive(Debug, Clone, Copy)]
+pub struct Backoff {
+    pub retries: u32,
+    pub base: Duration,
+    pub max: Duration,
+}
+
+impl Backoff {
+    pub fn new(retries: u32) -> Self {
+        Self {
+            retries,
+            base: Duration::from_secs(1),
+            max: Duration::from_secs(30),
+        }
+    }
+
+    fn delay(&self, retry: u32) -> Duration {
+        let full = self
+            .base
+            .saturating_mul(2u32.saturating_pow(retry))
+            .min(self.max);
+        full.mul_f64(rand::thread_rng().gen_range(0.5..=1.0))
+    }
+
+    /// Runs `attempt` until it succeeds, fails with an error that is not
+    /// [transient](is_transient), or has been retried `retries` times.
+    pub async fn run<T, F, Fut>(&self, what: &str, mut attempt: F) -> Result<T>
+    where
+        F: FnMut() -> Fut,
+        Fut: Future<Output = Result<T>>,
+    {
+        let mut retry = 0;
+        loop {
+            match attempt().await {
+                Err(e) if retry < self.retries && is_transient(&e) => {
+                    let delay = self.delay(retry);
+                    retry += 1;
+                    progress!(
+                        "  {} failed ({:#}), retrying in {:.1}s ({}/{})",
+                        what,
+                        e,
+                        delay.as_secs_f64(),
+                        retry,
+                        self.retries
+                    );
+                    tokio::time::sleep(delay).await;
+                }
+                result => return result,
+            }
+        }
+    }
+}
+
+/// Whether an API call may succeed when repeated: the server could not be
+/// reached, timed out, or a gateway in front of it answered 502, 503 or 504.
+/// Other failures, including a 500, may come after the server acted on the
+/// request, so they are not retried.
+pub fn is_transient(error: &anyhow::Error) -> bool {
+    error.chain().any(|cause| {
+        if let Some(e) = cause.downcast_ref::<reqwest::Error>() {
+            return e.is_connect() || e.is_timeout() || e.status().is_some_and(is_transient_status);
+        }
+        cause
+            .downcast_ref::<HttpStatusError>()
+            .is_some_and(|e| is_transient_status(e.0))
+    })
+}
+
+fn is_transient_status(status: reqwest::StatusCode) -> bool {
+    matches!(
+        status,
+        reqwest::StatusCode::BAD_GATEWAY
+            | reqwest::StatusCode::SERVICE_UNAVAILABLE
+            | reqwest::StatusCode::GATEWAY_TIMEOUT
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use reqwest::StatusCode;
+    use std::cell::Cell;
+
+    fn instant(retries: u32) -> Backoff {
+        Backoff {
+            retries,
+            base: Duration::ZERO,
+            max: Duration::ZERO,
+        }
+    }
+
+    #[test]
+    fn test_delay_doubles_up_to_max() {
+        let backoff = Backoff::new(10);
+        for (retry, full) in [(0, 1), (1, 2), (3, 8), (5, 30), (31, 30)] {
+            let delay = backoff.delay(retry);
+            let full = Duration::from_secs(full);
+            assert!(delay >= full / 2 && delay <= full, "{:?}", delay);
+        }
+    }
+
+    #[test]
+    fn test_is_transient() {
+        let status = |s| anyhow::Error::new(HttpStatusError(s)).context("Failed");
+        assert!(is_transient(&status(StatusCode::BAD_GATEWAY)));
+        assert!(is_transient(&status(StatusCode::GATEWAY_TIMEOUT)));
+        assert!(!is_transient(&status(StatusCode::INTERNAL_SERVER_ERROR)));
+        assert!(!is_transient(&status(StatusCode::UNAUTHORIZED)));
+        assert!(!is_transient(&anyhow::anyhow!(
+            "GraphQL error: no such project"
+        )));
+    }
+
+    #[tokio::test]
+    async fn test_run_retries_transient_errors_only() {
+        let calls = Cell::new(0);
+        let result = instant(3)
+            .run("Submitting", || {
+                calls.set(calls.get() + 1);
+                let n = calls.get();
+                async move {
+                    if n < 3 {
+                        Err(HttpStatusError(StatusCode::SERVICE_UNAVAILABLE).into())
+                    } else {
+                        Ok(n)
+                    }
+                }
+            })
+            .await;
+        assert_eq!(result.unwrap(), 3);
+
+        calls.set(0);
+        let result: Result<()> = instant(3)
+            .run("Submitting", || {
+                calls.set(calls.get() + 1);
+                async { Err(anyhow::anyhow!("GraphQL error: invalid input")) }
+            })
+            .await;
+        assert!(result.is_err());
+        assert_eq!(calls.get(), 1);
+
+        calls.set(0);
+        let result: Result<()> = instant(2)
+            .run("Submitting", || {
+                calls.set(calls.get() + 1);
+                async { Err(HttpStatusError(StatusCode::BAD_GATEWAY).into()) }
+            })
+            .await;
+        assert!(is_transient(&result.unwrap_err()));
+        assert_eq!(calls.get(), 3);
+    }
+}
@@ -0,0 +1,167 @@
+use anyhow::{Context, Result};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::api::CreateReportInput;
+
+/// A report that could not be submitted, kept until `driftwatch flush`.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct PendingReport {
+    pub created_at: DateTime<Utc>,
+    pub report: CreateReportInput,
+    /// Absolute paths, uploaded once the report is in
+    #[serde(default)]
+    pub flamegraphs: Vec<PathBuf>,
+}
+
+/// Directory of pending reports, one JSON file each.
+pub struct Spool {
+    dir: PathBuf,
+}
+
+impl Spool {
+    /// The spool in `dir`, or in the user's local data directory.
+    pub fn new(dir: Option<PathBuf>) -> Result<Self> {
+        let dir = match dir {
+            Some(dir) => dir,
+            None => dirs::data_local_dir()
+                .context("Could not determine data directory; pass --spool-dir")?
+                .join("driftwatch")
+                .join("spool"),
+        };
+        Ok(Self { dir })
+    }
+
+    pub fn dir(&self) -> &Path {
+        &self.dir
+    }
+
+    /// Writes `report` to the spool and returns its path.
+    pub fn save(&self, report: &PendingReport) -> Result<PathBuf> {
+        fs::create_dir_all(&self.dir)
+            .with_context(|| format!("Failed to create spool directory {}", self.dir.display()))?;
+        // The key is free text, so it goes into the name hashed
+        let key_hash = Sha256::digest(report.report.idempotency_key.as_bytes());
+        let name = format!(
+            "{}-{}.json",
+            report.created_at.format("%Y%m%dT%H%M%S%.3fZ"),
+            &hex::encode(key_hash)[..16]
+        );
+        let path = self.dir.join(name);
+        // Written under another name first so `flush` never reads half a file
+        let partial = path.with_extension("json.partial");
+        fs::write(&partial, serde_json::to_vec_pretty(report)?)
+            .with_context(|| format!("Failed to write {}", partial.display()))?;
+        fs::rename(&partial, &path)
+            .with_context(|| format!("Failed to write {}", path.display()))?;
+        Ok(path)
+    }
+
+    /// Every pending report, oldest first. Files that cannot be read are
+    /// skipped with a warning.
+    pub fn pending(&self) -> Result<Vec<(PathBuf, PendingReport)>> {
+        let entries = match fs::read_dir(&self.dir) {
+            Ok(entries) => entries,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(Vec::new()),
+            Err(e) => {
+                return Err(e).with_context(|| format!("Failed to read {}", self.dir.display()))
+            }
+        };
+
+        let mut paths: Vec<PathBuf> = entries
+            .filter_map(|e| e.ok().map(|e| e.path()))
+            .filter(|p| p.extension().is_some_and(|ext| ext == "json"))
+            .collect();
+        paths.sort();
+
+        let mut pending = Vec::new();
+        for path in paths {
+            let parsed = fs::read_to_string(&path)
+                .map_err(anyhow::Error::from)
+                .and_then(|s| Ok(serde_json::from_str::<PendingReport>(&s)?));
+            match parsed {
+                Ok(report) => pending.push((path, report)),
+                Err(e) => eprintln!("Warning: Skipping {}: {:#}", path.display(), e),
+            }
+        }
+        Ok(pending)
+    }
+
+    pub fn remove(&self, path: &Path) -> Result<()> {
+        fs::remove_file(path).with_context(|| format!("Failed to remove {}", path.display()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::api::MetricInput;
+
+    fn pending(key: &str, created_at: &str) -> PendingReport {
+        PendingReport {
+            created_at: created_at.parse().unwrap(),
+            report: CreateReportInput {
+                project_slug: "demo".to_string(),
+                branch: "main".to_string(),
+                testbed: "linux".to_string(),
+                git_hash: None,
+                pr_number: None,
+                base_branch: None,
+                ci_provider: None,
+                ci_run_url: None,
+                idempotency_key: key.to_string(),
+                testbed_fingerprint: None,
+                metrics: vec![MetricInput {
+                    benchmark: "parse".to_string(),
+                    measure: "latency".to_string(),
+                    units: Some("ns".to_string()),
+                    higher_is_better: None,
+                    value: 1.5,
+                    lower_value: None,
+                    upper_value: None,
+                }],
+            },
+            flamegraphs: vec![PathBuf::from("/tmp/flame.svg")],
+        }
+    }
+
+    #[test]
+    fn test_save_and_list_oldest_first() {
+        let dir = tempfile::tempdir().unwrap();
+        let spool = Spool::new(Some(dir.path().join("spool"))).unwrap();
+        assert!(spool.pending().unwrap().is_empty());
+
+        let newer = spool.save(&pending("b", "2026-10-18T12:00:00Z")).unwrap();
+        spool.save(&pending("a", "2026-10-17T12:00:00Z")).unwrap();
+        fs::write(dir.path().join("spool/garbage.json"), "{").unwrap();
+
+        let listed = spool.pending().unwrap();
+        let keys: Vec<_> = listed
+            .iter()
+            .map(|(_, r)| r.report.idempotency_key.as_str())
+            .collect();
+        assert_eq!(keys, ["a", "b"]);
+        assert_eq!(listed[0].1.report.metrics[0].value, 1.5);
+        assert_eq!(listed[0].1.flamegraphs, [PathBuf::from("/tmp/flame.svg")]);
+
+        spool.remove(&newer).unwrap();
+        assert_eq!(spool.pending().unwrap().len(), 1);
+    }
+
+    #[test]
+    fn test_save_with_key_unfit_for_a_file_name() {
+        let dir = tempfile::tempdir().unwrap();
+        let spool = Spool::new(Some(dir.path().to_path_buf())).unwrap();
+
+        let key = "github-actions:7-1-bench:../linux/x86_64";
+        let path = spool.save(&pending(key, "2026-10-18T12:00:00Z")).unwrap();
+        assert_eq!(path.parent(), Some(dir.path()));
+
+        let listed = spool.pending().unwrap();
+        assert_eq!(listed.len(), 1);
+        assert_eq!(listed[0].1.report.idempotency_key, key);
+    }
+}
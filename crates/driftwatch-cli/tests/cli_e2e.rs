@@ -26,6 +26,17 @@ hash_function           time:   [45.678 ms 46.789 ms 47.890 ms]
                         Performance has regressed.
 "#;
 
+/// Sample Bencher Metric Format output with two measures on one benchmark
+const BMF_OUTPUT: &str = r#"{
+    "parse/small": {
+        "latency": { "value": 830.5, "lower_value": 812.0, "upper_value": 851.2 },
+        "throughput": { "value": 1200000.0 }
+    },
+    "parse/large": {
+        "latency": { "value": 91234.0 }
+    }
+}"#;
+
 /// Get the path to the CLI binary
 fn cli_binary() -> std::path::PathBuf {
     // CARGO_MANIFEST_DIR is crates/driftwatch-cli, go up twice to workspace root
@@ -132,6 +143,33 @@ fn test_run_dry_run_parses_criterion_output() {
     assert!(stdout.contains("Dry run"), "Should indicate dry run mode");
 }
 
+#[test]
+fn test_run_append_to_ignores_idempotency_key_from_env() {
+    build_cli();
+
+    let script = create_mock_benchmark_script(CRITERION_OUTPUT);
+
+    let output = Command::new(cli_binary())
+        .args([
+            "run",
+            "-p",
+            "test-project",
+            "-b",
+            "main",
+            "--append-to",
+            "00000000-0000-0000-0000-000000000000",
+            "--dry-run",
+            script.to_str().unwrap(),
+        ])
+        .env("DRIFTWATCH_TOKEN", "fake-token-for-testing")
+        .env("DRIFTWATCH_IDEMPOTENCY_KEY", "run-7-attempt-1")
+        .output()
+        .expect("Failed to execute CLI");
+
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(output.status.success(), "stderr: {}", stderr);
+}
+
 #[test]
 fn test_run_dry_run_with_pr_flag() {
     build_cli();
@@ -240,6 +278,251 @@ fn test_run_dry_run_explicit_pr_overrides_env() {
     );
 }
 
+#[test]
+fn test_run_dry_run_detects_gitlab_merge_request() {
+    build_cli();
+
+    let script = create_mock_benchmark_script(CRITERION_OUTPUT);
+
+    let output = Command::new(cli_binary())
+        .args([
+            "run",
+            "-p",
+            "test-project",
+            "--dry-run",
+            script.to_str().unwrap(),
+        ])
+        .env("DRIFTWATCH_TOKEN", "fake-token-for-testing")
+        .env_remove("GITHUB_ACTIONS")
+        .env_remove("GITHUB_REF")
+        .env("GITLAB_CI", "true")
+        .env("CI_COMMIT_REF_NAME", "feature/faster")
+        .env("CI_MERGE_REQUEST_SOURCE_BRANCH_NAME", "feature/faster")
+        .env("CI_MERGE_REQUEST_TARGET_BRANCH_NAME", "develop")
+        .env("CI_MERGE_REQUEST_IID", "17")
+        .env("CI_COMMIT_SHA", "0123456789abcdef")
+        .output()
+        .expect("Failed to execute CLI");
+
+    let stdout = String::from_utf8_lossy(&output.stdout);
+
+    println!("stdout: {}", stdout);
+
+    assert!(stdout.contains("CI: gitlab"), "Should detect GitLab CI");
+    assert!(
+        stdout.contains("Branch: feature/faster"),
+        "Should use the merge request source branch, got: {}",
+        stdout
+    );
+    assert!(stdout.contains("Git hash: 0123456789abcdef"));
+    assert!(stdout.contains("PR: #17"));
+    assert!(stdout.contains("Base branch: develop"));
+}
+
+#[test]
+fn test_run_iterations_aggregate_with_warmup() {
+    build_cli();
+
+    // Each run reports 10 more than the previous one
+    let dir = tempfile::tempdir().unwrap();
+    let script = dir.path().join("bench.sh");
+    std::fs::write(
+        &script,
+        r#"#!/bin/sh
+n=$(( $(cat count 2>/dev/null || echo 0) + 1 ))
+echo $n > count
+echo "{\"counter\": {\"latency\": {\"value\": $((n * 10))}}}"
+"#,
+    )
+    .unwrap();
+
+    let output = Command::new(cli_binary())
+        .args([
+            "run",
+            "-p",
+            "test-project",
+            "--iterations",
+            "3",
+            "--warmup",
+            "--dry-run",
+            "sh",
+            script.to_str().unwrap(),
+        ])
+        .current_dir(dir.path())
+        .env("DRIFTWATCH_TOKEN", "fake-token-for-testing")
+        .output()
+        .expect("Failed to execute CLI");
+
+    let stdout = String::from_utf8_lossy(&output.stdout);
+
+    println!("stdout: {}", stdout);
+
+    assert!(stdout.contains("Iterations: 3 (mean, min-max bounds)"));
+    assert!(stdout.contains("Warmup run"));
+    assert!(stdout.contains("Run 3/3"));
+    // The warmup's 10 is discarded: mean of 20, 30 and 40
+    assert!(
+        stdout.contains("counter (latency) : 30.00 [20.00 - 40.00]"),
+        "Should aggregate the measured runs, got: {}",
+        stdout
+    );
+}
+
+#[test]
+fn test_run_dry_run_json_format_and_step_summary() {
+    build_cli();
+
+    let script = create_mock_benchmark_script(CRITERION_OUTPUT);
+    let dir = tempfile::tempdir().unwrap();
+    let step_summary = dir.path().join("summary.md");
+
+    let output = Command::new(cli_binary())
+        .args([
+            "run",
+            "-p",
+            "test-project",
+            "-b",
+            "main",
+            "--dry-run",
+            "--format",
+            "json",
+            script.to_str().unwrap(),
+        ])
+        .env("DRIFTWATCH_TOKEN", "fake-token-for-testing")
+        .env("GITHUB_STEP_SUMMARY", &step_summary)
+        .output()
+        .expect("Failed to execute CLI");
+
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let stderr = String::from_utf8_lossy(&output.stderr);
+
+    println!("stdout: {}", stdout);
+    println!("stderr: {}", stderr);
+
+    assert!(output.status.success());
+    // Progress moves to stderr so stdout is only the document
+    assert!(stderr.contains("Found 3 benchmark results"));
+    let json: serde_json::Value = serde_json::from_str(&stdout).expect("stdout should be JSON");
+    assert_eq!(json["schema_version"], 1);
+    assert_eq!(json["project"], "test-project");
+    assert_eq!(json["results"].as_array().unwrap().len(), 3);
+    assert_eq!(json["results"][0]["benchmark"], "fibonacci/10");
+    assert_eq!(json["results"][0]["status"], "unchecked");
+    assert_eq!(json["submission"], "dry_run");
+    assert!(json["verdict"].is_null());
+
+    let markdown = std::fs::read_to_string(&step_summary).unwrap();
+    assert!(
+        markdown.contains("| fibonacci/10 | latency |"),
+        "{}",
+        markdown
+    );
+    assert!(markdown.contains("_Dry run"));
+}
+
+#[test]
+fn test_run_rejects_output_with_text_format() {
+    build_cli();
+
+    let dir = tempfile::tempdir().unwrap();
+    let file = dir.path().join("results.txt");
+
+    let output = Command::new(cli_binary())
+        .args([
+            "run",
+            "-p",
+            "test-project",
+            "--dry-run",
+            "--output",
+            file.to_str().unwrap(),
+            "--",
+            "true",
+        ])
+        .output()
+        .expect("Failed to execute CLI");
+
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert_eq!(output.status.code(), Some(2), "stderr: {}", stderr);
+    assert!(stderr.contains("'--format text'"), "stderr: {}", stderr);
+    assert!(!file.exists());
+}
+
+#[test]
+fn test_run_spools_report_when_server_is_unreachable() {
+    build_cli();
+
+    let script = create_mock_benchmark_script(CRITERION_OUTPUT);
+    let dir = tempfile::tempdir().unwrap();
+    let spool = dir.path().join("spool");
+    let flamegraph = dir.path().join("flame.svg");
+    std::fs::write(&flamegraph, "<svg/>").unwrap();
+
+    // Nothing listens on port 1
+    let output = Command::new(cli_binary())
+        .args([
+            "run",
+            "-p",
+            "test-project",
+            "-b",
+            "main",
+            "--retries",
+            "0",
+            "--flamegraph",
+            flamegraph.to_str().unwrap(),
+            script.to_str().unwrap(),
+        ])
+        .env("DRIFTWATCH_TOKEN", "fake-token-for-testing")
+        .env("DRIFTWATCH_API_URL", "http://127.0.0.1:1")
+        .env("DRIFTWATCH_SPOOL_DIR", &spool)
+        .env("DRIFTWATCH_IDEMPOTENCY_KEY", "run-7-attempt-1")
+        .output()
+        .expect("Failed to execute CLI");
+
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let stderr = String::from_utf8_lossy(&output.stderr);
+
+    println!("stdout: {}", stdout);
+    println!("stderr: {}", stderr);
+
+    assert!(output.status.success(), "Spooling should not fail the run");
+    assert!(stderr.contains("Could not submit results"));
+    assert!(stdout.contains("Report spooled to"));
+
+    let files: Vec<_> = std::fs::read_dir(&spool).unwrap().collect();
+    assert_eq!(files.len(), 1);
+    let pending: serde_json::Value =
+        serde_json::from_slice(&std::fs::read(files[0].as_ref().unwrap().path()).unwrap()).unwrap();
+    assert_eq!(pending["report"]["idempotencyKey"], "run-7-attempt-1");
+    assert_eq!(pending["report"]["projectSlug"], "test-project");
+    assert_eq!(pending["report"]["metrics"].as_array().unwrap().len(), 3);
+    assert_eq!(pending["flamegraphs"][0], flamegraph.to_str().unwrap());
+
+    let output = Command::new(cli_binary())
+        .args(["flush", "--dry-run"])
+        .env("DRIFTWATCH_SPOOL_DIR", &spool)
+        .output()
+        .expect("Failed to execute CLI");
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(stdout.contains("1 pending report(s)"), "{}", stdout);
+    assert!(
+        stdout.contains("run-7-attempt-1 test-project main/"),
+        "{}",
+        stdout
+    );
+
+    // Still unreachable: the report stays spooled
+    let output = Command::new(cli_binary())
+        .args(["flush", "--retries", "0"])
+        .env("DRIFTWATCH_TOKEN", "fake-token-for-testing")
+        .env("DRIFTWATCH_API_URL", "http://127.0.0.1:1")
+        .env("DRIFTWATCH_SPOOL_DIR", &spool)
+        .output()
+        .expect("Failed to execute CLI");
+    assert!(!output.status.success());
+    assert!(String::from_utf8_lossy(&output.stderr).contains("still pending"));
+    assert_eq!(std::fs::read_dir(&spool).unwrap().count(), 1);
+}
+
 #[test]
 fn test_run_no_benchmarks_found() {
     build_cli();
@@ -346,6 +629,128 @@ fn test_run_displays_testbed_defaulting_to_os() {
     );
 }
 
+#[test]
+#[cfg(target_os = "linux")]
+fn test_run_displays_hardware_fingerprint() {
+    build_cli();
+
+    let script = create_mock_benchmark_script(CRITERION_OUTPUT);
+
+    let run = |extra: &[&str]| {
+        let output = Command::new(cli_binary())
+            .args(["run", "-p", "test-project", "--dry-run"])
+            .args(extra)
+            .arg(script.to_str().unwrap())
+            .env("DRIFTWATCH_TOKEN", "fake-token-for-testing")
+            .output()
+            .expect("Failed to execute CLI");
+        String::from_utf8_lossy(&output.stdout).to_string()
+    };
+
+    let stdout = run(&[]);
+    assert!(
+        stdout.contains("Hardware:") && stdout.contains(" core"),
+        "Should display the hardware fingerprint, got: {}",
+        stdout
+    );
+
+    let stdout = run(&["--no-fingerprint"]);
+    assert!(!stdout.contains("Hardware:"), "got: {}", stdout);
+}
+
+#[test]
+#[cfg(target_os = "linux")]
+fn test_run_measure_resources() {
+    build_cli();
+
+    let script = create_mock_benchmark_script(CRITERION_OUTPUT);
+
+    let output = Command::new(cli_binary())
+        .args([
+            "run",
+            "-p",
+            "test-project",
+            "--dry-run",
+            "--measure-resources",
+            script.to_str().unwrap(),
+        ])
+        .env("DRIFTWATCH_TOKEN", "fake-token-for-testing")
+        .output()
+        .expect("Failed to execute CLI");
+
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(output.status.success(), "stdout: {}", stdout);
+    for measure in ["wall-time", "user-time", "system-time", "peak-rss"] {
+        assert!(
+            stdout.contains(&format!("driftwatch/run ({})", measure)),
+            "Should report {}, got: {}",
+            measure,
+            stdout
+        );
+    }
+}
+
+#[test]
+#[cfg(unix)]
+fn test_run_passes_on_command_failure() {
+    build_cli();
+
+    let output = Command::new(cli_binary())
+        .args([
+            "run",
+            "-p",
+            "test-project",
+            "--dry-run",
+            "--",
+            "echo 'test parse ... bench: 100 ns/iter (+/- 5)'; exit 7",
+        ])
+        .env("DRIFTWATCH_TOKEN", "fake-token-for-testing")
+        .output()
+        .expect("Failed to execute CLI");
+
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert_eq!(output.status.code(), Some(7), "stderr: {}", stderr);
+    // The command's output is shown as it runs, but not parsed
+    assert!(stdout.contains("test parse ... bench"), "got: {}", stdout);
+    assert!(!stdout.contains("Found 1 benchmark results"));
+    assert!(stderr.contains("Benchmark command exited with code 7"));
+}
+
+#[test]
+#[cfg(unix)]
+fn test_run_timeout_with_submit_on_failure() {
+    build_cli();
+
+    let started = std::time::Instant::now();
+    let output = Command::new(cli_binary())
+        .args([
+            "run",
+            "-p",
+            "test-project",
+            "--dry-run",
+            "--timeout",
+            "1s",
+            "--submit-on-failure",
+            "--",
+            "echo 'test parse ... bench: 100 ns/iter (+/- 5)'; sleep 30",
+        ])
+        .env("DRIFTWATCH_TOKEN", "fake-token-for-testing")
+        .output()
+        .expect("Failed to execute CLI");
+
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(started.elapsed() < std::time::Duration::from_secs(20));
+    assert_eq!(output.status.code(), Some(124), "stderr: {}", stderr);
+    assert!(stderr.contains("timed out after 1s"), "got: {}", stderr);
+    assert!(
+        stdout.contains("parse (latency) : 100.00 ns"),
+        "Should keep the partial results, got: {}",
+        stdout
+    );
+}
+
 #[test]
 fn test_run_custom_testbed() {
     build_cli();
@@ -411,3 +816,492 @@ fn test_run_with_git_hash() {
         stdout
     );
 }
+
+#[test]
+fn test_run_dry_run_bmf_file() {
+    build_cli();
+
+    let path = std::env::temp_dir().join(format!(
+        "driftwatch_test_{}.json",
+        std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .unwrap()
+            .as_nanos()
+    ));
+    std::fs::write(&path, BMF_OUTPUT).expect("Failed to write BMF file");
+
+    let output = Command::new(cli_binary())
+        .args([
+            "run",
+            "-p",
+            "test-project",
+            "--file",
+            path.to_str().unwrap(),
+            "--dry-run",
+        ])
+        .env("DRIFTWATCH_TOKEN", "fake-token-for-testing")
+        .output()
+        .expect("Failed to execute CLI");
+
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let stderr = String::from_utf8_lossy(&output.stderr);
+
+    println!("stdout: {}", stdout);
+    println!("stderr: {}", stderr);
+
+    assert!(output.status.success(), "CLI should succeed: {}", stderr);
+    assert!(
+        stdout.contains("Found 3 benchmark results"),
+        "Should find one result per measure, got: {}",
+        stdout
+    );
+    assert!(
+        stdout.contains("parse/small (latency)"),
+        "Should contain the latency measure"
+    );
+    assert!(
+        stdout.contains("parse/small (throughput)"),
+        "Should contain the throughput measure"
+    );
+    assert!(
+        stdout.contains("parse/large (latency)"),
+        "Should contain parse/large"
+    );
+}
+
+#[test]
+fn test_run_dry_run_multiple_files() {
+    build_cli();
+
+    let dir = tempfile::tempdir().unwrap();
+    std::fs::write(dir.path().join("bmf.json"), BMF_OUTPUT).unwrap();
+    std::fs::write(dir.path().join("criterion.txt"), CRITERION_OUTPUT).unwrap();
+    std::fs::write(dir.path().join("empty.txt"), "nothing here\n").unwrap();
+
+    let output = Command::new(cli_binary())
+        .args([
+            "run",
+            "-p",
+            "test-project",
+            "--file",
+            "bmf=bmf.json",
+            "--file",
+            "criterion=criterion.txt",
+            "--file",
+            "empty.txt",
+            "--dry-run",
+        ])
+        .current_dir(dir.path())
+        .env("DRIFTWATCH_TOKEN", "fake-token-for-testing")
+        .output()
+        .expect("Failed to execute CLI");
+
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let stderr = String::from_utf8_lossy(&output.stderr);
+
+    println!("stdout: {}", stdout);
+    println!("stderr: {}", stderr);
+
+    assert!(output.status.success(), "CLI should succeed: {}", stderr);
+    assert!(stdout.contains("parse/small (throughput)"));
+    assert!(stdout.contains("fibonacci/10 (latency)"));
+    assert!(
+        stderr.contains("No benchmark results found in empty.txt"),
+        "Should warn about the empty file, got: {}",
+        stderr
+    );
+
+    // The same result in two files
+    let output = Command::new(cli_binary())
+        .args([
+            "run",
+            "-p",
+            "test-project",
+            "--file",
+            "bmf=bmf.json",
+            "--file",
+            "criterion=criterion.txt",
+            "--file",
+            "bmf=bmf.json",
+            "--dry-run",
+        ])
+        .current_dir(dir.path())
+        .env("DRIFTWATCH_TOKEN", "fake-token-for-testing")
+        .output()
+        .expect("Failed to execute CLI");
+
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(!output.status.success());
+    assert!(
+        stderr.contains("is in both bmf.json and bmf.json"),
+        "Should reject the duplicate, got: {}",
+        stderr
+    );
+}
+
+#[test]
+fn test_run_dry_run_filters_and_renames() {
+    build_cli();
+
+    let dir = tempfile::tempdir().unwrap();
+    std::fs::write(dir.path().join("bmf.json"), BMF_OUTPUT).unwrap();
+
+    let output = Command::new(cli_binary())
+        .args([
+            "run",
+            "-p",
+            "test-project",
+            "--file",
+            "bmf.json",
+            "--exclude",
+            "/large$",
+            "--rename",
+            "^parse/(.*)=>parser/$1",
+            "--dry-run",
+        ])
+        .current_dir(dir.path())
+        .env("DRIFTWATCH_TOKEN", "fake-token-for-testing")
+        .output()
+        .expect("Failed to execute CLI");
+
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let stderr = String::from_utf8_lossy(&output.stderr);
+
+    println!("stdout: {}", stdout);
+    println!("stderr: {}", stderr);
+
+    assert!(output.status.success(), "CLI should succeed: {}", stderr);
+    assert!(stdout.contains("Excluded 1 of 3 benchmark results"));
+    assert!(stdout.contains("parser/small (latency)"));
+    assert!(stdout.contains("parser/small (throughput)"));
+    assert!(
+        !stdout.contains("large"),
+        "Should drop parse/large: {}",
+        stdout
+    );
+
+    let output = Command::new(cli_binary())
+        .args([
+            "run",
+            "-p",
+            "test-project",
+            "--file",
+            "bmf.json",
+            "--rename",
+            "^parse/.*=>parse",
+            "--dry-run",
+        ])
+        .current_dir(dir.path())
+        .env("DRIFTWATCH_TOKEN", "fake-token-for-testing")
+        .output()
+        .expect("Failed to execute CLI");
+
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(!output.status.success());
+    assert!(
+        stderr.contains("Rename rules map both") && stderr.contains("to 'parse'"),
+        "Should reject colliding renames, got: {}",
+        stderr
+    );
+}
+
+#[test]
+fn test_run_dry_run_bmf_stdin() {
+    use std::io::Write;
+    use std::process::Stdio;
+
+    build_cli();
+
+    let mut child = Command::new(cli_binary())
+        .args(["run", "-p", "test-project", "--file", "-", "--dry-run"])
+        .env("DRIFTWATCH_TOKEN", "fake-token-for-testing")
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .spawn()
+        .expect("Failed to execute CLI");
+
+    child
+        .stdin
+        .take()
+        .unwrap()
+        .write_all(BMF_OUTPUT.as_bytes())
+        .unwrap();
+    let output = child.wait_with_output().expect("Failed to wait for CLI");
+
+    let stdout = String::from_utf8_lossy(&output.stdout);
+
+    println!("stdout: {}", stdout);
+
+    assert!(
+        stdout.contains("Found 3 benchmark results"),
+        "Should read BMF from stdin, got: {}",
+        stdout
+    );
+}
+
+#[test]
+fn test_run_custom_adapter_from_project_config() {
+    build_cli();
+
+    let dir = tempfile::tempdir().unwrap();
+    std::fs::write(
+        dir.path().join("driftwatch.toml"),
+        r#"
+[custom]
+measure = "latency"
+units = "ns"
+regex = '^(?P<name>\S+) took (?P<value>[0-9.]+)(?P<unit>us|ms)$'
+unit_scale = { us = 1000.0, ms = 1000000.0 }
+"#,
+    )
+    .unwrap();
+    // The config is found from a subdirectory too
+    let subdir = dir.path().join("scripts");
+    std::fs::create_dir(&subdir).unwrap();
+    std::fs::write(
+        subdir.join("results.txt"),
+        "warmup\nload took 1.5ms\nsave took 20us\n",
+    )
+    .unwrap();
+
+    let output = Command::new(cli_binary())
+        .args([
+            "run",
+            "-p",
+            "test-project",
+            "--adapter",
+            "custom",
+            "--file",
+            "results.txt",
+            "--dry-run",
+        ])
+        .current_dir(&subdir)
+        .env("DRIFTWATCH_TOKEN", "fake-token-for-testing")
+        .output()
+        .expect("Failed to execute CLI");
+
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let stderr = String::from_utf8_lossy(&output.stderr);
+
+    println!("stdout: {}", stdout);
+    println!("stderr: {}", stderr);
+
+    assert!(output.status.success(), "CLI should succeed: {}", stderr);
+    assert!(
+        stdout.contains("Found 2 benchmark results"),
+        "Should parse both lines, got: {}",
+        stdout
+    );
+    assert!(
+        stdout.contains("load (latency) : 1500000.00 ns"),
+        "Should scale ms to ns, got: {}",
+        stdout
+    );
+}
+
+#[test]
+fn test_run_uses_project_config_defaults() {
+    build_cli();
+
+    let dir = tempfile::tempdir().unwrap();
+    std::fs::write(dir.path().join("results.json"), BMF_OUTPUT).unwrap();
+    std::fs::write(
+        dir.path().join("driftwatch.toml"),
+        r#"
+project = "config-project"
+testbed = "config-testbed"
+exclude = ["large"]
+
+[commands]
+bench = "cat results.json"
+
+[measures]
+latency = "wall-time"
+"#,
+    )
+    .unwrap();
+
+    // The command runs next to driftwatch.toml, not in the subdirectory
+    let subdir = dir.path().join("src");
+    std::fs::create_dir(&subdir).unwrap();
+
+    let output = Command::new(cli_binary())
+        .args([
+            "run",
+            "--bench",
+            "bench",
+            "--testbed",
+            "flag-testbed",
+            "--dry-run",
+        ])
+        .current_dir(&subdir)
+        .env("DRIFTWATCH_TOKEN", "fake-token-for-testing")
+        .output()
+        .expect("Failed to execute CLI");
+
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let stderr = String::from_utf8_lossy(&output.stderr);
+
+    println!("stdout: {}", stdout);
+    println!("stderr: {}", stderr);
+
+    assert!(output.status.success(), "CLI should succeed: {}", stderr);
+    assert!(stdout.contains("Project: config-project"));
+    // Flags override the config file
+    assert!(stdout.contains("Testbed: flag-testbed"));
+    assert!(
+        stdout.contains("Found 2 benchmark results"),
+        "Should drop the excluded benchmark, got: {}",
+        stdout
+    );
+    assert!(stdout.contains("parse/small (wall-time)"));
+    // Only in the command's own output, which is shown as it runs
+    assert!(!stdout.contains("parse/large ("));
+}
+
+#[test]
+fn test_run_requires_project() {
+    build_cli();
+
+    let dir = tempfile::tempdir().unwrap();
+    let output = Command::new(cli_binary())
+        .args(["run", "--dry-run", "--", "true"])
+        .current_dir(dir.path())
+        .env("DRIFTWATCH_TOKEN", "fake-token-for-testing")
+        .output()
+        .expect("Failed to execute CLI");
+
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(!output.status.success());
+    assert!(
+        stderr.contains("No project given"),
+        "Should explain the missing project, got: {}",
+        stderr
+    );
+}
+
+#[test]
+fn test_config_profiles() {
+    build_cli();
+
+    let config_home = tempfile::tempdir().unwrap();
+    let driftwatch = |args: &[&str]| {
+        Command::new(cli_binary())
+            .args(args)
+            .env("XDG_CONFIG_HOME", config_home.path())
+            .env_remove("DRIFTWATCH_TOKEN")
+            .env_remove("DRIFTWATCH_PROFILE")
+            .env_remove("DRIFTWATCH_API_URL")
+            .output()
+            .expect("Failed to execute CLI")
+    };
+
+    assert!(
+        driftwatch(&["config", "set", "--grpc-url", "http://prod:50051"])
+            .status
+            .success()
+    );
+    assert!(driftwatch(&[
+        "--profile",
+        "staging",
+        "config",
+        "set",
+        "--grpc-url",
+        "http://staging:50051"
+    ])
+    .status
+    .success());
+
+    let output = driftwatch(&["auth", "status"]);
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    println!("stdout: {}", stdout);
+    assert!(stdout.contains("* default"), "default should be active");
+    assert!(stdout.contains("  staging"));
+    assert!(stdout.contains("http://staging:50051"));
+
+    assert!(driftwatch(&["config", "use", "staging"]).status.success());
+    let output = driftwatch(&["config", "show"]);
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(stdout.contains("Profile: staging"), "got: {}", stdout);
+    assert!(stdout.contains("gRPC URL: http://staging:50051"));
+
+    assert!(!driftwatch(&["config", "use", "missing"]).status.success());
+
+    assert!(driftwatch(&["auth", "logout", "--profile", "staging"])
+        .status
+        .success());
+    let output = driftwatch(&["auth", "status"]);
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(stdout.contains("* default"), "got: {}", stdout);
+    assert!(!stdout.contains("staging"));
+}
+
+#[test]
+fn test_threshold_apply_validates_file() {
+    build_cli();
+
+    let dir = tempfile::tempdir().unwrap();
+    std::fs::write(
+        dir.path().join("thresholds.toml"),
+        r#"
+project = "test-project"
+
+[[threshold]]
+measure = "latency"
+branch = "main"
+upper_boundary = 10.0
+
+[[threshold]]
+measure = "latency"
+branch = "main"
+lower_boundary = 5.0
+"#,
+    )
+    .unwrap();
+
+    // Fails on the file before contacting the server
+    let output = Command::new(cli_binary())
+        .args(["threshold", "apply", "-f", "thresholds.toml", "--dry-run"])
+        .current_dir(dir.path())
+        .env("DRIFTWATCH_TOKEN", "fake-token-for-testing")
+        .env("DRIFTWATCH_API_URL", "http://127.0.0.1:9")
+        .output()
+        .expect("Failed to execute CLI");
+
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    println!("stderr: {}", stderr);
+
+    assert!(!output.status.success());
+    assert!(
+        stderr.contains("latency on main / any testbed is defined more than once"),
+        "Should reject the duplicate threshold, got: {}",
+        stderr
+    );
+}
+
+#[test]
+fn test_threshold_create_requires_boundary() {
+    build_cli();
+
+    let output = Command::new(cli_binary())
+        .args([
+            "threshold",
+            "create",
+            "-p",
+            "test-project",
+            "--measure",
+            "latency",
+        ])
+        .env("DRIFTWATCH_TOKEN", "fake-token-for-testing")
+        .env("DRIFTWATCH_API_URL", "http://127.0.0.1:9")
+        .output()
+        .expect("Failed to execute CLI");
+
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(!output.status.success());
+    assert!(
+        stderr.contains("--upper-boundary, --lower-boundary or both"),
+        "Should ask for a boundary, got: {}",
+        stderr
+    );
+}